@@ -1,6 +1,9 @@
 -- ============================================
 -- Kioku Desktop Database Schema
 -- ============================================
+--
+-- content_id columns hold the stable ID of content from an imported file, so
+-- re-importing updates it in place; NULL means the row's own id is used.
 
 -- ============================================
 -- Core: Users & App State
@@ -31,12 +34,26 @@ CREATE TABLE IF NOT EXISTS courses (
     user_id TEXT NOT NULL,
     name TEXT NOT NULL,
     description TEXT,
+    content_id TEXT,
+    version INTEGER NOT NULL DEFAULT 1,  -- Set by the course author; updates must be newer
     created_at TEXT NOT NULL,
     updated_at TEXT NOT NULL,
     FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
     UNIQUE(user_id, name)
 );
 
+-- Groups of elective lessons: completing any required_count of them is enough
+CREATE TABLE IF NOT EXISTS elective_groups (
+    id TEXT PRIMARY KEY,
+    course_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    required_count INTEGER NOT NULL DEFAULT 1,
+    content_id TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (course_id) REFERENCES courses(id) ON DELETE CASCADE
+);
+
 -- Lessons within a course
 CREATE TABLE IF NOT EXISTS lessons (
     id TEXT PRIMARY KEY,
@@ -44,21 +61,37 @@ CREATE TABLE IF NOT EXISTS lessons (
     title TEXT NOT NULL,
     description TEXT,
     position INTEGER NOT NULL DEFAULT 0,
+    content_id TEXT,
+    requires_previous INTEGER NOT NULL DEFAULT 0,  -- Locked until the lesson before it is complete
+    due_day INTEGER,  -- Calendar pacing: days after the learner's start date the lesson is due
+    is_optional INTEGER NOT NULL DEFAULT 0,  -- Not needed to complete the course
+    elective_group_id TEXT REFERENCES elective_groups(id) ON DELETE SET NULL,
     created_at TEXT NOT NULL,
     updated_at TEXT NOT NULL,
     FOREIGN KEY (course_id) REFERENCES courses(id) ON DELETE CASCADE
 );
 
+-- Lessons that must be complete before another lesson unlocks
+CREATE TABLE IF NOT EXISTS lesson_prerequisites (
+    lesson_id TEXT NOT NULL,
+    prerequisite_id TEXT NOT NULL,
+    PRIMARY KEY (lesson_id, prerequisite_id),
+    FOREIGN KEY (lesson_id) REFERENCES lessons(id) ON DELETE CASCADE,
+    FOREIGN KEY (prerequisite_id) REFERENCES lessons(id) ON DELETE CASCADE
+);
+
 -- Lesson items (decks/quizzes within a lesson with optional requirements)
 CREATE TABLE IF NOT EXISTS lesson_items (
     id TEXT PRIMARY KEY,
     lesson_id TEXT NOT NULL,
-    item_type TEXT NOT NULL,  -- 'deck' or 'quiz'
-    item_id TEXT NOT NULL,    -- FK to decks or quizzes (may be NULL if not yet imported)
+    item_type TEXT NOT NULL,  -- 'deck', 'quiz', 'page' or 'notebook'
+    item_id TEXT,             -- FK to decks, quizzes, pages or notebooks (NULL if not yet imported)
     item_name TEXT NOT NULL,  -- Name for matching during import
-    requirement_type TEXT,    -- NULL, 'study', 'review', 'complete', 'min_score'
-    requirement_value INTEGER, -- For min_score: the percentage required (e.g., 80)
+    requirement_type TEXT,    -- NULL, 'study', 'review', 'complete', 'min_score', 'read'
+    requirement_value INTEGER, -- min_score: percentage; study: cards in one session; review: times opened; read: seconds per page
     position INTEGER NOT NULL DEFAULT 0,
+    content_id TEXT,
+    target_content_id TEXT,  -- Content ID of what the item points to, for relinking when it goes missing
     created_at TEXT NOT NULL,
     FOREIGN KEY (lesson_id) REFERENCES lessons(id) ON DELETE CASCADE
 );
@@ -83,6 +116,36 @@ CREATE TABLE IF NOT EXISTS lesson_progress (
     UNIQUE(user_id, lesson_item_id)
 );
 
+-- A learner's enrollment in a course, which sets the pace of its lessons
+CREATE TABLE IF NOT EXISTS course_enrollments (
+    user_id TEXT NOT NULL,
+    course_id TEXT NOT NULL,
+    start_date TEXT NOT NULL,  -- YYYY-MM-DD in the learner's time zone
+    pacing_mode TEXT NOT NULL DEFAULT 'self_paced',  -- 'self_paced', 'calendar' or 'weekly'
+    lessons_per_week INTEGER,  -- Weekly pacing only
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (user_id, course_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (course_id) REFERENCES courses(id) ON DELETE CASCADE
+);
+
+-- Certificates for completed courses. Names and scores are copied in so a
+-- certificate re-exports exactly as issued, even after the course changes.
+CREATE TABLE IF NOT EXISTS certificates (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    course_id TEXT,           -- NULL once the course is deleted
+    learner_name TEXT NOT NULL,
+    course_name TEXT NOT NULL,
+    completed_at TEXT NOT NULL,
+    scores TEXT NOT NULL,     -- JSON list of quiz names and best scores
+    verification_hash TEXT NOT NULL UNIQUE,
+    issued_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (course_id) REFERENCES courses(id) ON DELETE SET NULL
+);
+
 -- ============================================
 -- Flashcards: Decks, Cards & Tags
 -- ============================================
@@ -94,6 +157,7 @@ CREATE TABLE IF NOT EXISTS decks (
     name TEXT NOT NULL,
     description TEXT,
     shuffle_cards INTEGER NOT NULL DEFAULT 0,
+    content_id TEXT,
     created_at TEXT NOT NULL,
     updated_at TEXT NOT NULL,
     -- Legacy sync fields (unused but kept for compatibility)
@@ -114,6 +178,9 @@ CREATE TABLE IF NOT EXISTS cards (
     back_type TEXT NOT NULL DEFAULT 'TEXT',
     back_language TEXT,
     notes TEXT,
+    content_id TEXT,
+    source_page_id TEXT,  -- Page the card was generated from; no foreign key, so trashing either keeps the link
+    source_anchor TEXT,  -- Where the note sits on the source page: section, note number and cloze group
     created_at TEXT NOT NULL,
     updated_at TEXT NOT NULL,
     remote_id INTEGER,  -- Legacy: unused, kept for compatibility
@@ -162,6 +229,10 @@ CREATE TABLE IF NOT EXISTS quizzes (
     name TEXT NOT NULL,
     description TEXT,
     shuffle_questions INTEGER NOT NULL DEFAULT 0,
+    pool_size INTEGER,  -- NULL means every question is asked
+    shuffle_choices INTEGER NOT NULL DEFAULT 0,
+    time_limit_seconds INTEGER,  -- NULL means untimed
+    content_id TEXT,
     created_at TEXT NOT NULL,
     updated_at TEXT NOT NULL
 );
@@ -176,7 +247,9 @@ CREATE TABLE IF NOT EXISTS questions (
     correct_answer TEXT,  -- For fill_in_blank questions
     multiple_answers INTEGER NOT NULL DEFAULT 0,  -- For multiple_choice: allow multiple correct
     explanation TEXT,
+    time_limit_seconds INTEGER,  -- NULL means untimed
     position INTEGER NOT NULL DEFAULT 0,
+    content_id TEXT,
     created_at TEXT NOT NULL,
     updated_at TEXT NOT NULL,
     FOREIGN KEY (quiz_id) REFERENCES quizzes(id) ON DELETE CASCADE
@@ -208,6 +281,17 @@ CREATE TABLE IF NOT EXISTS question_tags (
     FOREIGN KEY (tag_id) REFERENCES quiz_tags(id) ON DELETE CASCADE
 );
 
+-- Question pool rules (draw N questions carrying a tag into each attempt)
+CREATE TABLE IF NOT EXISTS quiz_pool_rules (
+    id TEXT PRIMARY KEY,
+    quiz_id TEXT NOT NULL,
+    tag_id TEXT NOT NULL,
+    question_count INTEGER NOT NULL,
+    position INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY (quiz_id) REFERENCES quizzes(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES quiz_tags(id) ON DELETE CASCADE
+);
+
 -- ============================================
 -- Quiz Attempts & Results
 -- ============================================
@@ -221,15 +305,44 @@ CREATE TABLE IF NOT EXISTS quiz_attempts (
     total_questions INTEGER NOT NULL,
     correct_answers INTEGER NOT NULL DEFAULT 0,
     score_percentage INTEGER NOT NULL DEFAULT 0,
+    deadline TEXT,  -- Set when the quiz or its questions are timed
+    is_late INTEGER NOT NULL DEFAULT 0,
+    abandoned_at TEXT,  -- Set when a stale attempt is closed without grading
+    mode TEXT NOT NULL DEFAULT 'standard',  -- 'standard', 'retry' or 'adaptive'
     FOREIGN KEY (quiz_id) REFERENCES quizzes(id) ON DELETE CASCADE
 );
 
+-- Questions drawn for an attempt, in the order they were presented
+CREATE TABLE IF NOT EXISTS attempt_questions (
+    attempt_id TEXT NOT NULL,
+    question_id TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    choice_order TEXT,  -- Comma-separated choice IDs as presented
+    shown_at TEXT,  -- When the app first showed the question; its time limit runs from here
+    PRIMARY KEY (attempt_id, question_id),
+    FOREIGN KEY (attempt_id) REFERENCES quiz_attempts(id) ON DELETE CASCADE,
+    FOREIGN KEY (question_id) REFERENCES questions(id) ON DELETE CASCADE
+);
+
+-- Provisional answers saved while an attempt is in progress
+CREATE TABLE IF NOT EXISTS attempt_answers (
+    attempt_id TEXT NOT NULL,
+    question_id TEXT NOT NULL,
+    answer TEXT NOT NULL,
+    answered_at TEXT NOT NULL,
+    time_spent_seconds INTEGER,
+    PRIMARY KEY (attempt_id, question_id),
+    FOREIGN KEY (attempt_id) REFERENCES quiz_attempts(id) ON DELETE CASCADE,
+    FOREIGN KEY (question_id) REFERENCES questions(id) ON DELETE CASCADE
+);
+
 CREATE TABLE IF NOT EXISTS question_results (
     id TEXT PRIMARY KEY,
     attempt_id TEXT NOT NULL,
     question_id TEXT NOT NULL,
     user_answer TEXT,
     is_correct INTEGER NOT NULL DEFAULT 0,
+    time_spent_seconds INTEGER,  -- As reported by the app, when known
     FOREIGN KEY (attempt_id) REFERENCES quiz_attempts(id) ON DELETE CASCADE,
     FOREIGN KEY (question_id) REFERENCES questions(id) ON DELETE CASCADE
 );
@@ -277,6 +390,7 @@ CREATE TABLE IF NOT EXISTS notebooks (
     description TEXT,
     icon TEXT NOT NULL DEFAULT 'notebook',
     color TEXT,
+    content_id TEXT,
     created_at TEXT NOT NULL,
     updated_at TEXT NOT NULL,
     FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
@@ -291,11 +405,44 @@ CREATE TABLE IF NOT EXISTS pages (
     content TEXT NOT NULL DEFAULT '',
     position INTEGER NOT NULL DEFAULT 0,
     is_pinned INTEGER NOT NULL DEFAULT 0,
+    content_id TEXT,
+    parent_page_id TEXT REFERENCES pages(id) ON DELETE CASCADE,  -- NULL for top-level pages
     created_at TEXT NOT NULL,
     updated_at TEXT NOT NULL,
     FOREIGN KEY (notebook_id) REFERENCES notebooks(id) ON DELETE CASCADE
 );
 
+-- Reading progress on pages, for lesson items with a read requirement
+CREATE TABLE IF NOT EXISTS page_reads (
+    page_id TEXT PRIMARY KEY,
+    seconds_read INTEGER NOT NULL DEFAULT 0,  -- Time on the page, summed across visits
+    marked_read_at TEXT,  -- Set when the page is explicitly marked as read
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (page_id) REFERENCES pages(id) ON DELETE CASCADE
+);
+
+-- Earlier versions of pages, each saved before an edit replaced it
+CREATE TABLE IF NOT EXISTS page_revisions (
+    id TEXT PRIMARY KEY,
+    page_id TEXT NOT NULL,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (page_id) REFERENCES pages(id) ON DELETE CASCADE
+);
+
+-- [[Title]] links between pages, indexed from page content on each save
+CREATE TABLE IF NOT EXISTS page_links (
+    source_page_id TEXT NOT NULL,
+    position INTEGER NOT NULL,  -- Order of the link within the source page
+    target_title TEXT NOT NULL,  -- Title as written in the link
+    alias TEXT,  -- Display text from [[Title|alias]]
+    target_page_id TEXT,  -- NULL while no page has the title (a dangling link)
+    PRIMARY KEY (source_page_id, position),
+    FOREIGN KEY (source_page_id) REFERENCES pages(id) ON DELETE CASCADE,
+    FOREIGN KEY (target_page_id) REFERENCES pages(id) ON DELETE SET NULL
+);
+
 -- Notebook favorites (user-specific)
 CREATE TABLE IF NOT EXISTS notebook_favorites (
     user_id TEXT NOT NULL,
@@ -306,6 +453,23 @@ CREATE TABLE IF NOT EXISTS notebook_favorites (
     FOREIGN KEY (notebook_id) REFERENCES notebooks(id) ON DELETE CASCADE
 );
 
+-- ============================================
+-- Trash
+-- ============================================
+
+-- Deleted content, kept with everything deleted along with it until restored or purged
+CREATE TABLE IF NOT EXISTS trash (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    item_type TEXT NOT NULL,  -- 'deck', 'card', 'quiz', 'course', 'notebook' or 'page'
+    item_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    parent_id TEXT,           -- Deck of a card, notebook of a page
+    snapshot TEXT NOT NULL,   -- JSON rows of every table the delete removed from
+    deleted_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
 -- ============================================
 -- Indexes
 -- ============================================
@@ -332,12 +496,14 @@ CREATE INDEX IF NOT EXISTS idx_choices_question_id ON choices(question_id);
 CREATE INDEX IF NOT EXISTS idx_quiz_tags_quiz_id ON quiz_tags(quiz_id);
 CREATE INDEX IF NOT EXISTS idx_question_tags_question_id ON question_tags(question_id);
 CREATE INDEX IF NOT EXISTS idx_question_tags_tag_id ON question_tags(tag_id);
+CREATE INDEX IF NOT EXISTS idx_quiz_pool_rules_quiz_id ON quiz_pool_rules(quiz_id);
 
 -- Quiz attempt indexes
 CREATE INDEX IF NOT EXISTS idx_quiz_attempts_quiz_id ON quiz_attempts(quiz_id);
 CREATE INDEX IF NOT EXISTS idx_quiz_attempts_completed_at ON quiz_attempts(completed_at);
 CREATE INDEX IF NOT EXISTS idx_question_results_attempt_id ON question_results(attempt_id);
 CREATE INDEX IF NOT EXISTS idx_question_results_question_id ON question_results(question_id);
+CREATE INDEX IF NOT EXISTS idx_attempt_questions_question_id ON attempt_questions(question_id);
 
 -- Course indexes
 CREATE INDEX IF NOT EXISTS idx_courses_user_id ON courses(user_id);
@@ -363,5 +529,10 @@ CREATE INDEX IF NOT EXISTS idx_course_favorites_course_id ON course_favorites(co
 CREATE INDEX IF NOT EXISTS idx_notebooks_user_id ON notebooks(user_id);
 CREATE INDEX IF NOT EXISTS idx_pages_notebook_id ON pages(notebook_id);
 CREATE INDEX IF NOT EXISTS idx_pages_position ON pages(notebook_id, position);
+CREATE INDEX IF NOT EXISTS idx_page_revisions_page_id ON page_revisions(page_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_page_links_target_page_id ON page_links(target_page_id);
 CREATE INDEX IF NOT EXISTS idx_notebook_favorites_user_id ON notebook_favorites(user_id);
 CREATE INDEX IF NOT EXISTS idx_notebook_favorites_notebook_id ON notebook_favorites(notebook_id);
+
+-- Trash indexes
+CREATE INDEX IF NOT EXISTS idx_trash_user_id ON trash(user_id, deleted_at);
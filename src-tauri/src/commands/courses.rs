@@ -1,8 +1,11 @@
 use tauri::State;
 
 use crate::db::{
-    self, AddLessonItemRequest, Course, CreateCourseRequest, CreateLessonRequest, DbState, Lesson,
-    LessonItem, LessonProgress, ReorderLessonItemsRequest, ReorderLessonsRequest,
+    self, AddLessonItemRequest, Certificate, ConfirmItemLinksRequest, Course, CourseEnrollment,
+    CourseReport, CreateCourseRequest, CreateLessonRequest, DbState, ElectiveGroup,
+    ElectiveGroupRequest, EnrollCourseRequest, Lesson, LessonItem, LessonProgress, LinkItemsResult,
+    ReorderLessonItemsRequest, ReorderLessonsRequest, RequirementCheck,
+    SetLessonCompletionRuleRequest, SetLessonPrerequisitesRequest, TodayPlan, TrashItemType,
     UpdateCourseRequest, UpdateLessonRequest,
 };
 
@@ -76,7 +79,22 @@ pub fn delete_course(state: State<DbState>, id: String) -> Result<(), String> {
     let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
     let active_user =
         db::get_active_user(&conn)?.ok_or_else(|| "No active user".to_string())?;
-    db::delete_course(&conn, &active_user.id, &id)
+    db::move_to_trash(&conn, &active_user.id, TrashItemType::Course, &id)?;
+    Ok(())
+}
+
+/// Mark a course as revised before exporting it, to the next version unless
+/// one is given
+#[tauri::command]
+pub fn raise_course_version(
+    state: State<DbState>,
+    id: String,
+    version: Option<i32>,
+) -> Result<Course, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user =
+        db::get_active_user(&conn)?.ok_or_else(|| "No active user".to_string())?;
+    db::raise_course_version(&conn, &active_user.id, &id, version)
 }
 
 #[tauri::command]
@@ -149,6 +167,72 @@ pub fn reorder_lessons(
     db::reorder_lessons(&conn, &course_id, &request.lesson_ids)
 }
 
+#[tauri::command]
+pub fn set_lesson_prerequisites(
+    state: State<DbState>,
+    lesson_id: String,
+    request: SetLessonPrerequisitesRequest,
+) -> Result<(), String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::set_lesson_prerequisites(
+        &conn,
+        &lesson_id,
+        request.requires_previous,
+        &request.prerequisite_ids,
+    )
+}
+
+#[tauri::command]
+pub fn set_lesson_completion_rule(
+    state: State<DbState>,
+    lesson_id: String,
+    request: SetLessonCompletionRuleRequest,
+) -> Result<(), String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::set_lesson_completion_rule(
+        &conn,
+        &lesson_id,
+        request.is_optional,
+        request.elective_group_id.as_deref(),
+    )
+}
+
+// ============================================
+// Elective Group Commands
+// ============================================
+
+#[tauri::command]
+pub fn get_elective_groups(state: State<DbState>, course_id: String) -> Result<Vec<ElectiveGroup>, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::get_elective_groups(&conn, &course_id)
+}
+
+#[tauri::command]
+pub fn create_elective_group(
+    state: State<DbState>,
+    course_id: String,
+    request: ElectiveGroupRequest,
+) -> Result<ElectiveGroup, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::create_elective_group(&conn, &course_id, &request.name, request.required_count)
+}
+
+#[tauri::command]
+pub fn update_elective_group(
+    state: State<DbState>,
+    group_id: String,
+    request: ElectiveGroupRequest,
+) -> Result<ElectiveGroup, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::update_elective_group(&conn, &group_id, &request.name, request.required_count)
+}
+
+#[tauri::command]
+pub fn delete_elective_group(state: State<DbState>, group_id: String) -> Result<(), String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::delete_elective_group(&conn, &group_id)
+}
+
 // ============================================
 // Lesson Item Commands
 // ============================================
@@ -238,6 +322,22 @@ pub fn record_lesson_progress(
     )
 }
 
+#[tauri::command]
+pub fn check_lesson_item_requirement(
+    state: State<DbState>,
+    lesson_id: String,
+    lesson_item_id: String,
+) -> Result<RequirementCheck, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user =
+        db::get_active_user(&conn)?.ok_or_else(|| "No active user".to_string())?;
+    let item = db::get_lesson_items(&conn, &active_user.id, &lesson_id)?
+        .into_iter()
+        .find(|i| i.id == lesson_item_id)
+        .ok_or_else(|| format!("Lesson item not found: {}", lesson_item_id))?;
+    db::check_requirement(&conn, &item, None, None)
+}
+
 #[tauri::command]
 pub fn clear_lesson_item_progress(
     state: State<DbState>,
@@ -260,6 +360,95 @@ pub fn get_lesson_progress(
     db::get_lesson_progress(&conn, &active_user.id, &course_id)
 }
 
+#[tauri::command]
+pub fn get_course_report(state: State<DbState>, course_id: String) -> Result<CourseReport, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user =
+        db::get_active_user(&conn)?.ok_or_else(|| "No active user".to_string())?;
+    db::get_course_report(&conn, &active_user.id, &course_id)
+}
+
+// ============================================
+// Enrollment Commands
+// ============================================
+
+#[tauri::command]
+pub fn enroll_in_course(
+    state: State<DbState>,
+    course_id: String,
+    request: EnrollCourseRequest,
+) -> Result<CourseEnrollment, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user =
+        db::get_active_user(&conn)?.ok_or_else(|| "No active user".to_string())?;
+    db::get_course(&conn, &active_user.id, &course_id)?
+        .ok_or_else(|| format!("Course not found: {}", course_id))?;
+    db::enroll_in_course(
+        &conn,
+        &active_user.id,
+        &course_id,
+        request.start_date.as_deref(),
+        &request.pacing_mode,
+        request.lessons_per_week,
+    )
+}
+
+#[tauri::command]
+pub fn get_course_enrollment(state: State<DbState>, course_id: String) -> Result<Option<CourseEnrollment>, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user =
+        db::get_active_user(&conn)?.ok_or_else(|| "No active user".to_string())?;
+    db::get_enrollment(&conn, &active_user.id, &course_id)
+}
+
+#[tauri::command]
+pub fn unenroll_from_course(state: State<DbState>, course_id: String) -> Result<(), String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user =
+        db::get_active_user(&conn)?.ok_or_else(|| "No active user".to_string())?;
+    db::unenroll_from_course(&conn, &active_user.id, &course_id)
+}
+
+#[tauri::command]
+pub fn set_lesson_due_day(state: State<DbState>, lesson_id: String, due_day: Option<i32>) -> Result<(), String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::set_lesson_due_day(&conn, &lesson_id, due_day)
+}
+
+#[tauri::command]
+pub fn get_today_plan(state: State<DbState>) -> Result<TodayPlan, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user =
+        db::get_active_user(&conn)?.ok_or_else(|| "No active user".to_string())?;
+    db::get_today_plan(&conn, &active_user.id)
+}
+
+// ============================================
+// Certificate Commands
+// ============================================
+
+#[tauri::command]
+pub fn issue_course_certificate(state: State<DbState>, course_id: String) -> Result<Certificate, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user =
+        db::get_active_user(&conn)?.ok_or_else(|| "No active user".to_string())?;
+    db::issue_certificate(&conn, &active_user.id, &course_id)
+}
+
+#[tauri::command]
+pub fn get_certificates(state: State<DbState>) -> Result<Vec<Certificate>, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user =
+        db::get_active_user(&conn)?.ok_or_else(|| "No active user".to_string())?;
+    db::get_certificates(&conn, &active_user.id)
+}
+
+#[tauri::command]
+pub fn verify_certificate(state: State<DbState>, hash: String) -> Result<Option<Certificate>, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::verify_certificate(&conn, &hash)
+}
+
 // ============================================
 // Utility: Link missing items
 // ============================================
@@ -272,17 +461,17 @@ pub fn link_course_items(
     let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
     let active_user =
         db::get_active_user(&conn)?.ok_or_else(|| "No active user".to_string())?;
-    let (items_linked, items_not_found) =
-        db::link_lesson_items_by_name(&conn, &active_user.id, &course_id)?;
-    Ok(LinkItemsResult {
-        items_linked,
-        items_not_found,
-    })
-}
-
-#[derive(serde::Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct LinkItemsResult {
-    pub items_linked: i32,
-    pub items_not_found: Vec<String>,
+    db::link_missing_lesson_items(&conn, &active_user.id, &course_id)
+}
+
+#[tauri::command]
+pub fn confirm_course_item_links(
+    state: State<DbState>,
+    course_id: String,
+    request: ConfirmItemLinksRequest,
+) -> Result<i32, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user =
+        db::get_active_user(&conn)?.ok_or_else(|| "No active user".to_string())?;
+    db::confirm_item_links(&conn, &active_user.id, &course_id, &request.links)
 }
@@ -1,8 +1,8 @@
 use tauri::State;
 
 use crate::db::{
-    self, Card, CardTag, CreateCardRequest, CreateDeckRequest, DbState, Deck, Tag,
-    UpdateCardRequest, UpdateDeckRequest,
+    self, Card, CardTag, CreateCardRequest, CreateDeckRequest, DbState, Deck, GenerateCardsRequest,
+    GenerateCardsResult, Tag, TrashItemType, UpdateCardRequest, UpdateDeckRequest,
 };
 
 // ============================================
@@ -58,7 +58,8 @@ pub fn delete_deck(state: State<DbState>, id: String) -> Result<(), String> {
     let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
     let active_user = db::get_active_user(&conn)?
         .ok_or_else(|| "No active user".to_string())?;
-    db::delete_deck(&conn, &active_user.id, &id)
+    db::move_to_trash(&conn, &active_user.id, TrashItemType::Deck, &id)?;
+    Ok(())
 }
 
 // ============================================
@@ -108,7 +109,30 @@ pub fn update_card(
 #[tauri::command]
 pub fn delete_card(state: State<DbState>, id: String, deck_id: String) -> Result<(), String> {
     let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
-    db::delete_card(&conn, &id, &deck_id)
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+    db::get_card(&conn, &id, &deck_id)?;
+    db::move_to_trash(&conn, &active_user.id, TrashItemType::Card, &id)?;
+    Ok(())
+}
+
+/// Create or update cards in a deck from the notes on a notebook page
+#[tauri::command]
+pub fn generate_cards_from_page(
+    state: State<DbState>,
+    page_id: String,
+    request: GenerateCardsRequest,
+) -> Result<GenerateCardsResult, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+    db::generate_cards_from_page(
+        &conn,
+        &active_user.id,
+        &page_id,
+        &request.deck_id,
+        request.remove_missing.unwrap_or(false),
+    )
 }
 
 // ============================================
@@ -4,7 +4,7 @@ use tauri::{AppHandle, Manager};
 use uuid::Uuid;
 
 /// Get the images directory path, creating it if needed
-fn get_images_dir(app: &AppHandle) -> Result<PathBuf, String> {
+pub(crate) fn get_images_dir(app: &AppHandle) -> Result<PathBuf, String> {
     let app_data = app
         .path()
         .app_data_dir()
@@ -2,8 +2,10 @@ pub mod courses;
 pub mod decks;
 pub mod images;
 pub mod notebooks;
+pub mod print;
 pub mod quizzes;
 pub mod sessions;
 pub mod transfer;
+pub mod trash;
 pub mod tts;
 pub mod users;
@@ -1,8 +1,11 @@
 use tauri::State;
 
+use rusqlite::Connection;
+
 use crate::db::{
-    self, CreateNotebookRequest, CreatePageRequest, DbState, Notebook, Page,
-    PageSearchResult, ReorderPagesRequest, UpdateNotebookRequest, UpdatePageRequest,
+    self, CreateNotebookRequest, CreatePageRequest, DanglingLink, DbState, LessonItem,
+    LessonItemType, Notebook, Page, PageDiff, PageGraph, PageRead, PageRevision, PageSearchResult,
+    PageTreeNode, ReorderPagesRequest, TrashItemType, UpdateNotebookRequest, UpdatePageRequest,
 };
 
 // ============================================
@@ -46,7 +49,8 @@ pub fn delete_notebook(state: State<DbState>, id: String) -> Result<(), String>
     let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
     let active_user = db::get_active_user(&conn)?
         .ok_or_else(|| "No active user".to_string())?;
-    db::delete_notebook(&conn, &active_user.id, &id)
+    db::move_to_trash(&conn, &active_user.id, TrashItemType::Notebook, &id)?;
+    Ok(())
 }
 
 #[tauri::command]
@@ -62,9 +66,9 @@ pub fn toggle_notebook_favorite(state: State<DbState>, notebook_id: String) -> R
 // ============================================
 
 #[tauri::command]
-pub fn get_pages_for_notebook(state: State<DbState>, notebook_id: String) -> Result<Vec<Page>, String> {
+pub fn get_pages_for_notebook(state: State<DbState>, notebook_id: String) -> Result<Vec<PageTreeNode>, String> {
     let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
-    db::get_pages_for_notebook(&conn, &notebook_id)
+    db::get_page_tree(&conn, &notebook_id)
 }
 
 #[tauri::command]
@@ -93,10 +97,15 @@ pub fn update_page(
     db::update_page(&conn, &id, &request)
 }
 
+/// Sub-pages are deleted with the page unless `promote_children` moves them
+/// up to take its place
 #[tauri::command]
-pub fn delete_page(state: State<DbState>, id: String) -> Result<(), String> {
+pub fn delete_page(state: State<DbState>, id: String, promote_children: Option<bool>) -> Result<(), String> {
     let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
-    db::delete_page(&conn, &id)
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+    db::move_page_to_trash(&conn, &active_user.id, &id, promote_children.unwrap_or(false))?;
+    Ok(())
 }
 
 #[tauri::command]
@@ -106,7 +115,7 @@ pub fn reorder_pages(
     request: ReorderPagesRequest,
 ) -> Result<(), String> {
     let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
-    db::reorder_pages(&conn, &notebook_id, &request.page_ids)
+    db::reorder_pages(&conn, &notebook_id, request.parent_page_id.as_deref(), &request.page_ids)
 }
 
 #[tauri::command]
@@ -115,6 +124,127 @@ pub fn toggle_page_pin(state: State<DbState>, id: String) -> Result<bool, String
     db::toggle_page_pin(&conn, &id)
 }
 
+// ============================================
+// Revision Commands
+// ============================================
+
+#[tauri::command]
+pub fn get_page_revisions(state: State<DbState>, page_id: String) -> Result<Vec<PageRevision>, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::get_page_revisions(&conn, &page_id)
+}
+
+/// Line diff between two revisions; a missing revision ID means the page as it is now
+#[tauri::command]
+pub fn diff_page_revisions(
+    state: State<DbState>,
+    page_id: String,
+    from_revision_id: Option<String>,
+    to_revision_id: Option<String>,
+) -> Result<PageDiff, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::diff_page_revisions(&conn, &page_id, from_revision_id.as_deref(), to_revision_id.as_deref())
+}
+
+#[tauri::command]
+pub fn restore_page_revision(state: State<DbState>, revision_id: String) -> Result<Page, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::restore_page_revision(&conn, &revision_id)
+}
+
+// ============================================
+// Reading Commands
+// ============================================
+
+/// Add time spent on a page. With `lesson_item_id`, the reading also counts
+/// towards that page or notebook item of a course.
+#[tauri::command]
+pub fn record_page_reading(
+    state: State<DbState>,
+    page_id: String,
+    seconds: i32,
+    lesson_item_id: Option<String>,
+) -> Result<PageRead, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    // Check the lesson item before anything is written
+    let progress = match lesson_item_id.as_deref() {
+        Some(lesson_item_id) => Some(reading_lesson_item(&conn, &page_id, lesson_item_id)?),
+        None => None,
+    };
+    let read = db::add_page_reading_time(&conn, &page_id, seconds)?;
+    if let Some((user_id, course_id, item)) = progress {
+        db::record_lesson_progress(
+            &conn,
+            &user_id,
+            &course_id,
+            &item.lesson_id,
+            &item.id,
+            None,
+            None,
+            None,
+        )?;
+    }
+    Ok(read)
+}
+
+#[tauri::command]
+pub fn mark_page_read(
+    state: State<DbState>,
+    page_id: String,
+    is_read: bool,
+    lesson_item_id: Option<String>,
+) -> Result<PageRead, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    // Check the lesson item before anything is written
+    let progress = match lesson_item_id.as_deref() {
+        Some(lesson_item_id) => Some(reading_lesson_item(&conn, &page_id, lesson_item_id)?),
+        None => None,
+    };
+    let read = db::mark_page_read(&conn, &page_id, is_read)?;
+    if let Some((user_id, course_id, item)) = progress {
+        db::record_lesson_progress(
+            &conn,
+            &user_id,
+            &course_id,
+            &item.lesson_id,
+            &item.id,
+            None,
+            None,
+            None,
+        )?;
+    }
+    Ok(read)
+}
+
+#[tauri::command]
+pub fn get_page_read(state: State<DbState>, page_id: String) -> Result<Option<PageRead>, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::get_page_read(&conn, &page_id)
+}
+
+/// The open page or notebook item a reading of `page_id` counts towards,
+/// with the active user and the item's course
+fn reading_lesson_item(
+    conn: &Connection,
+    page_id: &str,
+    lesson_item_id: &str,
+) -> Result<(String, String, LessonItem), String> {
+    let active_user = db::get_active_user(conn)?.ok_or_else(|| "No active user".to_string())?;
+    let (course_id, item) = db::get_open_lesson_item(conn, &active_user.id, lesson_item_id)?;
+    let page = db::get_page(conn, page_id)?.ok_or_else(|| format!("Page not found: {}", page_id))?;
+
+    let target = match item.item_type {
+        LessonItemType::Page => page.id.as_str(),
+        LessonItemType::Notebook => page.notebook_id.as_str(),
+        _ => return Err("The lesson item is not a page or notebook".to_string()),
+    };
+    if item.item_id.as_deref() != Some(target) {
+        return Err("This page is not part of the lesson item".to_string());
+    }
+
+    Ok((active_user.id, course_id, item))
+}
+
 // ============================================
 // Search Commands
 // ============================================
@@ -157,9 +287,10 @@ pub fn move_page(
     state: State<DbState>,
     page_id: String,
     target_notebook_id: String,
+    parent_page_id: Option<String>,
 ) -> Result<Page, String> {
     let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
-    db::move_page(&conn, &page_id, &target_notebook_id)
+    db::move_page(&conn, &page_id, &target_notebook_id, parent_page_id.as_deref())
 }
 
 // ============================================
@@ -184,3 +315,20 @@ pub fn get_all_page_titles(state: State<DbState>) -> Result<Vec<PageSearchResult
         .ok_or_else(|| "No active user".to_string())?;
     db::get_all_page_titles(&conn, &active_user.id)
 }
+
+#[tauri::command]
+pub fn get_dangling_links(state: State<DbState>) -> Result<Vec<DanglingLink>, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+    db::get_dangling_links(&conn, &active_user.id)
+}
+
+/// Pages and their links for a graph view, across all notebooks or within one
+#[tauri::command]
+pub fn get_page_graph(state: State<DbState>, notebook_id: Option<String>) -> Result<PageGraph, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+    db::get_page_graph(&conn, &active_user.id, notebook_id.as_deref())
+}
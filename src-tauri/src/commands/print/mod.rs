@@ -0,0 +1,441 @@
+pub(crate) mod pdf;
+
+use tauri::State;
+
+use crate::db::{self, Card, Certificate, DbState, Deck, QuestionType, Quiz};
+use pdf::{line_height, text_width, wrap, Paper, PdfWriter, Style};
+
+const PAGE_MARGIN: f32 = 15.0;
+const CARD_COLUMNS: usize = 2;
+const CARD_ROWS: usize = 4;
+const CARD_PADDING: f32 = 5.0;
+const CARD_FONT_SIZES: [f32; 9] = [16.0, 14.0, 12.0, 11.0, 10.0, 9.0, 8.0, 7.0, 6.0];
+const QUESTION_INDENT: f32 = 8.0;
+const CHOICE_INDENT: f32 = 14.0;
+const CERTIFICATE_BORDER: f32 = 12.0;
+
+// ============================================
+// Print Commands
+// ============================================
+
+/// Cut-out cards: each sheet is a page of fronts followed by a page of backs,
+/// mirrored so they line up when printed duplex and flipped on the long edge
+#[tauri::command]
+pub fn export_deck_to_pdf(
+    state: State<DbState>,
+    deck_id: String,
+    file_path: String,
+    paper: Option<String>,
+) -> Result<(), String> {
+    let paper = Paper::parse(paper.as_deref())?;
+    let bytes = {
+        let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let deck = db::get_deck(&conn, &deck_id)?
+            .ok_or_else(|| format!("Deck not found: {}", deck_id))?;
+        let cards = db::get_cards_for_deck(&conn, &deck_id)?;
+        if cards.is_empty() {
+            return Err("Deck has no cards to print".to_string());
+        }
+        render_deck(&deck, &cards, paper)?
+    };
+
+    std::fs::write(&file_path, bytes).map_err(|e| format!("Failed to write PDF: {}", e))
+}
+
+/// A numbered test with every question, followed by an answer key
+#[tauri::command]
+pub fn export_quiz_to_pdf(
+    state: State<DbState>,
+    quiz_id: String,
+    file_path: String,
+    paper: Option<String>,
+) -> Result<(), String> {
+    let paper = Paper::parse(paper.as_deref())?;
+    let bytes = {
+        let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let quiz = db::get_quiz(&conn, &quiz_id)?;
+        if quiz.questions.is_empty() {
+            return Err("Quiz has no questions to print".to_string());
+        }
+        render_quiz(&quiz, paper)?
+    };
+
+    std::fs::write(&file_path, bytes).map_err(|e| format!("Failed to write PDF: {}", e))
+}
+
+/// A landscape certificate; it is stored when issued, so re-exporting one
+/// always prints the same names, scores and hash
+#[tauri::command]
+pub fn export_certificate_to_pdf(
+    state: State<DbState>,
+    certificate_id: String,
+    file_path: String,
+    paper: Option<String>,
+) -> Result<(), String> {
+    let paper = Paper::parse(paper.as_deref())?;
+    let bytes = {
+        let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let active_user = db::get_active_user(&conn)?
+            .ok_or_else(|| "No active user".to_string())?;
+        let certificate = db::get_certificate(&conn, &active_user.id, &certificate_id)?
+            .ok_or_else(|| format!("Certificate not found: {}", certificate_id))?;
+        render_certificate(&certificate, paper)?
+    };
+
+    std::fs::write(&file_path, bytes).map_err(|e| format!("Failed to write PDF: {}", e))
+}
+
+// ============================================
+// Deck Layout
+// ============================================
+
+fn render_deck(deck: &Deck, cards: &[Card], paper: Paper) -> Result<Vec<u8>, String> {
+    let mut writer = PdfWriter::new(&deck.name, paper)?;
+    let cell_width = (writer.width - 2.0 * PAGE_MARGIN) / CARD_COLUMNS as f32;
+    let cell_height = (writer.height - 2.0 * PAGE_MARGIN) / CARD_ROWS as f32;
+    let per_sheet = CARD_COLUMNS * CARD_ROWS;
+
+    for (sheet, sheet_cards) in cards.chunks(per_sheet).enumerate() {
+        if sheet > 0 {
+            writer.new_page();
+        }
+
+        for back in [false, true] {
+            if back {
+                writer.new_page();
+            }
+            draw_card_grid(&writer, cell_width, cell_height);
+
+            for (idx, card) in sheet_cards.iter().enumerate() {
+                let row = idx / CARD_COLUMNS;
+                let column = if back {
+                    CARD_COLUMNS - 1 - idx % CARD_COLUMNS
+                } else {
+                    idx % CARD_COLUMNS
+                };
+                let x = PAGE_MARGIN + column as f32 * cell_width;
+                let top = writer.height - PAGE_MARGIN - row as f32 * cell_height;
+
+                let (text, content_type) = if back {
+                    (&card.back, &card.back_type)
+                } else {
+                    (&card.front, &card.front_type)
+                };
+                draw_card_face(&writer, text, content_type == "CODE", x, top, cell_width, cell_height);
+
+                // Matching numbers on both faces help sort the cut cards
+                let number = (sheet * per_sheet + idx + 1).to_string();
+                let number_width = text_width(&number, Style::Regular, 6.0);
+                writer.text(
+                    &number,
+                    Style::Regular,
+                    6.0,
+                    x + cell_width - 2.0 - number_width,
+                    top - cell_height + 2.0,
+                );
+            }
+        }
+    }
+
+    writer.finish()
+}
+
+fn draw_card_grid(writer: &PdfWriter, cell_width: f32, cell_height: f32) {
+    let left = PAGE_MARGIN;
+    let right = writer.width - PAGE_MARGIN;
+    let bottom = PAGE_MARGIN;
+    let top = writer.height - PAGE_MARGIN;
+
+    for column in 0..=CARD_COLUMNS {
+        let x = left + column as f32 * cell_width;
+        writer.line((x, bottom), (x, top), 0.3, true);
+    }
+    for row in 0..=CARD_ROWS {
+        let y = top - row as f32 * cell_height;
+        writer.line((left, y), (right, y), 0.3, true);
+    }
+}
+
+/// Fit the text in the cell by stepping down the font size, centring the block
+fn draw_card_face(writer: &PdfWriter, text: &str, is_code: bool, x: f32, top: f32, width: f32, height: f32) {
+    let style = if is_code { Style::Mono } else { Style::Regular };
+    let inner_width = width - 2.0 * CARD_PADDING;
+    let inner_height = height - 2.0 * CARD_PADDING;
+
+    let mut size = CARD_FONT_SIZES[CARD_FONT_SIZES.len() - 1];
+    let mut lines = Vec::new();
+    for candidate in CARD_FONT_SIZES {
+        size = candidate;
+        lines = wrap(text.trim(), style, size, inner_width);
+        if lines.len() as f32 * line_height(size) <= inner_height {
+            break;
+        }
+    }
+
+    let max_lines = (inner_height / line_height(size)).floor().max(1.0) as usize;
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        if let Some(last) = lines.last_mut() {
+            last.push_str(" ...");
+        }
+    }
+
+    let block_height = lines.len() as f32 * line_height(size);
+    let block_width = lines
+        .iter()
+        .map(|l| text_width(l, style, size))
+        .fold(0.0, f32::max);
+    let mut baseline = top - (height - block_height) / 2.0 - line_height(size) * 0.8;
+
+    for line in &lines {
+        // Code keeps its indentation, so the block is centred as a whole
+        let line_x = if is_code {
+            x + (width - block_width) / 2.0
+        } else {
+            x + (width - text_width(line, style, size)) / 2.0
+        };
+        writer.text(line, style, size, line_x, baseline);
+        baseline -= line_height(size);
+    }
+}
+
+// ============================================
+// Quiz Layout
+// ============================================
+
+/// Top-to-bottom text flow that starts a new page when it runs out of room
+struct Flow {
+    writer: PdfWriter,
+    y: f32,
+}
+
+impl Flow {
+    fn width(&self) -> f32 {
+        self.writer.width - 2.0 * PAGE_MARGIN
+    }
+
+    fn top(&self) -> f32 {
+        self.writer.height - PAGE_MARGIN
+    }
+
+    fn new_page(&mut self) {
+        self.writer.new_page();
+        self.y = self.top();
+    }
+
+    /// Move to a new page unless `height` still fits on this one
+    fn keep_together(&mut self, height: f32) {
+        if self.y - height < PAGE_MARGIN && self.y < self.top() {
+            self.new_page();
+        }
+    }
+
+    fn paragraph(&mut self, text: &str, style: Style, size: f32, indent: f32) {
+        for line in wrap(text, style, size, self.width() - indent) {
+            self.keep_together(line_height(size));
+            self.y -= line_height(size);
+            self.writer.text(&line, style, size, PAGE_MARGIN + indent, self.y + line_height(size) * 0.2);
+        }
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+}
+
+fn render_quiz(quiz: &Quiz, paper: Paper) -> Result<Vec<u8>, String> {
+    let writer = PdfWriter::new(&quiz.name, paper)?;
+    let mut flow = Flow {
+        y: writer.height - PAGE_MARGIN,
+        writer,
+    };
+
+    flow.paragraph(&quiz.name, Style::Bold, 18.0, 0.0);
+    if let Some(description) = quiz.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        flow.gap(1.0);
+        flow.paragraph(description, Style::Regular, 10.0, 0.0);
+    }
+    flow.gap(4.0);
+    flow.paragraph("Name: ______________________________    Date: ______________", Style::Regular, 10.0, 0.0);
+    flow.gap(3.0);
+    let rule_y = flow.y;
+    flow.writer.line((PAGE_MARGIN, rule_y), (flow.writer.width - PAGE_MARGIN, rule_y), 0.5, false);
+    flow.gap(6.0);
+
+    for (idx, question) in quiz.questions.iter().enumerate() {
+        let width = flow.width();
+        let content_style = if question.content_type == "CODE" { Style::Mono } else { Style::Regular };
+        let content_size = if question.content_type == "CODE" { 9.0 } else { 11.0 };
+
+        // Estimate the question's height so short questions are not split
+        let mut height = wrap(&question.content, content_style, content_size, width - QUESTION_INDENT).len() as f32
+            * line_height(content_size);
+        for choice in &question.choices {
+            height += wrap(&choice.text, Style::Regular, 11.0, width - CHOICE_INDENT - 6.0).len() as f32
+                * line_height(11.0);
+        }
+        flow.keep_together(height + 2.0 * line_height(11.0));
+
+        let number_y = flow.y - line_height(content_size) * 0.8;
+        flow.writer.text(&format!("{}.", idx + 1), Style::Bold, 11.0, PAGE_MARGIN, number_y);
+        flow.paragraph(&question.content, content_style, content_size, QUESTION_INDENT);
+
+        match question.question_type {
+            QuestionType::MultipleChoice => {
+                if question.multiple_answers {
+                    flow.paragraph("Select all that apply.", Style::Italic, 9.0, QUESTION_INDENT);
+                }
+                flow.gap(1.5);
+                for (choice_idx, choice) in question.choices.iter().enumerate() {
+                    let label = format!("{}.", choice_letter(choice_idx));
+                    flow.keep_together(line_height(11.0));
+                    let label_y = flow.y - line_height(11.0) * 0.8;
+                    flow.writer.text(&label, Style::Regular, 11.0, PAGE_MARGIN + CHOICE_INDENT, label_y);
+                    flow.paragraph(&choice.text, Style::Regular, 11.0, CHOICE_INDENT + 6.0);
+                }
+            }
+            QuestionType::FillInBlank => {
+                flow.gap(3.0);
+                flow.paragraph("Answer: ________________________________________", Style::Regular, 11.0, QUESTION_INDENT);
+            }
+        }
+        flow.gap(6.0);
+    }
+
+    // The answer key always starts on its own page so it can be removed
+    flow.new_page();
+    flow.paragraph(&format!("Answer Key: {}", quiz.name), Style::Bold, 16.0, 0.0);
+    flow.gap(4.0);
+
+    for (idx, question) in quiz.questions.iter().enumerate() {
+        let answer = match question.question_type {
+            QuestionType::MultipleChoice => question
+                .choices
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.is_correct)
+                .map(|(i, _)| choice_letter(i).to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            QuestionType::FillInBlank => question.correct_answer.clone().unwrap_or_default(),
+        };
+
+        flow.keep_together(2.0 * line_height(11.0));
+        flow.paragraph(&format!("{}.  {}", idx + 1, answer), Style::Regular, 11.0, 0.0);
+        if let Some(explanation) = question.explanation.as_deref().filter(|e| !e.trim().is_empty()) {
+            flow.paragraph(explanation, Style::Italic, 9.0, QUESTION_INDENT);
+        }
+        flow.gap(2.0);
+    }
+
+    flow.writer.finish()
+}
+
+// ============================================
+// Certificate Layout
+// ============================================
+
+fn render_certificate(certificate: &Certificate, paper: Paper) -> Result<Vec<u8>, String> {
+    let (short_side, long_side) = paper.size();
+    let mut writer = PdfWriter::with_size(
+        &format!("Certificate: {}", certificate.course_name),
+        long_side,
+        short_side,
+    )?;
+    let completed_on = chrono::DateTime::parse_from_rfc3339(&certificate.completed_at)
+        .map(|d| format!("on {}", d.format("%-d %B %Y")))
+        .unwrap_or_else(|_| format!("on {}", certificate.completed_at));
+
+    draw_certificate_frame(&writer, certificate);
+    let mut y = writer.height - CERTIFICATE_BORDER - 28.0;
+    for (text, style, size, gap) in [
+        ("Certificate of Completion", Style::Bold, 30.0, 16.0),
+        ("This certifies that", Style::Italic, 12.0, 12.0),
+        (certificate.learner_name.as_str(), Style::Bold, 24.0, 12.0),
+        ("has completed the course", Style::Italic, 12.0, 11.0),
+        (certificate.course_name.as_str(), Style::Bold, 20.0, 10.0),
+        (completed_on.as_str(), Style::Regular, 12.0, 12.0),
+    ] {
+        // Long names are shrunk to fit between the borders
+        let max_width = writer.width - 4.0 * CERTIFICATE_BORDER;
+        let size = (size * max_width / text_width(text, style, size)).min(size);
+        centred_text(&writer, text, style, size, y);
+        y -= gap;
+    }
+
+    // Final quiz scores, continuing on further pages if there are many
+    let footer = CERTIFICATE_BORDER + 16.0;
+    if !certificate.scores.is_empty() {
+        let left = writer.width / 2.0 - 60.0;
+        let right = writer.width / 2.0 + 60.0;
+        let mut heading = true;
+        for score in &certificate.scores {
+            if heading {
+                writer.text("Quiz", Style::Bold, 10.0, left, y);
+                let label_width = text_width("Score", Style::Bold, 10.0);
+                writer.text("Score", Style::Bold, 10.0, right - label_width, y);
+                writer.line((left, y - 1.5), (right, y - 1.5), 0.3, false);
+                y -= line_height(10.0) + 1.0;
+                heading = false;
+            }
+
+            let name = wrap(&score.quiz_name, Style::Regular, 10.0, right - left - 20.0)
+                .into_iter()
+                .next()
+                .unwrap_or_default();
+            let value = score
+                .score_percentage
+                .map(|s| format!("{}%", s))
+                .unwrap_or_else(|| "-".to_string());
+            writer.text(&name, Style::Regular, 10.0, left, y);
+            writer.text(&value, Style::Regular, 10.0, right - text_width(&value, Style::Regular, 10.0), y);
+            y -= line_height(10.0);
+
+            if y < footer + line_height(10.0) {
+                writer.new_page();
+                draw_certificate_frame(&writer, certificate);
+                y = writer.height - CERTIFICATE_BORDER - 16.0;
+                heading = true;
+            }
+        }
+    }
+
+    writer.finish()
+}
+
+/// Border and verification footer, repeated on every page
+fn draw_certificate_frame(writer: &PdfWriter, certificate: &Certificate) {
+    for inset in [CERTIFICATE_BORDER, CERTIFICATE_BORDER + 2.0] {
+        let (left, bottom) = (inset, inset);
+        let (right, top) = (writer.width - inset, writer.height - inset);
+        writer.line((left, bottom), (right, bottom), 0.8, false);
+        writer.line((right, bottom), (right, top), 0.8, false);
+        writer.line((right, top), (left, top), 0.8, false);
+        writer.line((left, top), (left, bottom), 0.8, false);
+    }
+
+    let issued_on = certificate.issued_at.get(..10).unwrap_or(&certificate.issued_at);
+    centred_text(
+        writer,
+        &format!("Certificate {} issued {}", certificate.id, issued_on),
+        Style::Regular,
+        8.0,
+        CERTIFICATE_BORDER + 10.0,
+    );
+    centred_text(
+        writer,
+        &format!("Verification: {}", certificate.verification_hash),
+        Style::Mono,
+        8.0,
+        CERTIFICATE_BORDER + 6.0,
+    );
+}
+
+fn centred_text(writer: &PdfWriter, text: &str, style: Style, size: f32, y: f32) {
+    let x = (writer.width - text_width(text, style, size)) / 2.0;
+    writer.text(text, style, size, x, y);
+}
+
+fn choice_letter(idx: usize) -> char {
+    (b'A' + (idx % 26) as u8) as char
+}
@@ -0,0 +1,213 @@
+// Thin layout layer over printpdf using the built-in PDF fonts, so nothing has
+// to be bundled or fetched. Coordinates are millimetres from the bottom left.
+
+use printpdf::{
+    BuiltinFont, Color, IndirectFontRef, Line, LineDashPattern, Mm, PdfDocument,
+    PdfDocumentReference, PdfLayerReference, Point, Rgb,
+};
+
+const PT_TO_MM: f32 = 0.352_778;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Paper {
+    A4,
+    Letter,
+}
+
+impl Paper {
+    pub fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value.map(|v| v.to_ascii_lowercase()).as_deref() {
+            None | Some("a4") => Ok(Paper::A4),
+            Some("letter") => Ok(Paper::Letter),
+            Some(other) => Err(format!("Unsupported paper size: {}", other)),
+        }
+    }
+
+    pub fn size(&self) -> (f32, f32) {
+        match self {
+            Paper::A4 => (210.0, 297.0),
+            Paper::Letter => (215.9, 279.4),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Regular,
+    Bold,
+    Italic,
+    Mono,
+}
+
+pub struct PdfWriter {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    fonts: [IndirectFontRef; 4],
+    pub width: f32,
+    pub height: f32,
+}
+
+impl PdfWriter {
+    pub fn new(title: &str, paper: Paper) -> Result<Self, String> {
+        let (width, height) = paper.size();
+        Self::with_size(title, width, height)
+    }
+
+    /// Pages of any size, such as a paper size turned to landscape
+    pub fn with_size(title: &str, width: f32, height: f32) -> Result<Self, String> {
+        let (doc, page, layer) = PdfDocument::new(title, Mm(width), Mm(height), "Layer 1");
+
+        let font = |builtin| {
+            doc.add_builtin_font(builtin)
+                .map_err(|e| format!("Failed to load font: {}", e))
+        };
+        let fonts = [
+            font(BuiltinFont::Helvetica)?,
+            font(BuiltinFont::HelveticaBold)?,
+            font(BuiltinFont::HelveticaOblique)?,
+            font(BuiltinFont::Courier)?,
+        ];
+
+        let layer = doc.get_page(page).get_layer(layer);
+        Ok(PdfWriter {
+            doc,
+            layer,
+            fonts,
+            width,
+            height,
+        })
+    }
+
+    pub fn new_page(&mut self) {
+        let (page, layer) = self.doc.add_page(Mm(self.width), Mm(self.height), "Layer 1");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+    }
+
+    /// Draw one line of text with its baseline at `y`
+    pub fn text(&self, text: &str, style: Style, size: f32, x: f32, y: f32) {
+        let font = match style {
+            Style::Regular => &self.fonts[0],
+            Style::Bold => &self.fonts[1],
+            Style::Italic => &self.fonts[2],
+            Style::Mono => &self.fonts[3],
+        };
+        self.layer.use_text(to_win_ansi(text), size, Mm(x), Mm(y), font);
+    }
+
+    pub fn line(&self, from: (f32, f32), to: (f32, f32), thickness: f32, dashed: bool) {
+        self.layer.set_outline_thickness(thickness);
+        self.layer.set_outline_color(Color::Rgb(Rgb::new(0.6, 0.6, 0.6, None)));
+        self.layer.set_line_dash_pattern(LineDashPattern {
+            dash_1: if dashed { Some(3) } else { None },
+            ..Default::default()
+        });
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(from.0), Mm(from.1)), false),
+                (Point::new(Mm(to.0), Mm(to.1)), false),
+            ],
+            is_closed: false,
+        });
+    }
+
+    pub fn finish(self) -> Result<Vec<u8>, String> {
+        self.doc
+            .save_to_bytes()
+            .map_err(|e| format!("Failed to write PDF: {}", e))
+    }
+}
+
+/// Height of one line of text in millimetres
+pub fn line_height(size: f32) -> f32 {
+    size * PT_TO_MM * 1.25
+}
+
+pub fn text_width(text: &str, style: Style, size: f32) -> f32 {
+    let units: u32 = text
+        .chars()
+        .map(|c| match style {
+            Style::Mono => 600,
+            Style::Bold => helvetica_width(c) * 105 / 100,
+            _ => helvetica_width(c),
+        })
+        .sum();
+    units as f32 / 1000.0 * size * PT_TO_MM
+}
+
+/// Break text into lines no wider than `max_width`, keeping explicit line
+/// breaks. Monospace text is wrapped by character so indentation survives.
+pub fn wrap(text: &str, style: Style, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.replace('\t', "    ").lines() {
+        if style == Style::Mono {
+            let per_line = ((max_width / text_width("M", style, size)) as usize).max(1);
+            let chars: Vec<char> = paragraph.chars().collect();
+            if chars.is_empty() {
+                lines.push(String::new());
+            }
+            for chunk in chars.chunks(per_line) {
+                lines.push(chunk.iter().collect());
+            }
+            continue;
+        }
+
+        let mut current = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if current.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", current, word)
+            };
+            if text_width(&candidate, style, size) <= max_width {
+                current = candidate;
+                continue;
+            }
+
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            // Split words that are wider than a whole line
+            for c in word.chars() {
+                current.push(c);
+                if text_width(&current, style, size) > max_width && current.chars().count() > 1 {
+                    current.pop();
+                    lines.push(std::mem::replace(&mut current, c.to_string()));
+                }
+            }
+        }
+        lines.push(current);
+    }
+
+    lines
+}
+
+/// Replace characters the built-in fonts cannot show
+fn to_win_ansi(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{2018}' | '\u{2019}' => '\'',
+            '\u{201C}' | '\u{201D}' => '"',
+            '\u{2013}' | '\u{2014}' => '-',
+            '\u{2022}' => '*',
+            c if (c as u32) < 0x100 && !c.is_control() => c,
+            _ => '?',
+        })
+        .collect()
+}
+
+/// Advance widths of Helvetica from its AFM metrics, in 1/1000 em
+fn helvetica_width(c: char) -> u32 {
+    const ASCII: [u32; 95] = [
+        278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, // ' '..'/'
+        556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, // '0'..'?'
+        1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, // '@'..'O'
+        667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556, // 'P'..'_'
+        333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556, // '`'..'o'
+        556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584, // 'p'..'~'
+    ];
+    match c as u32 {
+        code @ 32..=126 => ASCII[(code - 32) as usize],
+        _ => 556,
+    }
+}
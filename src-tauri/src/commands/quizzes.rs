@@ -1,9 +1,10 @@
 use tauri::State;
 
 use crate::db::{
-    self, CreateChoiceRequest, CreateQuestionRequest, CreateQuizRequest, DbState, Question,
-    QuestionTag, Quiz, QuizAttempt, QuizStats, QuizTag, SubmitQuizRequest, UpdateQuestionRequest,
-    UpdateQuizRequest,
+    self, AdaptiveProgress, AttemptCleanupResult, CreateChoiceRequest, CreateQuestionRequest,
+    CreateQuizRequest, DbState, Question, QuestionAnswer, QuestionTag, Quiz, QuizAttempt,
+    QuizItemAnalysis, QuizPoolRule, QuizPoolRuleRequest, QuizStats, QuizTag, RetryQuizRequest,
+    SavedAnswer, SubmitQuizRequest, TrashItemType, UpdateQuestionRequest, UpdateQuizRequest,
 };
 
 // ============================================
@@ -106,7 +107,8 @@ pub fn delete_quiz(state: State<DbState>, quiz_id: String) -> Result<(), String>
     let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
     let active_user = db::get_active_user(&conn)?
         .ok_or_else(|| "No active user".to_string())?;
-    db::delete_quiz(&conn, &active_user.id, &quiz_id)
+    db::move_to_trash(&conn, &active_user.id, TrashItemType::Quiz, &quiz_id)?;
+    Ok(())
 }
 
 // ============================================
@@ -174,6 +176,29 @@ pub fn update_question_choices(
     db::update_choices_for_question(&conn, &question_id, &choices)
 }
 
+// ============================================
+// Question Pool Commands
+// ============================================
+
+#[tauri::command]
+pub fn get_quiz_pool_rules(
+    state: State<DbState>,
+    quiz_id: String,
+) -> Result<Vec<QuizPoolRule>, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::get_pool_rules_for_quiz(&conn, &quiz_id)
+}
+
+#[tauri::command]
+pub fn set_quiz_pool_rules(
+    state: State<DbState>,
+    quiz_id: String,
+    rules: Vec<QuizPoolRuleRequest>,
+) -> Result<Vec<QuizPoolRule>, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::set_pool_rules_for_quiz(&conn, &quiz_id, &rules)
+}
+
 // ============================================
 // Quiz Attempt Commands
 // ============================================
@@ -184,6 +209,15 @@ pub fn start_quiz_attempt(state: State<DbState>, quiz_id: String) -> Result<Quiz
     db::start_quiz_attempt(&conn, &quiz_id)
 }
 
+#[tauri::command]
+pub fn get_attempt_questions(
+    state: State<DbState>,
+    attempt_id: String,
+) -> Result<Vec<Question>, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::get_attempt_questions(&conn, &attempt_id)
+}
+
 #[tauri::command]
 pub fn submit_quiz_attempt(
     state: State<DbState>,
@@ -191,7 +225,40 @@ pub fn submit_quiz_attempt(
     request: SubmitQuizRequest,
 ) -> Result<QuizAttempt, String> {
     let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
-    db::submit_quiz_attempt(&conn, &attempt_id, &request.answers)
+
+    // In a course, check the item before grading so a bad item leaves the attempt open
+    let course_item = match request.lesson_item_id.as_deref() {
+        Some(lesson_item_id) => {
+            let active_user =
+                db::get_active_user(&conn)?.ok_or_else(|| "No active user".to_string())?;
+            let (course_id, item) = db::get_open_lesson_item(&conn, &active_user.id, lesson_item_id)?;
+            let quiz_id = db::get_quiz_attempt(&conn, &attempt_id)?.quiz_id;
+            if item.item_id.as_deref() != Some(quiz_id.as_str()) {
+                return Err("This attempt is not for the lesson item's quiz".to_string());
+            }
+            Some((active_user.id, course_id, item))
+        }
+        None => None,
+    };
+
+    let attempt = db::submit_quiz_attempt(&conn, &attempt_id, &request.answers)?;
+
+    // The attempt is already graded, so a progress failure must not report it as lost
+    if let Some((user_id, course_id, item)) = course_item {
+        if let Err(e) = db::record_lesson_progress(
+            &conn,
+            &user_id,
+            &course_id,
+            &item.lesson_id,
+            &item.id,
+            None,
+            Some(&attempt.id),
+            None,
+        ) {
+            eprintln!("Warning: Failed to record lesson progress for attempt {}: {}", attempt.id, e);
+        }
+    }
+    Ok(attempt)
 }
 
 #[tauri::command]
@@ -209,12 +276,93 @@ pub fn get_quiz_attempts(
     db::get_quiz_attempts(&conn, &quiz_id)
 }
 
+#[tauri::command]
+pub fn save_attempt_answer(
+    state: State<DbState>,
+    attempt_id: String,
+    answer: QuestionAnswer,
+) -> Result<SavedAnswer, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::save_attempt_answer(&conn, &attempt_id, &answer)
+}
+
+/// Start a timed question's own limit when the app shows it
+#[tauri::command]
+pub fn mark_question_shown(
+    state: State<DbState>,
+    attempt_id: String,
+    question_id: String,
+) -> Result<String, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::mark_question_shown(&conn, &attempt_id, &question_id)
+}
+
+#[tauri::command]
+pub fn get_in_progress_attempts(state: State<DbState>) -> Result<Vec<QuizAttempt>, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+    db::get_in_progress_attempts(&conn, &active_user.id)
+}
+
+#[tauri::command]
+pub fn resume_quiz_attempt(
+    state: State<DbState>,
+    attempt_id: String,
+) -> Result<QuizAttempt, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::resume_quiz_attempt(&conn, &attempt_id)
+}
+
+#[tauri::command]
+pub fn cleanup_stale_attempts(state: State<DbState>) -> Result<AttemptCleanupResult, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::cleanup_stale_attempts(&conn, db::STALE_ATTEMPT_HOURS)
+}
+
+#[tauri::command]
+pub fn start_retry_attempt(
+    state: State<DbState>,
+    request: RetryQuizRequest,
+) -> Result<QuizAttempt, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::start_retry_attempt(&conn, &request)
+}
+
+#[tauri::command]
+pub fn answer_adaptive_question(
+    state: State<DbState>,
+    attempt_id: String,
+    answer: QuestionAnswer,
+) -> Result<AdaptiveProgress, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::answer_adaptive_question(&conn, &attempt_id, &answer)
+}
+
+#[tauri::command]
+pub fn get_adaptive_progress(
+    state: State<DbState>,
+    attempt_id: String,
+) -> Result<AdaptiveProgress, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::get_adaptive_progress(&conn, &attempt_id)
+}
+
 #[tauri::command]
 pub fn get_quiz_stats(state: State<DbState>, quiz_id: String) -> Result<QuizStats, String> {
     let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
     db::get_quiz_stats(&conn, &quiz_id)
 }
 
+#[tauri::command]
+pub fn get_quiz_item_analysis(
+    state: State<DbState>,
+    quiz_id: String,
+) -> Result<QuizItemAnalysis, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::get_quiz_item_analysis(&conn, &quiz_id)
+}
+
 // ============================================
 // Quiz Favorite Commands
 // ============================================
@@ -8,14 +8,49 @@ pub fn start_study_session(state: State<DbState>, deck_id: String) -> Result<Stu
     db::start_study_session(&conn, &deck_id)
 }
 
+/// With `lesson_item_id`, the session also counts towards that item of a course
 #[tauri::command]
 pub fn end_study_session(
     state: State<DbState>,
     session_id: String,
     cards_studied: i32,
+    lesson_item_id: Option<String>,
 ) -> Result<StudySession, String> {
     let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
-    db::end_study_session(&conn, &session_id, cards_studied)
+
+    // In a course, check the item before ending so a bad item leaves the session open
+    let course_item = match lesson_item_id.as_deref() {
+        Some(lesson_item_id) => {
+            let active_user =
+                db::get_active_user(&conn)?.ok_or_else(|| "No active user".to_string())?;
+            let (course_id, item) = db::get_open_lesson_item(&conn, &active_user.id, lesson_item_id)?;
+            let deck_id = db::get_study_session(&conn, &session_id)?.deck_id;
+            if item.item_id.as_deref() != Some(deck_id.as_str()) {
+                return Err("This session is not for the lesson item's deck".to_string());
+            }
+            Some((active_user.id, course_id, item))
+        }
+        None => None,
+    };
+
+    let session = db::end_study_session(&conn, &session_id, cards_studied)?;
+
+    // The session is already saved, so a progress failure must not report it as lost
+    if let Some((user_id, course_id, item)) = course_item {
+        if let Err(e) = db::record_lesson_progress(
+            &conn,
+            &user_id,
+            &course_id,
+            &item.lesson_id,
+            &item.id,
+            None,
+            None,
+            Some(&session.id),
+        ) {
+            eprintln!("Warning: Failed to record lesson progress for session {}: {}", session.id, e);
+        }
+    }
+    Ok(session)
 }
 
 #[tauri::command]
@@ -0,0 +1,701 @@
+// Kioku's course bundle: a course, its lessons and the decks, quizzes and
+// notebook pages they use, in one JSON file
+//
+// Bundles carry a version and stable IDs for the course, its lessons and
+// items, so a newer bundle can be applied to an imported course in place.
+
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use super::merge::{self, ImportOptions, MergeSummary};
+use super::{BankQuestion, ImportedCard, ImportedPage, JsonCard, JsonQuestion};
+use crate::db::{
+    self, Course, CreateQuizRequest, LessonItem, LessonItemType, LessonPrerequisites, RequirementType,
+    UpdateNotebookRequest,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseBundle {
+    pub id: Option<String>,
+    /// Bundles from before versioning count as the first version
+    #[serde(default = "first_version")]
+    pub version: i32,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub elective_groups: Vec<BundleElectiveGroup>,
+    pub lessons: Vec<BundleLesson>,
+    #[serde(default)]
+    pub decks: Vec<BundleDeck>,
+    #[serde(default)]
+    pub quizzes: Vec<BundleQuiz>,
+    /// Notebooks holding the pages the lessons use
+    #[serde(default)]
+    pub notebooks: Vec<BundleNotebook>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleElectiveGroup {
+    id: Option<String>,
+    name: String,
+    required_count: i32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleLesson {
+    id: Option<String>,
+    title: String,
+    description: Option<String>,
+    #[serde(default)]
+    requires_previous: bool,
+    /// IDs of other lessons in the bundle
+    #[serde(default)]
+    prerequisites: Vec<String>,
+    /// Calendar pacing: days after the learner's start date
+    due_day: Option<i32>,
+    #[serde(default)]
+    optional: bool,
+    /// ID of one of the bundle's elective groups, or its name
+    elective_group: Option<String>,
+    items: Vec<BundleItem>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleItem {
+    id: Option<String>,
+    #[serde(rename = "type")]
+    item_type: String,
+    name: String,
+    /// Content ID of the deck, quiz, page or notebook, for relinking when it is not embedded
+    target_id: Option<String>,
+    requirement_type: Option<String>,
+    requirement_value: Option<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleDeck {
+    id: Option<String>,
+    name: String,
+    description: Option<String>,
+    #[serde(default)]
+    shuffle_cards: bool,
+    cards: Vec<JsonCard>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleQuiz {
+    id: Option<String>,
+    name: String,
+    description: Option<String>,
+    #[serde(default)]
+    shuffle_questions: bool,
+    pool_size: Option<i32>,
+    #[serde(default)]
+    shuffle_choices: bool,
+    time_limit_seconds: Option<i32>,
+    #[serde(default)]
+    pool_rules: Vec<BundlePoolRule>,
+    questions: Vec<JsonQuestion>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundlePoolRule {
+    tag: String,
+    question_count: i32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleNotebook {
+    id: Option<String>,
+    name: String,
+    description: Option<String>,
+    icon: Option<String>,
+    color: Option<String>,
+    pages: Vec<BundlePage>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundlePage {
+    id: Option<String>,
+    title: String,
+    #[serde(default)]
+    content: String,
+    /// Content ID of the page this one is nested under
+    parent_id: Option<String>,
+}
+
+fn first_version() -> i32 {
+    1
+}
+
+pub fn parse(content: &str) -> Result<CourseBundle, String> {
+    let bundle: CourseBundle =
+        serde_json::from_str(content).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+    let items = bundle.lessons.iter().flat_map(|l| &l.items);
+    merge::check_unique_ids("lesson", bundle.lessons.iter().map(|l| l.id.as_deref()))?;
+    merge::check_unique_ids("lesson item", items.map(|i| i.id.as_deref()))?;
+    merge::check_unique_ids("elective group", bundle.elective_groups.iter().map(|g| g.id.as_deref()))?;
+    merge::check_unique_ids("deck", bundle.decks.iter().map(|d| d.id.as_deref()))?;
+    merge::check_unique_ids("quiz", bundle.quizzes.iter().map(|q| q.id.as_deref()))?;
+    merge::check_unique_ids("notebook", bundle.notebooks.iter().map(|n| n.id.as_deref()))?;
+    Ok(bundle)
+}
+
+/// What saving a bundle's content did, and where lesson items should point:
+/// deck, quiz and notebook IDs by name and page IDs by title
+#[derive(Default)]
+pub struct SavedContent {
+    pub decks: HashMap<String, String>,
+    pub quizzes: HashMap<String, String>,
+    pub notebooks: HashMap<String, String>,
+    pub pages: HashMap<String, String>,
+    pub decks_updated: i32,
+    pub quizzes_updated: i32,
+    pub notebooks_updated: i32,
+    pub card_changes: MergeSummary,
+    pub question_changes: MergeSummary,
+    pub page_changes: MergeSummary,
+}
+
+impl SavedContent {
+    fn item_id(&self, item: &BundleItem) -> Option<String> {
+        match item.item_type.as_str() {
+            "deck" => self.decks.get(&item.name).cloned(),
+            "quiz" => self.quizzes.get(&item.name).cloned(),
+            "notebook" => self.notebooks.get(&item.name).cloned(),
+            "page" => self.pages.get(&item.name).cloned(),
+            _ => None,
+        }
+    }
+}
+
+/// Save the embedded decks, quizzes and notebooks. Items the bundle does not
+/// embed are linked to the user's own content of the same name.
+pub fn save_content(
+    conn: &Connection,
+    user_id: &str,
+    bundle: &CourseBundle,
+    options: &ImportOptions,
+) -> Result<SavedContent, String> {
+    let mut saved = SavedContent::default();
+
+    for deck_import in &bundle.decks {
+        let (deck, updated) = merge::upsert_deck(
+            conn,
+            user_id,
+            deck_import.id.as_deref(),
+            &deck_import.name,
+            deck_import.description.as_deref(),
+            deck_import.shuffle_cards,
+            options,
+        )?;
+
+        let changes = merge::save_cards(conn, &deck.id, deck_import.cards(), options.delete_missing)?;
+        saved.card_changes.include(&changes);
+
+        if updated {
+            saved.decks_updated += 1;
+        }
+        saved.decks.insert(deck_import.name.clone(), deck.id);
+    }
+
+    for quiz_import in &bundle.quizzes {
+        let (quiz, updated) = merge::upsert_quiz(
+            conn,
+            user_id,
+            quiz_import.id.as_deref(),
+            &quiz_import.request(),
+            options,
+        )?;
+
+        let changes = merge::save_questions(conn, &quiz.id, quiz_import.questions(), options.delete_missing)?;
+        saved.question_changes.include(&changes);
+
+        let pool_rules: Vec<(String, i32)> = quiz_import
+            .pool_rules
+            .iter()
+            .map(|r| (r.tag.clone(), r.question_count))
+            .collect();
+        merge::save_pool_rules(conn, &quiz.id, &pool_rules, updated)?;
+
+        if updated {
+            saved.quizzes_updated += 1;
+        }
+        saved.quizzes.insert(quiz_import.name.clone(), quiz.id);
+    }
+
+    for notebook_import in &bundle.notebooks {
+        let request = UpdateNotebookRequest {
+            name: notebook_import.name.clone(),
+            description: notebook_import.description.clone(),
+            icon: notebook_import.icon.clone(),
+            color: notebook_import.color.clone(),
+        };
+        let (notebook, updated) =
+            merge::upsert_notebook(conn, user_id, notebook_import.id.as_deref(), &request, options)?;
+
+        let (changes, page_ids) = merge::save_pages(conn, &notebook.id, notebook_import.pages(), options.delete_missing)?;
+        saved.page_changes.include(&changes);
+        saved.pages.extend(page_ids);
+
+        if updated {
+            saved.notebooks_updated += 1;
+        }
+        // A new notebook may have been renamed to avoid a clash
+        saved.notebooks.insert(notebook_import.name.clone(), notebook.id);
+    }
+
+    for deck in db::get_all_decks(conn, user_id)? {
+        saved.decks.entry(deck.name).or_insert(deck.id);
+    }
+    for quiz in db::get_all_quizzes(conn, user_id)? {
+        saved.quizzes.entry(quiz.name).or_insert(quiz.id);
+    }
+    for notebook in db::get_all_notebooks(conn, user_id)? {
+        saved.notebooks.entry(notebook.name).or_insert(notebook.id);
+    }
+    for page in db::get_all_page_titles(conn, user_id)? {
+        saved.pages.entry(page.title).or_insert(page.id);
+    }
+
+    Ok(saved)
+}
+
+/// Decks and quizzes imported before they had stable IDs are recognised
+/// through the course's lesson items, so an update changes them instead of
+/// adding copies next to them
+pub fn adopt_content_ids(conn: &Connection, user_id: &str, course: &Course, bundle: &CourseBundle) -> Result<(), String> {
+    let linked = |item_type: LessonItemType, name: &str| {
+        course
+            .lessons
+            .iter()
+            .flat_map(|l| &l.items)
+            .find(|i| i.item_type == item_type && i.item_name == name)
+            .and_then(|i| i.item_id.clone())
+    };
+
+    for deck in &bundle.decks {
+        let Some(content_id) = deck.id.as_deref() else { continue };
+        if db::find_deck_by_content_id(conn, user_id, content_id)?.is_none() {
+            if let Some(deck_id) = linked(LessonItemType::Deck, &deck.name) {
+                db::set_deck_content_id(conn, &deck_id, content_id)?;
+            }
+        }
+    }
+    for quiz in &bundle.quizzes {
+        let Some(content_id) = quiz.id.as_deref() else { continue };
+        if db::find_quiz_by_content_id(conn, user_id, content_id)?.is_none() {
+            if let Some(quiz_id) = linked(LessonItemType::Quiz, &quiz.name) {
+                db::set_quiz_content_id(conn, &quiz_id, content_id)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl BundleDeck {
+    fn cards(&self) -> Vec<ImportedCard> {
+        self.cards.iter().cloned().map(ImportedCard::from).collect()
+    }
+}
+
+impl BundleNotebook {
+    fn pages(&self) -> Vec<ImportedPage> {
+        self.pages
+            .iter()
+            .map(|page| ImportedPage {
+                title: page.title.clone(),
+                content: page.content.clone(),
+                content_id: page.id.clone(),
+                parent_content_id: page.parent_id.clone(),
+            })
+            .collect()
+    }
+}
+
+impl BundleQuiz {
+    fn request(&self) -> CreateQuizRequest {
+        CreateQuizRequest {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            shuffle_questions: Some(self.shuffle_questions),
+            pool_size: self.pool_size,
+            shuffle_choices: Some(self.shuffle_choices),
+            time_limit_seconds: self.time_limit_seconds,
+        }
+    }
+
+    fn questions(&self) -> Vec<BankQuestion> {
+        self.questions.iter().cloned().map(BankQuestion::from).collect()
+    }
+}
+
+// ============================================
+// Lessons
+// ============================================
+
+/// Create the bundle's lessons in a new course; returns how many items were
+/// linked to a deck or quiz
+pub fn create_lessons(
+    conn: &Connection,
+    course_id: &str,
+    bundle: &CourseBundle,
+    saved: &SavedContent,
+) -> Result<i32, String> {
+    let mut items_linked = 0;
+    let mut lesson_ids = Vec::new();
+
+    for (lesson_pos, lesson_import) in bundle.lessons.iter().enumerate() {
+        let lesson = db::create_lesson(
+            conn,
+            course_id,
+            &lesson_import.title,
+            lesson_import.description.as_deref(),
+            Some(lesson_pos as i32),
+        )?;
+        if let Some(content_id) = lesson_import.id.as_deref() {
+            db::set_lesson_content_id(conn, &lesson.id, content_id)?;
+        }
+
+        for (item_pos, item) in lesson_import.items.iter().enumerate() {
+            let item_id = saved.item_id(item);
+            let lesson_item = db::add_lesson_item(
+                conn,
+                &lesson.id,
+                &item.item_type,
+                &item.name,
+                item_id.as_deref(),
+                item.requirement_type.as_deref(),
+                item.requirement_value,
+                Some(item_pos as i32),
+            )?;
+            if let Some(content_id) = item.id.as_deref() {
+                db::set_lesson_item_content_id(conn, &lesson_item.id, content_id)?;
+            }
+            if let (None, Some(target_id)) = (&item_id, item.target_id.as_deref()) {
+                db::set_lesson_item_target(conn, &lesson_item.id, target_id)?;
+            }
+
+            if item_id.is_some() {
+                items_linked += 1;
+            }
+        }
+        lesson_ids.push(lesson.id);
+    }
+
+    save_lesson_rules(conn, course_id, bundle, &lesson_ids)?;
+    Ok(items_linked)
+}
+
+/// Apply the bundle's unlock, pacing and completion rules; `lesson_ids` holds
+/// the saved lesson for each bundle lesson, in bundle order
+fn save_lesson_rules(
+    conn: &Connection,
+    course_id: &str,
+    bundle: &CourseBundle,
+    lesson_ids: &[String],
+) -> Result<(), String> {
+    let group_ids = save_elective_groups(conn, course_id, bundle)?;
+    let by_content_id: HashMap<&str, &String> = bundle
+        .lessons
+        .iter()
+        .zip(lesson_ids)
+        .filter_map(|(lesson, id)| Some((lesson.id.as_deref()?, id)))
+        .collect();
+
+    // All lessons at once, so an update may reverse a dependency between two of them
+    let prerequisites: Vec<LessonPrerequisites> = bundle
+        .lessons
+        .iter()
+        .zip(lesson_ids)
+        .map(|(lesson_import, lesson_id)| LessonPrerequisites {
+            lesson_id: lesson_id.clone(),
+            requires_previous: lesson_import.requires_previous,
+            prerequisite_ids: lesson_import
+                .prerequisites
+                .iter()
+                .filter_map(|content_id| by_content_id.get(content_id.as_str()).map(|id| id.to_string()))
+                .collect(),
+        })
+        .collect();
+    db::set_course_prerequisites(conn, &prerequisites)?;
+
+    for (lesson_import, lesson_id) in bundle.lessons.iter().zip(lesson_ids) {
+        db::set_lesson_due_day(conn, lesson_id, lesson_import.due_day)?;
+
+        let group_id = match lesson_import.elective_group.as_deref() {
+            Some(key) => Some(group_ids.get(key).ok_or_else(|| {
+                format!("Lesson \"{}\" names an unknown elective group: {}", lesson_import.title, key)
+            })?),
+            None => None,
+        };
+        db::set_lesson_completion_rule(
+            conn,
+            lesson_id,
+            lesson_import.optional && group_id.is_none(),
+            group_id.map(|id| id.as_str()),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Bring the course's elective groups in line with the bundle, matching by
+/// ID and then by name. Returns the saved group for each bundle group's ID
+/// and name, which is how lessons refer to them.
+fn save_elective_groups(
+    conn: &Connection,
+    course_id: &str,
+    bundle: &CourseBundle,
+) -> Result<HashMap<String, String>, String> {
+    let mut existing = db::get_elective_groups(conn, course_id)?;
+    let bundle_ids: HashSet<&str> = bundle.elective_groups.iter().filter_map(|g| g.id.as_deref()).collect();
+    let mut group_ids = HashMap::new();
+
+    for group_import in &bundle.elective_groups {
+        let matched = take_match(
+            &mut existing,
+            |g| group_import.id.as_deref() == Some(g.content_id.as_str()),
+            |g| g.name == group_import.name && !bundle_ids.contains(g.content_id.as_str()),
+        );
+        let group = match matched {
+            Some(group) => db::update_elective_group(conn, &group.id, &group_import.name, group_import.required_count)?,
+            None => db::create_elective_group(conn, course_id, &group_import.name, group_import.required_count)?,
+        };
+        if let Some(content_id) = group_import.id.as_deref() {
+            db::set_elective_group_content_id(conn, &group.id, content_id)?;
+            group_ids.insert(content_id.to_string(), group.id.clone());
+        }
+        group_ids.insert(group_import.name.clone(), group.id);
+    }
+
+    for group in existing {
+        db::delete_elective_group(conn, &group.id)?;
+    }
+    Ok(group_ids)
+}
+
+/// A completed item that an update removed or whose requirement it changed
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletedItemChange {
+    lesson: String,
+    item_type: LessonItemType,
+    item_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    requirement_type: Option<RequirementType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    requirement_value: Option<i32>,
+    /// The requirement after a change; unset for removed items and dropped requirements
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new_requirement_type: Option<RequirementType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new_requirement_value: Option<i32>,
+}
+
+impl CompletedItemChange {
+    fn new(lesson: &str, item: &LessonItem) -> Self {
+        CompletedItemChange {
+            lesson: lesson.to_string(),
+            item_type: item.item_type.clone(),
+            item_name: item.item_name.clone(),
+            requirement_type: item.requirement_type.clone(),
+            requirement_value: item.requirement_value,
+            new_requirement_type: None,
+            new_requirement_value: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LessonChanges {
+    lessons_added: usize,
+    lessons_removed: usize,
+    items_added: usize,
+    items_updated: usize,
+    items_removed: usize,
+    /// Completed items that matched an item in the update and kept their progress
+    progress_kept: usize,
+    /// Progress rows that followed their item into another lesson
+    progress_moved: usize,
+    removed_completed: Vec<CompletedItemChange>,
+    changed_requirements: Vec<CompletedItemChange>,
+}
+
+/// Bring an existing course's lessons in line with the bundle.
+///
+/// Lessons match by ID and then by title; items match by ID anywhere in the
+/// course and then by type and name. A matched item keeps its row, so
+/// every learner's progress on it carries over even when it moves lesson.
+/// Whatever is left unmatched is removed along with its progress.
+pub fn update_lessons(
+    conn: &Connection,
+    user_id: &str,
+    course_id: &str,
+    bundle: &CourseBundle,
+    saved: &SavedContent,
+) -> Result<LessonChanges, String> {
+    let mut changes = LessonChanges::default();
+    let mut old_lessons = db::get_lessons(conn, user_id, course_id)?;
+    let mut old_items: Vec<(String, LessonItem)> = old_lessons
+        .iter_mut()
+        .flat_map(|lesson| {
+            let title = lesson.title.clone();
+            lesson.items.drain(..).map(move |item| (title.clone(), item))
+        })
+        .collect();
+
+    // Name matching must not take a row that an ID later in the bundle claims
+    let lesson_ids: HashSet<&str> = bundle.lessons.iter().filter_map(|l| l.id.as_deref()).collect();
+    let item_ids: HashSet<&str> = bundle
+        .lessons
+        .iter()
+        .flat_map(|l| &l.items)
+        .filter_map(|i| i.id.as_deref())
+        .collect();
+
+    let mut lesson_order = Vec::new();
+    for (lesson_pos, lesson_import) in bundle.lessons.iter().enumerate() {
+        let matched = take_match(
+            &mut old_lessons,
+            |l| lesson_import.id.as_deref() == Some(l.content_id.as_str()),
+            |l| l.title == lesson_import.title && !lesson_ids.contains(l.content_id.as_str()),
+        );
+
+        let lesson_id = match matched {
+            Some(lesson) => {
+                if lesson.title != lesson_import.title || lesson.description != lesson_import.description {
+                    db::update_lesson(conn, &lesson.id, &lesson_import.title, lesson_import.description.as_deref())?;
+                }
+                lesson.id
+            }
+            None => {
+                changes.lessons_added += 1;
+                db::create_lesson(
+                    conn,
+                    course_id,
+                    &lesson_import.title,
+                    lesson_import.description.as_deref(),
+                    Some(lesson_pos as i32),
+                )?
+                .id
+            }
+        };
+        if let Some(content_id) = lesson_import.id.as_deref() {
+            db::set_lesson_content_id(conn, &lesson_id, content_id)?;
+        }
+        lesson_order.push(lesson_id.clone());
+
+        for (item_pos, item_import) in lesson_import.items.iter().enumerate() {
+            let item_type = LessonItemType::from_str(&item_import.item_type);
+            let requirement_type = item_import.requirement_type.as_deref().and_then(RequirementType::from_str);
+            let item_id = saved.item_id(item_import);
+
+            // A changed type is a different item, even under the same ID
+            let matched = take_match(
+                &mut old_items,
+                |(_, i)| i.item_type == item_type && item_import.id.as_deref() == Some(i.content_id.as_str()),
+                |(_, i)| {
+                    i.item_type == item_type
+                        && i.item_name == item_import.name
+                        && !item_ids.contains(i.content_id.as_str())
+                },
+            );
+
+            let lesson_item_id = match matched {
+                Some((old_lesson, old)) => {
+                    let completed = old.is_completed == Some(true);
+                    let requirement_changed =
+                        old.requirement_type != requirement_type || old.requirement_value != item_import.requirement_value;
+                    if completed && requirement_changed {
+                        changes.changed_requirements.push(CompletedItemChange {
+                            new_requirement_type: requirement_type.clone(),
+                            new_requirement_value: item_import.requirement_value,
+                            ..CompletedItemChange::new(&old_lesson, &old)
+                        });
+                    }
+                    if completed {
+                        changes.progress_kept += 1;
+                    }
+
+                    if requirement_changed || old.item_name != item_import.name || old.item_id != item_id {
+                        changes.items_updated += 1;
+                    }
+                    db::update_lesson_item(
+                        conn,
+                        &old.id,
+                        &item_import.name,
+                        item_id.as_deref(),
+                        item_import.requirement_type.as_deref(),
+                        item_import.requirement_value,
+                    )?;
+                    changes.progress_moved += db::move_lesson_item(conn, &old.id, &lesson_id, item_pos as i32)?;
+                    old.id
+                }
+                None => {
+                    changes.items_added += 1;
+                    db::add_lesson_item(
+                        conn,
+                        &lesson_id,
+                        &item_import.item_type,
+                        &item_import.name,
+                        item_id.as_deref(),
+                        item_import.requirement_type.as_deref(),
+                        item_import.requirement_value,
+                        Some(item_pos as i32),
+                    )?
+                    .id
+                }
+            };
+            if let Some(content_id) = item_import.id.as_deref() {
+                db::set_lesson_item_content_id(conn, &lesson_item_id, content_id)?;
+            }
+            if let (None, Some(target_id)) = (&item_id, item_import.target_id.as_deref()) {
+                db::set_lesson_item_target(conn, &lesson_item_id, target_id)?;
+            }
+        }
+    }
+
+    for (lesson_title, item) in old_items {
+        if item.is_completed == Some(true) {
+            changes.removed_completed.push(CompletedItemChange::new(&lesson_title, &item));
+        }
+        db::remove_lesson_item(conn, &item.id)?;
+        changes.items_removed += 1;
+    }
+    for lesson in old_lessons {
+        db::delete_lesson(conn, &lesson.id)?;
+        changes.lessons_removed += 1;
+    }
+
+    db::set_lesson_order(conn, course_id, &lesson_order)?;
+    save_lesson_rules(conn, course_id, bundle, &lesson_order)?;
+    Ok(changes)
+}
+
+/// Remove and return the first entry matching by ID, or failing that by name
+fn take_match<T>(
+    entries: &mut Vec<T>,
+    by_id: impl Fn(&T) -> bool,
+    by_name: impl Fn(&T) -> bool,
+) -> Option<T> {
+    let idx = entries
+        .iter()
+        .position(by_id)
+        .or_else(|| entries.iter().position(by_name))?;
+    Some(entries.remove(idx))
+}
@@ -0,0 +1,146 @@
+// Delimited term/definition text, as exported by Quizlet and most
+// spreadsheet-style flashcard tools
+//
+// Each row holds a term and its definition. Quizlet lets the user pick both
+// separators, so they can be given explicitly or detected from the text.
+
+use serde::Serialize;
+
+/// Tried in order; earlier candidates win ties
+const TERM_SEPARATORS: [&str; 5] = ["\t", " - ", " – ", ",", ":"];
+const ROW_SEPARATORS: [&str; 3] = ["\n\n", "\n", ";"];
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DelimitedPreview {
+    pub term_separator: String,
+    pub row_separator: String,
+    pub cards: Vec<DelimitedCard>,
+    pub skipped_rows: Vec<SkippedRow>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DelimitedCard {
+    pub front: String,
+    pub back: String,
+}
+
+/// A row that had no term separator or an empty side
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedRow {
+    pub row: usize,
+    pub text: String,
+}
+
+/// Parse pasted text, detecting any separator that is not given
+pub fn parse(
+    text: &str,
+    term_separator: Option<&str>,
+    row_separator: Option<&str>,
+) -> Result<DelimitedPreview, String> {
+    let text = text.replace("\r\n", "\n");
+    let term_separator = term_separator.map(resolve_separator).transpose()?;
+    let row_separator = row_separator.map(resolve_separator).transpose()?;
+
+    let (term_separator, row_separator) = match (term_separator, row_separator) {
+        (Some(term), Some(row)) => (term, row),
+        (term, row) => detect_separators(&text, term.as_deref(), row.as_deref())
+            .ok_or("Could not detect the separators; please choose them")?,
+    };
+    if term_separator == row_separator {
+        return Err("Term and row separators must be different".to_string());
+    }
+
+    let mut cards = Vec::new();
+    let mut skipped_rows = Vec::new();
+    for (idx, row) in rows(&text, &row_separator).enumerate() {
+        match split_row(row, &term_separator) {
+            Some((front, back)) => cards.push(DelimitedCard {
+                front: front.to_string(),
+                back: back.to_string(),
+            }),
+            None => skipped_rows.push(SkippedRow {
+                row: idx + 1,
+                text: row.to_string(),
+            }),
+        }
+    }
+
+    Ok(DelimitedPreview {
+        term_separator,
+        row_separator,
+        cards,
+        skipped_rows,
+    })
+}
+
+/// Accept the names Quizlet shows as well as literal and escaped separators
+fn resolve_separator(value: &str) -> Result<String, String> {
+    let separator = match value.to_ascii_lowercase().as_str() {
+        "tab" | "\\t" => "\t".to_string(),
+        "newline" | "new line" | "\\n" => "\n".to_string(),
+        "comma" => ",".to_string(),
+        "semicolon" => ";".to_string(),
+        _ => value.replace("\\t", "\t").replace("\\n", "\n"),
+    };
+
+    if separator.is_empty() {
+        Err("Separator cannot be empty".to_string())
+    } else {
+        Ok(separator)
+    }
+}
+
+fn rows<'a>(text: &'a str, row_separator: &'a str) -> impl Iterator<Item = &'a str> {
+    text.split(row_separator)
+        .map(|row| row.trim())
+        .filter(|row| !row.is_empty())
+}
+
+/// Definitions may contain the term separator, so only the first one splits
+fn split_row<'a>(row: &'a str, term_separator: &str) -> Option<(&'a str, &'a str)> {
+    let (front, back) = row.split_once(term_separator)?;
+    let (front, back) = (front.trim(), back.trim());
+    if front.is_empty() || back.is_empty() {
+        None
+    } else {
+        Some((front, back))
+    }
+}
+
+/// Pick the separator pair that turns the most rows into cards
+fn detect_separators(text: &str, term: Option<&str>, row: Option<&str>) -> Option<(String, String)> {
+    let terms: Vec<&str> = term.map_or(TERM_SEPARATORS.to_vec(), |t| vec![t]);
+    let row_candidates: Vec<&str> = row.map_or(ROW_SEPARATORS.to_vec(), |r| vec![r]);
+
+    let mut best: Option<((usize, usize), &str, &str)> = None;
+    for row_separator in &row_candidates {
+        for term_separator in &terms {
+            if term_separator == row_separator {
+                continue;
+            }
+            let mut matched = 0;
+            let mut unmatched = 0;
+            for row in rows(text, row_separator) {
+                if split_row(row, term_separator).is_some() {
+                    matched += 1;
+                } else {
+                    unmatched += 1;
+                }
+            }
+            if matched == 0 {
+                continue;
+            }
+
+            // More cards first, then fewer skipped rows
+            let score = (matched, usize::MAX - unmatched);
+            if best.map_or(true, |(best_score, _, _)| score > best_score) {
+                best = Some((score, term_separator, row_separator));
+            }
+        }
+    }
+
+    best.map(|(_, term, row)| (term.to_string(), row.to_string()))
+}
@@ -0,0 +1,521 @@
+// Moodle GIFT text format
+//
+// Questions are separated by blank lines. Each one is optional `::title::`,
+// optional `[format]`, the question text, and an answer block in braces:
+//   {=right ~wrong}       multiple choice
+//   {~%50%a ~%50%b ~c}    multiple answers (weights)
+//   {=answer =other}      short answer
+//   {T} / {FALSE}         true/false
+//   {#3.14:0.01}          numeric
+// `#feedback` follows an answer and `####feedback` closes the block.
+// A `// [tag:name]` comment line tags the question it precedes, as in Moodle.
+
+use super::{category_to_tag, code_fence, format_fraction, parse_code_fence, tag_to_category};
+use super::{BankQuestion, ImportWarning};
+use crate::db::{CreateChoiceRequest, CreateQuestionRequest, QuestionType, Quiz};
+
+const SPECIAL_CHARS: [char; 6] = ['~', '=', '#', '{', '}', ':'];
+
+pub fn parse(text: &str) -> (Vec<BankQuestion>, Vec<ImportWarning>) {
+    let mut questions = Vec::new();
+    let mut warnings = Vec::new();
+    let mut category: Option<String> = None;
+
+    for (line_number, block, tags) in split_blocks(text) {
+        if let Some(path) = block.trim().strip_prefix("$CATEGORY:") {
+            category = category_to_tag(path.trim());
+            continue;
+        }
+
+        let label = format!("Question at line {}", line_number);
+        match parse_question(&block, &label, &mut warnings) {
+            Ok(Some(mut question)) => {
+                // The category comes first, so exporting again keeps it
+                for tag in category.iter().chain(&tags) {
+                    if !question.tags.contains(tag) {
+                        question.tags.push(tag.clone());
+                    }
+                }
+                questions.push(question);
+            }
+            Ok(None) => {}
+            Err(message) => warnings.push(ImportWarning::new(&label, message)),
+        }
+    }
+
+    (questions, warnings)
+}
+
+/// Group lines into blank-line separated blocks, dropping `//` comments.
+/// Each block carries the line number it starts on and the tags its
+/// comments name.
+fn split_blocks(text: &str) -> Vec<(usize, String, Vec<String>)> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(usize, String, Vec<String>)> = None;
+    let mut pending_tags: Vec<String> = Vec::new();
+
+    for (idx, line) in text.lines().enumerate() {
+        if let Some(comment) = line.trim_start().strip_prefix("//") {
+            let tags = comment_tags(comment);
+            match current.as_mut() {
+                Some((_, _, block_tags)) => block_tags.extend(tags),
+                None => pending_tags.extend(tags),
+            }
+            continue;
+        }
+        if line.trim().is_empty() {
+            blocks.extend(current.take());
+            continue;
+        }
+
+        // A category line stands on its own even without a blank line after it
+        if line.trim_start().starts_with("$CATEGORY:") {
+            blocks.extend(current.take());
+            blocks.push((idx + 1, line.to_string(), Vec::new()));
+            continue;
+        }
+
+        match current.as_mut() {
+            Some((_, block, _)) => {
+                block.push('\n');
+                block.push_str(line);
+            }
+            None => current = Some((idx + 1, line.to_string(), std::mem::take(&mut pending_tags))),
+        }
+    }
+    blocks.extend(current);
+    blocks
+}
+
+/// The names in `[tag:name]` markers of a comment
+fn comment_tags(comment: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut rest = comment;
+    while let Some(start) = rest.find("[tag:") {
+        let after = &rest[start + 5..];
+        let Some(end) = after.find(']') else { break };
+        let tag = after[..end].trim();
+        if !tag.is_empty() {
+            tags.push(tag.to_string());
+        }
+        rest = &after[end + 1..];
+    }
+    tags
+}
+
+/// Returns `Ok(None)` for questions skipped with a warning already recorded
+fn parse_question(
+    block: &str,
+    label: &str,
+    warnings: &mut Vec<ImportWarning>,
+) -> Result<Option<BankQuestion>, String> {
+    let mut rest = block.trim();
+    let mut label = label.to_string();
+
+    if let Some(after) = rest.strip_prefix("::") {
+        let end = find_unescaped(after, "::").ok_or("Question title is not closed with ::")?;
+        let title = unescape(after[..end].trim());
+        if !title.is_empty() {
+            label = title;
+        }
+        rest = after[end + 2..].trim_start();
+    }
+
+    let format = if rest.starts_with('[') {
+        let end = rest.find(']').ok_or("Text format is not closed with ]")?;
+        let format = rest[1..end].to_ascii_lowercase();
+        rest = &rest[end + 1..];
+        format
+    } else {
+        "moodle".to_string()
+    };
+
+    let open = find_unescaped(rest, "{").ok_or("Missing answer block { }")?;
+    let close = find_unescaped(&rest[open..], "}")
+        .map(|idx| open + idx)
+        .ok_or("Answer block is not closed with }")?;
+
+    let before = unescape(rest[..open].trim());
+    let after = unescape(rest[close + 1..].trim());
+    let body = &rest[open + 1..close];
+
+    // Text continuing after the answers is the "missing word" style
+    let text = if after.is_empty() {
+        before
+    } else {
+        format!("{} _____ {}", before, after)
+    };
+    if text.is_empty() {
+        return Err("Question has no text".to_string());
+    }
+
+    let (content, content_type, content_language) = match parse_code_fence(&text) {
+        Some((language, code)) if format == "markdown" => (code, "CODE", language),
+        _ => (text, "TEXT", None),
+    };
+    if format == "html" && content.contains('<') {
+        warnings.push(ImportWarning::new(&label, "HTML markup was kept as plain text"));
+    }
+
+    let (body, general_feedback) = match find_unescaped(body, "####") {
+        Some(idx) => (&body[..idx], Some(unescape(body[idx + 4..].trim()))),
+        None => (body, None),
+    };
+    let explanation = general_feedback.filter(|f| !f.is_empty());
+
+    let answers = match parse_answers(body.trim(), &label, warnings)? {
+        Some(answers) => answers,
+        None => return Ok(None),
+    };
+
+    let (question_type, correct_answer, multiple_answers, choices) = answers;
+    Ok(Some(BankQuestion {
+        request: CreateQuestionRequest {
+            question_type: match question_type {
+                QuestionType::MultipleChoice => "multiple_choice".to_string(),
+                QuestionType::FillInBlank => "fill_in_blank".to_string(),
+            },
+            content,
+            content_type: Some(content_type.to_string()),
+            content_language,
+            correct_answer,
+            multiple_answers: Some(multiple_answers),
+            explanation,
+            time_limit_seconds: None,
+            choices: Some(choices),
+        },
+        tags: vec![],
+        content_id: None,
+    }))
+}
+
+type ParsedAnswers = (QuestionType, Option<String>, bool, Vec<CreateChoiceRequest>);
+
+fn parse_answers(
+    body: &str,
+    label: &str,
+    warnings: &mut Vec<ImportWarning>,
+) -> Result<Option<ParsedAnswers>, String> {
+    if body.is_empty() {
+        warnings.push(ImportWarning::new(label, "Essay questions are not supported"));
+        return Ok(None);
+    }
+
+    if let Some(numeric) = body.strip_prefix('#') {
+        return parse_numeric(numeric.trim(), label, warnings);
+    }
+
+    let (head, feedback) = match find_unescaped(body, "#") {
+        Some(idx) => (body[..idx].trim(), Some(&body[idx..])),
+        None => (body.trim(), None),
+    };
+    let true_false = match head.to_ascii_uppercase().as_str() {
+        "T" | "TRUE" => Some(true),
+        "F" | "FALSE" => Some(false),
+        _ => None,
+    };
+    if let Some(answer) = true_false {
+        if feedback.is_some() {
+            warnings.push(ImportWarning::new(label, "Answer feedback was dropped"));
+        }
+        let choices = vec![
+            CreateChoiceRequest {
+                text: "True".to_string(),
+                is_correct: answer,
+            },
+            CreateChoiceRequest {
+                text: "False".to_string(),
+                is_correct: !answer,
+            },
+        ];
+        return Ok(Some((QuestionType::MultipleChoice, None, false, choices)));
+    }
+
+    if find_unescaped(body, "->").is_some() {
+        warnings.push(ImportWarning::new(label, "Matching questions are not supported"));
+        return Ok(None);
+    }
+
+    let tokens = split_answer_tokens(body);
+    if tokens.is_empty() {
+        return Err("Answer block has no answers".to_string());
+    }
+
+    let mut dropped_feedback = false;
+    let mut answers: Vec<(char, Option<f64>, String)> = Vec::new();
+    for (marker, raw) in tokens {
+        let (raw, answer_feedback) = match find_unescaped(raw, "#") {
+            Some(idx) => (&raw[..idx], Some(raw[idx + 1..].trim())),
+            None => (raw, None),
+        };
+        dropped_feedback |= answer_feedback.is_some_and(|f| !f.is_empty());
+
+        let (weight, raw) = parse_weight(raw.trim())?;
+        answers.push((marker, weight, unescape(raw.trim())));
+    }
+    if dropped_feedback {
+        warnings.push(ImportWarning::new(label, "Answer feedback was dropped"));
+    }
+
+    // Only `=` answers means short answer
+    if answers.iter().all(|(marker, _, _)| *marker == '=') {
+        let mut accepted = answers
+            .iter()
+            .filter(|(_, weight, _)| weight.map_or(true, |w| w >= 100.0));
+        let (_, _, first) = accepted
+            .next()
+            .ok_or("Short answer question has no fully correct answer")?;
+        if accepted.next().is_some() {
+            warnings.push(ImportWarning::new(
+                label,
+                "Only the first accepted answer was kept",
+            ));
+        }
+        if answers.iter().any(|(_, weight, _)| weight.is_some_and(|w| w < 100.0)) {
+            warnings.push(ImportWarning::new(label, "Partial credit answers were dropped"));
+        }
+        return Ok(Some((QuestionType::FillInBlank, Some(first.clone()), false, vec![])));
+    }
+
+    let weighted = answers.iter().any(|(_, weight, _)| weight.is_some());
+    let choices: Vec<CreateChoiceRequest> = answers
+        .iter()
+        .map(|(marker, weight, text)| CreateChoiceRequest {
+            text: text.clone(),
+            is_correct: match weight {
+                Some(w) => *w > 0.0,
+                None => *marker == '=',
+            },
+        })
+        .collect();
+
+    let correct_count = choices.iter().filter(|c| c.is_correct).count();
+    if correct_count == 0 {
+        return Err("Multiple choice question has no correct answer".to_string());
+    }
+    let partial_single = answers
+        .iter()
+        .any(|(_, weight, _)| weight.is_some_and(|w| w > 0.0 && w < 100.0));
+    if partial_single && correct_count == 1 {
+        warnings.push(ImportWarning::new(label, "Partial credit answers were dropped"));
+    }
+
+    // Weighted `~` answers are Moodle's "multiple answers" layout
+    let multiple_answers = weighted && answers.iter().all(|(marker, _, _)| *marker == '~');
+    Ok(Some((QuestionType::MultipleChoice, None, multiple_answers, choices)))
+}
+
+fn parse_numeric(
+    body: &str,
+    label: &str,
+    warnings: &mut Vec<ImportWarning>,
+) -> Result<Option<ParsedAnswers>, String> {
+    // Either a single `value[:tolerance]` / `min..max`, or several `=` answers
+    let first = if body.starts_with('=') {
+        let tokens = split_answer_tokens(body);
+        if tokens.len() > 1 {
+            warnings.push(ImportWarning::new(label, "Only the first accepted answer was kept"));
+        }
+        let (_, raw) = tokens.first().ok_or("Numeric question has no answer")?;
+        let (_, raw) = parse_weight(raw.trim())?;
+        raw.trim().to_string()
+    } else {
+        body.to_string()
+    };
+
+    let first = match find_unescaped(&first, "#") {
+        Some(idx) => first[..idx].trim().to_string(),
+        None => first,
+    };
+
+    if first.contains("..") {
+        warnings.push(ImportWarning::new(label, "Numeric ranges are not supported"));
+        return Ok(None);
+    }
+
+    let (value, tolerance) = match first.split_once(':') {
+        Some((value, tolerance)) => (value.trim(), Some(tolerance.trim())),
+        None => (first.trim(), None),
+    };
+    value
+        .parse::<f64>()
+        .map_err(|_| format!("Invalid numeric answer: {}", value))?;
+    if tolerance.is_some_and(|t| !t.parse::<f64>().is_ok_and(|t| t == 0.0)) {
+        warnings.push(ImportWarning::new(
+            label,
+            "Numeric tolerance is not supported; only the exact value is accepted",
+        ));
+    }
+
+    Ok(Some((QuestionType::FillInBlank, Some(value.to_string()), false, vec![])))
+}
+
+/// Split an answer block into (`=` or `~`, text) pairs
+fn split_answer_tokens(body: &str) -> Vec<(char, &str)> {
+    let mut tokens = Vec::new();
+    let mut current: Option<(char, usize)> = None;
+    let mut escaped = false;
+
+    for (idx, ch) in body.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match ch {
+            '\\' => escaped = true,
+            '=' | '~' => {
+                if let Some((marker, start)) = current {
+                    tokens.push((marker, &body[start..idx]));
+                }
+                current = Some((ch, idx + 1));
+            }
+            _ => {}
+        }
+    }
+    if let Some((marker, start)) = current {
+        tokens.push((marker, &body[start..]));
+    }
+    tokens
+}
+
+/// Split a leading `%50%` weight off an answer
+fn parse_weight(raw: &str) -> Result<(Option<f64>, &str), String> {
+    let Some(rest) = raw.strip_prefix('%') else {
+        return Ok((None, raw));
+    };
+    let end = rest.find('%').ok_or("Answer weight is not closed with %")?;
+    let weight = rest[..end]
+        .trim()
+        .parse::<f64>()
+        .map_err(|_| format!("Invalid answer weight: {}", &rest[..end]))?;
+    Ok((Some(weight), &rest[end + 1..]))
+}
+
+fn find_unescaped(text: &str, pattern: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (idx, ch) in text.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        if ch == '\\' {
+            escaped = true;
+            continue;
+        }
+        if text[idx..].starts_with(pattern) {
+            return Some(idx);
+        }
+    }
+    None
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some(next) => result.push(next),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            ch if SPECIAL_CHARS.contains(&ch) => {
+                result.push('\\');
+                result.push(ch);
+            }
+            ch => result.push(ch),
+        }
+    }
+    result
+}
+
+// ============================================
+// Export
+// ============================================
+
+pub fn write(quiz: &Quiz) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("// {}\n", quiz.name.replace('\n', " ")));
+    if let Some(description) = quiz.description.as_deref().filter(|d| !d.is_empty()) {
+        for line in description.lines() {
+            out.push_str(&format!("// {}\n", line));
+        }
+    }
+    out.push('\n');
+
+    // GIFT has one category per question; the first tag is used and the
+    // rest go in a tag comment
+    let mut current_category: Option<String> = None;
+    for question in &quiz.questions {
+        let category = tag_to_category(question.tags.first().map(|t| t.name.as_str()));
+        if current_category.as_deref() != Some(category.as_str()) {
+            if current_category.is_some() || !question.tags.is_empty() {
+                out.push_str(&format!("$CATEGORY: {}\n\n", category));
+            }
+            current_category = Some(category);
+        }
+
+        let other_tags: Vec<String> = question
+            .tags
+            .iter()
+            .skip(1)
+            .filter(|t| !t.name.contains(']'))
+            .map(|t| format!("[tag:{}]", t.name))
+            .collect();
+        if !other_tags.is_empty() {
+            out.push_str(&format!("// {}\n", other_tags.join(" ")));
+        }
+
+        let text = if question.content_type == "CODE" {
+            format!(
+                "[markdown]{}",
+                escape(&code_fence(question.content_language.as_deref(), &question.content))
+            )
+        } else {
+            escape(&question.content)
+        };
+        out.push_str(&text);
+        out.push_str(" {\n");
+
+        match question.question_type {
+            QuestionType::FillInBlank => {
+                let answer = question.correct_answer.as_deref().unwrap_or("");
+                out.push_str(&format!("\t={}\n", escape(answer)));
+            }
+            QuestionType::MultipleChoice if question.multiple_answers => {
+                let correct = question.choices.iter().filter(|c| c.is_correct).count().max(1);
+                let weight = format_fraction(100.0 / correct as f64);
+                for choice in &question.choices {
+                    let weight = if choice.is_correct { weight.as_str() } else { "-100" };
+                    out.push_str(&format!("\t~%{}%{}\n", weight, escape(&choice.text)));
+                }
+            }
+            QuestionType::MultipleChoice => {
+                for choice in &question.choices {
+                    let marker = if choice.is_correct { '=' } else { '~' };
+                    out.push_str(&format!("\t{}{}\n", marker, escape(&choice.text)));
+                }
+            }
+        }
+
+        if let Some(explanation) = question.explanation.as_deref().filter(|e| !e.is_empty()) {
+            out.push_str(&format!("\t####{}\n", escape(explanation)));
+        }
+        out.push_str("}\n\n");
+    }
+
+    out
+}
@@ -0,0 +1,581 @@
+// Markdown authoring format for decks and quizzes
+//
+//     # Deck or quiz name
+//     Optional description.
+//
+//     ## Q: What does `mut` mean?
+//     ### A: The binding can be reassigned.
+//     > Notes (decks) or explanation (quizzes)
+//     #rust #syntax #"ownership rules"
+//
+//     ## Q (multiple): Which are prime?
+//     - [x] 2
+//     - [x] 3
+//     - [ ] 4
+//
+// A prompt or answer that is a single fenced code block becomes CODE content
+// with the fence's language. Quiz questions with choices are multiple choice;
+// with `### A:` they are fill-in-the-blank. `(multiple)` marks a question that
+// accepts several answers even if only one is checked. Text lines that would
+// read as markers are escaped with a leading backslash.
+
+use super::{BankQuestion, ImportedCard};
+use crate::db::{Card, CreateCardRequest, CreateChoiceRequest, CreateQuestionRequest, Deck, QuestionType, Quiz};
+
+pub struct MarkdownDocument {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    entries: Vec<Entry>,
+}
+
+struct Entry {
+    line: usize,
+    multiple: bool,
+    prompt: Vec<String>,
+    answer: Option<Vec<String>>,
+    choices: Vec<(bool, String)>,
+    note: Vec<String>,
+    tags: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    Prompt,
+    Answer,
+    Choices,
+    Note,
+    Tags,
+}
+
+/// Text content with its Kioku content type and language
+struct Content {
+    text: String,
+    content_type: &'static str,
+    language: Option<String>,
+}
+
+// ============================================
+// Parsing
+// ============================================
+
+pub fn parse(text: &str) -> Result<MarkdownDocument, String> {
+    let mut title: Option<String> = None;
+    let mut description: Vec<String> = Vec::new();
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut section = Section::Prompt;
+    let mut open_fence: Option<usize> = None;
+
+    for (idx, raw) in text.lines().enumerate() {
+        let line_number = idx + 1;
+        let line = raw.trim_end();
+        let trimmed = line.trim_start();
+
+        if open_fence.is_some() {
+            if trimmed.starts_with("```") && trimmed.trim_end_matches('`').is_empty() {
+                open_fence = None;
+            }
+            push_line(&mut entries, &mut description, section, line.to_string());
+            continue;
+        }
+
+        if trimmed.starts_with("```") {
+            if matches!(section, Section::Choices | Section::Note | Section::Tags) && !entries.is_empty() {
+                return Err(format!(
+                    "Line {}: code blocks must come before choices, notes and tags",
+                    line_number
+                ));
+            }
+            open_fence = Some(line_number);
+            push_line(&mut entries, &mut description, section, line.to_string());
+            continue;
+        }
+
+        if let Some((multiple, prompt)) = parse_question_marker(line, line_number)? {
+            entries.push(Entry {
+                line: line_number,
+                multiple,
+                prompt: first_line(prompt),
+                answer: None,
+                choices: Vec::new(),
+                note: Vec::new(),
+                tags: Vec::new(),
+            });
+            section = Section::Prompt;
+            continue;
+        }
+
+        if line.starts_with("# ") && title.is_none() && entries.is_empty() && description.is_empty() {
+            title = Some(line[2..].trim().to_string());
+            continue;
+        }
+
+        let Some(entry) = entries.last_mut() else {
+            description.push(unescape(line).to_string());
+            continue;
+        };
+
+        if let Some(answer) = line.strip_prefix("### A:") {
+            if entry.answer.is_some() {
+                return Err(format!("Line {}: question already has an answer", line_number));
+            }
+            if !entry.choices.is_empty() {
+                return Err(format!(
+                    "Line {}: a question cannot have both choices and `### A:`",
+                    line_number
+                ));
+            }
+            if section != Section::Prompt {
+                return Err(format!("Line {}: `### A:` must follow the question text", line_number));
+            }
+            entry.answer = Some(first_line(answer));
+            section = Section::Answer;
+            continue;
+        }
+
+        if let Some((is_correct, choice)) = parse_choice(line) {
+            if entry.answer.is_some() {
+                return Err(format!(
+                    "Line {}: a question cannot have both `### A:` and choices",
+                    line_number
+                ));
+            }
+            if !matches!(section, Section::Prompt | Section::Choices) {
+                return Err(format!("Line {}: choices must follow the question text", line_number));
+            }
+            entry.choices.push((is_correct, choice.to_string()));
+            section = Section::Choices;
+            continue;
+        }
+
+        if let Some(note) = line.strip_prefix('>') {
+            if section == Section::Tags {
+                return Err(format!("Line {}: tags must be the last line of a question", line_number));
+            }
+            entry.note.push(note.strip_prefix(' ').unwrap_or(note).to_string());
+            section = Section::Note;
+            continue;
+        }
+
+        if let Some(tags) = parse_tag_line(line) {
+            entry.tags.extend(tags);
+            section = Section::Tags;
+            continue;
+        }
+
+        match section {
+            Section::Prompt | Section::Answer => {
+                push_line(&mut entries, &mut description, section, unescape(line).to_string())
+            }
+            _ if line.is_empty() => {}
+            Section::Choices if raw.starts_with("  ") => {
+                // Indented lines continue the previous choice
+                if let Some((_, choice)) = entry.choices.last_mut() {
+                    choice.push('\n');
+                    choice.push_str(unescape(trimmed));
+                }
+            }
+            Section::Choices => {
+                return Err(format!(
+                    "Line {}: unexpected text after choices; indent it to continue a choice",
+                    line_number
+                ))
+            }
+            Section::Note => {
+                return Err(format!(
+                    "Line {}: unexpected text after notes; start each note line with `>`",
+                    line_number
+                ))
+            }
+            Section::Tags => {
+                return Err(format!("Line {}: unexpected text after tags", line_number))
+            }
+        }
+    }
+
+    if let Some(fence_line) = open_fence {
+        return Err(format!("Line {}: code block is never closed", fence_line));
+    }
+
+    let description = trim_blank_lines(&description).join("\n");
+    Ok(MarkdownDocument {
+        title,
+        description: if description.is_empty() { None } else { Some(description) },
+        entries,
+    })
+}
+
+fn push_line(entries: &mut [Entry], description: &mut Vec<String>, section: Section, line: String) {
+    match entries.last_mut() {
+        None => description.push(line),
+        Some(entry) => match section {
+            Section::Answer => entry.answer.get_or_insert_with(Vec::new).push(line),
+            _ => entry.prompt.push(line),
+        },
+    }
+}
+
+/// `## Q:` or `## Q (multiple):`, with the rest of the line. Other `## Q…`
+/// headings, such as `## Quick facts`, are ordinary text.
+fn parse_question_marker(line: &str, line_number: usize) -> Result<Option<(bool, &str)>, String> {
+    let Some(rest) = line.strip_prefix("## Q") else {
+        return Ok(None);
+    };
+    let (multiple, rest) = match rest.trim_start().strip_prefix("(multiple)") {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+    match rest.trim_start().strip_prefix(':') {
+        Some(prompt) => Ok(Some((multiple, prompt))),
+        None if multiple => Err(format!("Line {}: expected `## Q (multiple):`", line_number)),
+        None => Ok(None),
+    }
+}
+
+fn first_line(rest: &str) -> Vec<String> {
+    let rest = rest.trim();
+    if rest.is_empty() {
+        Vec::new()
+    } else {
+        vec![rest.to_string()]
+    }
+}
+
+fn parse_choice(line: &str) -> Option<(bool, &str)> {
+    let rest = line.strip_prefix("- [")?;
+    let (mark, text) = rest.split_once("] ").or_else(|| rest.strip_suffix(']').map(|m| (m, "")))?;
+    match mark {
+        "x" | "X" => Some((true, text.trim())),
+        " " => Some((false, text.trim())),
+        _ => None,
+    }
+}
+
+/// A line made only of `#tag` words. Tags with spaces are quoted, as in
+/// `#"two words"`, with `\` escaping a quote or backslash inside.
+fn parse_tag_line(line: &str) -> Option<Vec<String>> {
+    let mut tags = Vec::new();
+    let mut chars = line.trim().chars().peekable();
+
+    while chars.peek().is_some() {
+        if chars.next()? != '#' {
+            return None;
+        }
+        let mut tag = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => tag.push(chars.next()?),
+                    c => tag.push(c),
+                }
+            }
+            if tag.trim().is_empty() || chars.peek().is_some_and(|c| !c.is_whitespace()) {
+                return None;
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                tag.push(c);
+            }
+            if tag.is_empty() || tag.starts_with('#') {
+                return None;
+            }
+        }
+        tags.push(tag);
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    (!tags.is_empty()).then_some(tags)
+}
+
+fn is_marker_line(line: &str) -> bool {
+    line.starts_with("## Q")
+        || line.starts_with("### A:")
+        || line.starts_with("# ")
+        || line.starts_with('>')
+        || line.starts_with('\\')
+        || parse_choice(line).is_some()
+        || parse_tag_line(line).is_some()
+}
+
+fn unescape(line: &str) -> &str {
+    match line.strip_prefix('\\') {
+        Some(rest) if is_marker_line(rest) => rest,
+        _ => line,
+    }
+}
+
+fn trim_blank_lines(lines: &[String]) -> &[String] {
+    let start = lines.iter().position(|l| !l.trim().is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|l| !l.trim().is_empty()).map_or(start, |e| e + 1);
+    &lines[start..end]
+}
+
+fn content_from_lines(lines: &[String]) -> Content {
+    let text = trim_blank_lines(lines).join("\n");
+    match super::parse_code_fence(&text) {
+        Some((language, code)) => Content {
+            text: code,
+            content_type: "CODE",
+            language,
+        },
+        None => Content {
+            text,
+            content_type: "TEXT",
+            language: None,
+        },
+    }
+}
+
+fn note_from_lines(lines: &[String]) -> Option<String> {
+    let note = trim_blank_lines(lines).join("\n");
+    if note.is_empty() {
+        None
+    } else {
+        Some(note)
+    }
+}
+
+impl MarkdownDocument {
+    pub fn into_cards(self) -> Result<Vec<ImportedCard>, String> {
+        if self.entries.is_empty() {
+            return Err("No cards found; start each card with `## Q:`".to_string());
+        }
+
+        self.entries
+            .into_iter()
+            .map(|entry| {
+                if !entry.choices.is_empty() {
+                    return Err(format!("Line {}: choices are only supported in quizzes", entry.line));
+                }
+                let front = content_from_lines(&entry.prompt);
+                if front.text.is_empty() {
+                    return Err(format!("Line {}: card has no front text", entry.line));
+                }
+                let back = content_from_lines(entry.answer.as_deref().unwrap_or_default());
+                if back.text.is_empty() {
+                    return Err(format!("Line {}: card has no `### A:` answer", entry.line));
+                }
+
+                Ok(ImportedCard {
+                    request: CreateCardRequest {
+                        front: front.text,
+                        front_type: Some(front.content_type.to_string()),
+                        front_language: front.language,
+                        back: back.text,
+                        back_type: Some(back.content_type.to_string()),
+                        back_language: back.language,
+                        notes: note_from_lines(&entry.note),
+                    },
+                    tags: entry.tags,
+                    content_id: None,
+                })
+            })
+            .collect()
+    }
+
+    pub fn into_questions(self) -> Result<Vec<BankQuestion>, String> {
+        if self.entries.is_empty() {
+            return Err("No questions found; start each question with `## Q:`".to_string());
+        }
+
+        self.entries
+            .into_iter()
+            .map(|entry| {
+                let content = content_from_lines(&entry.prompt);
+                if content.text.is_empty() {
+                    return Err(format!("Line {}: question has no text", entry.line));
+                }
+
+                let (question_type, correct_answer, choices) = match entry.answer {
+                    Some(answer) => {
+                        if entry.multiple {
+                            return Err(format!(
+                                "Line {}: `(multiple)` needs choices, not `### A:`",
+                                entry.line
+                            ));
+                        }
+                        let answer = trim_blank_lines(&answer).join("\n");
+                        if answer.is_empty() {
+                            return Err(format!("Line {}: answer is empty", entry.line));
+                        }
+                        ("fill_in_blank", Some(answer), Vec::new())
+                    }
+                    None if entry.choices.is_empty() => {
+                        return Err(format!(
+                            "Line {}: question needs choices or an `### A:` answer",
+                            entry.line
+                        ))
+                    }
+                    None => {
+                        if !entry.choices.iter().any(|(correct, _)| *correct) {
+                            return Err(format!(
+                                "Line {}: no choice is marked correct with `- [x]`",
+                                entry.line
+                            ));
+                        }
+                        let choices = entry
+                            .choices
+                            .iter()
+                            .map(|(is_correct, text)| CreateChoiceRequest {
+                                text: text.clone(),
+                                is_correct: *is_correct,
+                            })
+                            .collect();
+                        ("multiple_choice", None, choices)
+                    }
+                };
+                let multiple_answers =
+                    entry.multiple || entry.choices.iter().filter(|(correct, _)| *correct).count() > 1;
+
+                Ok(BankQuestion {
+                    request: CreateQuestionRequest {
+                        question_type: question_type.to_string(),
+                        content: content.text,
+                        content_type: Some(content.content_type.to_string()),
+                        content_language: content.language,
+                        correct_answer,
+                        multiple_answers: Some(multiple_answers),
+                        explanation: note_from_lines(&entry.note),
+                        time_limit_seconds: None,
+                        choices: Some(choices),
+                    },
+                    tags: entry.tags,
+                    content_id: None,
+                })
+            })
+            .collect()
+    }
+}
+
+// ============================================
+// Writing
+// ============================================
+
+pub fn write_deck(deck: &Deck, cards: &[Card]) -> String {
+    let mut out = String::new();
+    write_header(&mut out, &deck.name, deck.description.as_deref());
+
+    for card in cards {
+        write_prompt(&mut out, "## Q:", &card.front, &card.front_type, card.front_language.as_deref());
+        write_prompt(&mut out, "### A:", &card.back, &card.back_type, card.back_language.as_deref());
+        write_note(&mut out, card.notes.as_deref());
+        write_tags(&mut out, card.tags.iter().map(|t| t.name.as_str()));
+        out.push('\n');
+    }
+
+    out
+}
+
+pub fn write_quiz(quiz: &Quiz) -> String {
+    let mut out = String::new();
+    write_header(&mut out, &quiz.name, quiz.description.as_deref());
+
+    for question in &quiz.questions {
+        let correct = question.choices.iter().filter(|c| c.is_correct).count();
+        let marker = if question.multiple_answers && correct < 2 {
+            "## Q (multiple):"
+        } else {
+            "## Q:"
+        };
+        write_prompt(
+            &mut out,
+            marker,
+            &question.content,
+            &question.content_type,
+            question.content_language.as_deref(),
+        );
+
+        match question.question_type {
+            QuestionType::FillInBlank => {
+                let answer = question.correct_answer.as_deref().unwrap_or("");
+                write_prompt(&mut out, "### A:", answer, "TEXT", None);
+            }
+            QuestionType::MultipleChoice => {
+                for choice in &question.choices {
+                    let mut lines = choice.text.lines();
+                    out.push_str(&format!(
+                        "- [{}] {}\n",
+                        if choice.is_correct { "x" } else { " " },
+                        lines.next().unwrap_or("")
+                    ));
+                    for line in lines {
+                        out.push_str(&format!("  {}\n", line));
+                    }
+                }
+            }
+        }
+
+        write_note(&mut out, question.explanation.as_deref());
+        write_tags(&mut out, question.tags.iter().map(|t| t.name.as_str()));
+        out.push('\n');
+    }
+
+    out
+}
+
+fn write_header(out: &mut String, name: &str, description: Option<&str>) {
+    out.push_str(&format!("# {}\n\n", name));
+    if let Some(description) = description.filter(|d| !d.trim().is_empty()) {
+        out.push_str(&escape_block(description));
+        out.push_str("\n\n");
+    }
+}
+
+fn write_prompt(out: &mut String, marker: &str, text: &str, content_type: &str, language: Option<&str>) {
+    if content_type == "CODE" {
+        out.push_str(&format!("{}\n{}\n", marker, super::code_fence(language, text)));
+    } else if !text.contains('\n') && !text.trim().is_empty() {
+        out.push_str(&format!("{} {}\n", marker, text.trim()));
+    } else {
+        out.push_str(&format!("{}\n{}\n", marker, escape_block(text)));
+    }
+}
+
+fn write_note(out: &mut String, note: Option<&str>) {
+    let Some(note) = note.filter(|n| !n.trim().is_empty()) else {
+        return;
+    };
+    for line in note.lines() {
+        if line.is_empty() {
+            out.push_str(">\n");
+        } else {
+            out.push_str(&format!("> {}\n", line));
+        }
+    }
+}
+
+fn write_tags<'a>(out: &mut String, tags: impl Iterator<Item = &'a str>) {
+    let tags: Vec<String> = tags
+        .map(|t| {
+            if t.contains(char::is_whitespace) || t.starts_with(['"', '#']) {
+                format!("#\"{}\"", t.replace('\\', "\\\\").replace('"', "\\\""))
+            } else {
+                format!("#{}", t)
+            }
+        })
+        .collect();
+    if !tags.is_empty() {
+        out.push_str(&tags.join(" "));
+        out.push('\n');
+    }
+}
+
+/// Escape lines of free text that would otherwise read as markers
+fn escape_block(text: &str) -> String {
+    let mut in_fence = false;
+    text.lines()
+        .map(|line| {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+                return line.to_string();
+            }
+            if !in_fence && is_marker_line(line) {
+                format!("\\{}", line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,509 @@
+// Re-importing updated files in place
+//
+// JSON exports carry a content ID for each course, deck, card, quiz,
+// question, notebook and page. With `update_existing`, an import matches those IDs against what
+// earlier imports created and edits the rows it finds, so study sessions,
+// attempts and course progress stay attached to them.
+
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use super::{BankQuestion, ImportedCard, ImportedPage};
+use crate::db::{
+    self, Card, CreateCardRequest, CreateNotebookRequest, CreatePageRequest, CreateQuestionRequest,
+    CreateQuizRequest, Deck, Notebook, Page, Question, QuestionType, Quiz, QuizPoolRuleRequest,
+    UpdateCardRequest, UpdateNotebookRequest, UpdatePageRequest, UpdateQuestionRequest,
+    UpdateQuizRequest,
+};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportOptions {
+    /// Update content created by an earlier import of the same file instead of copying it
+    #[serde(default)]
+    pub update_existing: bool,
+    /// Delete cards and questions that are no longer in the file
+    #[serde(default)]
+    pub delete_missing: bool,
+}
+
+/// How the cards or questions in a file compare with what was already saved
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub deleted: usize,
+    /// Missing from the file but left in place because `delete_missing` was off
+    pub kept: usize,
+}
+
+impl MergeSummary {
+    pub fn include(&mut self, other: &MergeSummary) {
+        self.added += other.added;
+        self.updated += other.updated;
+        self.unchanged += other.unchanged;
+        self.deleted += other.deleted;
+        self.kept += other.kept;
+    }
+}
+
+/// Content IDs match rows on re-import, so one file may not use an ID twice
+pub fn check_unique_ids<'a>(kind: &str, ids: impl IntoIterator<Item = Option<&'a str>>) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for id in ids.into_iter().flatten() {
+        if !seen.insert(id) {
+            return Err(format!("The file has more than one {} with the ID \"{}\"", kind, id));
+        }
+    }
+    Ok(())
+}
+
+// ============================================
+// Decks
+// ============================================
+
+/// Reuse the deck an earlier import created from the same source, or create one.
+/// Returns whether an existing deck was updated.
+pub fn upsert_deck(
+    conn: &Connection,
+    user_id: &str,
+    content_id: Option<&str>,
+    name: &str,
+    description: Option<&str>,
+    shuffle_cards: bool,
+    options: &ImportOptions,
+) -> Result<(Deck, bool), String> {
+    let existing = match content_id {
+        Some(content_id) => db::find_deck_by_content_id(conn, user_id, content_id)?,
+        None => None,
+    };
+    if let Some(existing) = existing.as_ref().filter(|_| options.update_existing) {
+        let deck = db::update_deck(conn, &existing.id, name, description, shuffle_cards)?;
+        return Ok((deck, true));
+    }
+
+    let deck = db::create_deck(conn, user_id, name, description, shuffle_cards)?;
+    // A copy made next to the original gets an identity of its own
+    if let Some(content_id) = content_id.filter(|_| existing.is_none()) {
+        db::set_deck_content_id(conn, &deck.id, content_id)?;
+    }
+    Ok((deck, false))
+}
+
+/// Add, update and optionally delete cards so the deck matches the file
+pub fn save_cards(
+    conn: &Connection,
+    deck_id: &str,
+    cards: Vec<ImportedCard>,
+    delete_missing: bool,
+) -> Result<MergeSummary, String> {
+    check_unique_ids("card", cards.iter().map(|c| c.content_id.as_deref()))?;
+    let mut existing: HashMap<String, Card> = db::get_cards_for_deck(conn, deck_id)?
+        .into_iter()
+        .map(|card| (card.content_id.clone(), card))
+        .collect();
+    let mut tag_ids: HashMap<String, String> = HashMap::new();
+    let mut summary = MergeSummary::default();
+
+    for card in cards {
+        let current = card.content_id.as_ref().and_then(|id| existing.remove(id));
+        let Some(current) = current else {
+            let created = db::create_card(conn, deck_id, &card.request)?;
+            if let Some(content_id) = &card.content_id {
+                db::set_card_content_id(conn, &created.id, content_id)?;
+            }
+            for tag_name in &card.tags {
+                let tag_id = deck_tag_id(conn, deck_id, tag_name, &mut tag_ids)?;
+                db::add_tag_to_card(conn, deck_id, &created.id, &tag_id)?;
+            }
+            summary.added += 1;
+            continue;
+        };
+
+        let mut changed = false;
+        if card_changed(&current, &card.request) {
+            let request = UpdateCardRequest {
+                front: card.request.front,
+                front_type: card.request.front_type,
+                front_language: card.request.front_language,
+                back: card.request.back,
+                back_type: card.request.back_type,
+                back_language: card.request.back_language,
+                notes: card.request.notes,
+            };
+            db::update_card(conn, &current.id, deck_id, &request)?;
+            changed = true;
+        }
+
+        let wanted: HashSet<&String> = card.tags.iter().collect();
+        for tag in &current.tags {
+            if !wanted.contains(&tag.name) {
+                db::remove_tag_from_card(conn, deck_id, &current.id, &tag.id)?;
+                changed = true;
+            }
+        }
+        for tag_name in &card.tags {
+            if !current.tags.iter().any(|t| &t.name == tag_name) {
+                let tag_id = deck_tag_id(conn, deck_id, tag_name, &mut tag_ids)?;
+                db::add_tag_to_card(conn, deck_id, &current.id, &tag_id)?;
+                changed = true;
+            }
+        }
+
+        if changed {
+            summary.updated += 1;
+        } else {
+            summary.unchanged += 1;
+        }
+    }
+
+    for card in existing.into_values() {
+        if delete_missing {
+            db::delete_card(conn, &card.id, deck_id)?;
+            summary.deleted += 1;
+        } else {
+            summary.kept += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+fn card_changed(card: &Card, request: &CreateCardRequest) -> bool {
+    card.front != request.front
+        || card.front_type != request.front_type.as_deref().unwrap_or("TEXT")
+        || card.front_language != request.front_language
+        || card.back != request.back
+        || card.back_type != request.back_type.as_deref().unwrap_or("TEXT")
+        || card.back_language != request.back_language
+        || card.notes != request.notes
+}
+
+fn deck_tag_id(
+    conn: &Connection,
+    deck_id: &str,
+    name: &str,
+    cache: &mut HashMap<String, String>,
+) -> Result<String, String> {
+    if let Some(id) = cache.get(name) {
+        return Ok(id.clone());
+    }
+    let tag = match db::get_tag_by_name(conn, deck_id, name)? {
+        Some(existing) => existing,
+        None => db::create_tag(conn, deck_id, name)?,
+    };
+    cache.insert(name.to_string(), tag.id.clone());
+    Ok(tag.id)
+}
+
+// ============================================
+// Quizzes
+// ============================================
+
+/// Reuse the quiz an earlier import created from the same source, or create one.
+/// Returns whether an existing quiz was updated.
+pub fn upsert_quiz(
+    conn: &Connection,
+    user_id: &str,
+    content_id: Option<&str>,
+    request: &CreateQuizRequest,
+    options: &ImportOptions,
+) -> Result<(Quiz, bool), String> {
+    let existing = match content_id {
+        Some(content_id) => db::find_quiz_by_content_id(conn, user_id, content_id)?,
+        None => None,
+    };
+    if let Some(existing) = existing.as_ref().filter(|_| options.update_existing) {
+        let update = UpdateQuizRequest {
+            name: request.name.clone(),
+            description: request.description.clone(),
+            shuffle_questions: request.shuffle_questions,
+            pool_size: request.pool_size,
+            shuffle_choices: request.shuffle_choices,
+            time_limit_seconds: request.time_limit_seconds,
+        };
+        // The file's pool rules replace the old ones once its tags are saved,
+        // so the old rules must not hold back a smaller pool size
+        db::set_pool_rules_for_quiz(conn, &existing.id, &[])?;
+        let quiz = db::update_quiz(conn, &existing.id, &update)?;
+        return Ok((quiz, true));
+    }
+
+    let quiz = db::create_quiz(conn, user_id, request)?;
+    if let Some(content_id) = content_id.filter(|_| existing.is_none()) {
+        db::set_quiz_content_id(conn, &quiz.id, content_id)?;
+    }
+    Ok((quiz, false))
+}
+
+/// Add, update and optionally delete questions so the quiz matches the file,
+/// in the file's order
+pub fn save_questions(
+    conn: &Connection,
+    quiz_id: &str,
+    questions: Vec<BankQuestion>,
+    delete_missing: bool,
+) -> Result<MergeSummary, String> {
+    check_unique_ids("question", questions.iter().map(|q| q.content_id.as_deref()))?;
+    let current_questions = db::get_questions_for_quiz(conn, quiz_id)?;
+    let mut existing: HashMap<String, Question> = current_questions
+        .iter()
+        .map(|q| (q.content_id.clone(), q.clone()))
+        .collect();
+    let mut tag_ids: HashMap<String, String> = HashMap::new();
+    let mut summary = MergeSummary::default();
+    let mut order: Vec<String> = Vec::new();
+
+    for question in questions {
+        let current = question.content_id.as_ref().and_then(|id| existing.remove(id));
+        let Some(current) = current else {
+            let created = db::create_question(conn, quiz_id, &question.request)?;
+            if let Some(content_id) = &question.content_id {
+                db::set_question_content_id(conn, &created.id, content_id)?;
+            }
+            for tag_name in &question.tags {
+                let tag_id = quiz_tag_id(conn, quiz_id, tag_name, &mut tag_ids)?;
+                db::add_tag_to_question(conn, &created.id, &tag_id)?;
+            }
+            order.push(created.id);
+            summary.added += 1;
+            continue;
+        };
+
+        let mut changed = false;
+        if question_changed(&current, &question.request) {
+            let request = UpdateQuestionRequest {
+                question_type: question.request.question_type.clone(),
+                content: question.request.content.clone(),
+                content_type: question.request.content_type.clone(),
+                content_language: question.request.content_language.clone(),
+                correct_answer: question.request.correct_answer.clone(),
+                multiple_answers: question.request.multiple_answers,
+                explanation: question.request.explanation.clone(),
+                time_limit_seconds: question.request.time_limit_seconds,
+            };
+            db::update_question(conn, &current.id, &request)?;
+            changed = true;
+        }
+        if choices_changed(&current, &question.request) {
+            let choices = question.request.choices.unwrap_or_default();
+            db::update_choices_for_question(conn, &current.id, &choices)?;
+            changed = true;
+        }
+
+        let wanted: HashSet<&String> = question.tags.iter().collect();
+        for tag in &current.tags {
+            if !wanted.contains(&tag.name) {
+                db::remove_tag_from_question(conn, &current.id, &tag.id)?;
+                changed = true;
+            }
+        }
+        for tag_name in &question.tags {
+            if !current.tags.iter().any(|t| &t.name == tag_name) {
+                let tag_id = quiz_tag_id(conn, quiz_id, tag_name, &mut tag_ids)?;
+                db::add_tag_to_question(conn, &current.id, &tag_id)?;
+                changed = true;
+            }
+        }
+
+        if changed {
+            summary.updated += 1;
+        } else {
+            summary.unchanged += 1;
+        }
+        order.push(current.id);
+    }
+
+    // Questions the file no longer has keep their relative order after the rest
+    for question in current_questions {
+        if !existing.contains_key(&question.content_id) {
+            continue;
+        }
+        if delete_missing {
+            db::delete_question(conn, &question.id)?;
+            summary.deleted += 1;
+        } else {
+            order.push(question.id);
+            summary.kept += 1;
+        }
+    }
+
+    db::set_question_order(conn, quiz_id, &order)?;
+    Ok(summary)
+}
+
+fn question_changed(question: &Question, request: &CreateQuestionRequest) -> bool {
+    question.question_type != QuestionType::from_str(&request.question_type)
+        || question.content != request.content
+        || question.content_type != request.content_type.as_deref().unwrap_or("TEXT")
+        || question.content_language != request.content_language
+        || question.correct_answer != request.correct_answer
+        || question.multiple_answers != request.multiple_answers.unwrap_or(false)
+        || question.explanation != request.explanation
+        || question.time_limit_seconds != request.time_limit_seconds
+}
+
+fn choices_changed(question: &Question, request: &CreateQuestionRequest) -> bool {
+    let choices = request.choices.as_deref().unwrap_or_default();
+    question.choices.len() != choices.len()
+        || question
+            .choices
+            .iter()
+            .zip(choices)
+            .any(|(current, new)| current.text != new.text || current.is_correct != new.is_correct)
+}
+
+fn quiz_tag_id(
+    conn: &Connection,
+    quiz_id: &str,
+    name: &str,
+    cache: &mut HashMap<String, String>,
+) -> Result<String, String> {
+    if let Some(id) = cache.get(name) {
+        return Ok(id.clone());
+    }
+    let tag = match db::get_quiz_tag_by_name(conn, quiz_id, name)? {
+        Some(existing) => existing,
+        None => db::create_quiz_tag(conn, quiz_id, name)?,
+    };
+    cache.insert(name.to_string(), tag.id.clone());
+    Ok(tag.id)
+}
+
+/// Resolve pool rules by tag name once the questions and their tags are saved.
+/// An updated quiz always takes the file's rules, even when there are none.
+pub fn save_pool_rules(
+    conn: &Connection,
+    quiz_id: &str,
+    rules: &[(String, i32)],
+    replace_existing: bool,
+) -> Result<(), String> {
+    let requests = rules
+        .iter()
+        .map(|(tag, question_count)| {
+            db::get_quiz_tag_by_name(conn, quiz_id, tag)?
+                .map(|t| QuizPoolRuleRequest {
+                    tag_id: t.id,
+                    question_count: *question_count,
+                })
+                .ok_or_else(|| format!("Pool rule references unknown tag: {}", tag))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if !requests.is_empty() || replace_existing {
+        db::set_pool_rules_for_quiz(conn, quiz_id, &requests)?;
+    }
+    Ok(())
+}
+
+/// Like `upsert_deck`, for a notebook embedded in a course bundle
+pub fn upsert_notebook(
+    conn: &Connection,
+    user_id: &str,
+    content_id: Option<&str>,
+    request: &UpdateNotebookRequest,
+    options: &ImportOptions,
+) -> Result<(Notebook, bool), String> {
+    let existing = match content_id {
+        Some(content_id) => db::find_notebook_by_content_id(conn, user_id, content_id)?,
+        None => None,
+    };
+    if let Some(existing) = existing.as_ref().filter(|_| options.update_existing) {
+        let notebook = db::update_notebook(conn, &existing.id, request)?;
+        return Ok((notebook, true));
+    }
+
+    let create = CreateNotebookRequest {
+        name: request.name.clone(),
+        description: request.description.clone(),
+        icon: request.icon.clone(),
+        color: request.color.clone(),
+    };
+    let notebook = db::create_notebook(conn, user_id, &create)?;
+    if let Some(content_id) = content_id.filter(|_| existing.is_none()) {
+        db::set_notebook_content_id(conn, &notebook.id, content_id)?;
+    }
+    Ok((notebook, false))
+}
+
+/// Add, update and optionally delete pages so the notebook matches the file.
+/// Pages keep their pin; new pages go after the existing ones in file order,
+/// and pages are nested as the file nests them.
+pub fn save_pages(
+    conn: &Connection,
+    notebook_id: &str,
+    pages: Vec<ImportedPage>,
+    delete_missing: bool,
+) -> Result<(MergeSummary, HashMap<String, String>), String> {
+    check_unique_ids("page", pages.iter().map(|p| p.content_id.as_deref()))?;
+    let mut existing: HashMap<String, Page> = db::get_pages_for_notebook(conn, notebook_id)?
+        .into_iter()
+        .map(|page| (page.content_id.clone(), page))
+        .collect();
+    let mut summary = MergeSummary::default();
+    let mut saved_ids = HashMap::new();
+    let mut saved_by_content_id = HashMap::new();
+    let mut nesting = Vec::new();
+
+    for page in pages {
+        let current = page.content_id.as_ref().and_then(|id| existing.remove(id));
+        let current_parent = current.as_ref().and_then(|p| p.parent_page_id.clone());
+        let page_id = match current {
+            Some(current) if current.title == page.title && current.content == page.content => {
+                summary.unchanged += 1;
+                current.id
+            }
+            Some(current) => {
+                let request = UpdatePageRequest {
+                    title: page.title.clone(),
+                    content: page.content,
+                    is_pinned: Some(current.is_pinned),
+                };
+                db::update_page(conn, &current.id, &request)?;
+                summary.updated += 1;
+                current.id
+            }
+            None => {
+                let request = CreatePageRequest {
+                    title: page.title.clone(),
+                    content: Some(page.content),
+                    position: None,
+                    parent_page_id: None,
+                };
+                let created = db::create_page(conn, notebook_id, &request)?;
+                if let Some(content_id) = &page.content_id {
+                    db::set_page_content_id(conn, &created.id, content_id)?;
+                }
+                summary.added += 1;
+                created.id
+            }
+        };
+        saved_by_content_id.insert(page.content_id.unwrap_or_else(|| page_id.clone()), page_id.clone());
+        nesting.push((page_id.clone(), page.parent_content_id, current_parent));
+        saved_ids.insert(page.title, page_id);
+    }
+
+    // Nest pages once all are saved, before any page that is no longer in
+    // the file is deleted and takes its sub-pages with it
+    for (page_id, parent_content_id, current_parent) in nesting {
+        let parent = parent_content_id.and_then(|id| saved_by_content_id.get(&id).cloned());
+        if parent != current_parent {
+            db::move_page(conn, &page_id, notebook_id, parent.as_deref())?;
+        }
+    }
+
+    for page in existing.into_values() {
+        if delete_missing {
+            db::delete_page(conn, &page.id)?;
+            summary.deleted += 1;
+        } else {
+            summary.kept += 1;
+        }
+    }
+
+    Ok((summary, saved_ids))
+}
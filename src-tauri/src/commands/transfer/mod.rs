@@ -0,0 +1,1594 @@
+mod course_bundle;
+mod delimited;
+mod gift;
+mod markdown;
+mod merge;
+mod moodle_xml;
+mod qti;
+
+use rusqlite::Connection;
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+use crate::db::{
+    self, CreateCardRequest, CreateChoiceRequest, CreateQuestionRequest, CreateQuizRequest,
+    DbState, Deck, Quiz,
+};
+use course_bundle::LessonChanges;
+use merge::{ImportOptions, MergeSummary};
+
+const MAX_IMPORT_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+    deck: Deck,
+    cards_imported: usize,
+    updated_existing: bool,
+    changes: MergeSummary,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuizImportResult {
+    quiz: Quiz,
+    questions_imported: usize,
+    updated_existing: bool,
+    changes: MergeSummary,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionBankImportResult {
+    quiz: Quiz,
+    questions_imported: usize,
+    warnings: Vec<ImportWarning>,
+}
+
+/// Something in an imported question that Kioku could not represent
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportWarning {
+    question: String,
+    message: String,
+}
+
+impl ImportWarning {
+    fn new(question: &str, message: impl Into<String>) -> Self {
+        ImportWarning {
+            question: question.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// A question read from another format, ready to be saved into a quiz
+struct BankQuestion {
+    request: CreateQuestionRequest,
+    tags: Vec<String>,
+    /// Only Kioku's own JSON format carries one
+    content_id: Option<String>,
+}
+
+/// A card read from another format, ready to be saved into a deck
+struct ImportedCard {
+    request: CreateCardRequest,
+    tags: Vec<String>,
+    content_id: Option<String>,
+}
+
+/// A notebook page embedded in a course bundle
+struct ImportedPage {
+    title: String,
+    content: String,
+    content_id: Option<String>,
+    parent_content_id: Option<String>,
+}
+
+impl ImportedCard {
+    /// A plain text card with nothing but its two sides
+    fn plain(front: String, back: String) -> Self {
+        ImportedCard {
+            request: CreateCardRequest {
+                front,
+                front_type: None,
+                front_language: None,
+                back,
+                back_type: None,
+                back_language: None,
+                notes: None,
+            },
+            tags: Vec::new(),
+            content_id: None,
+        }
+    }
+}
+
+/// A card in Kioku's JSON deck files and course bundles
+#[derive(Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonCard {
+    id: Option<String>,
+    front: String,
+    back: String,
+    #[serde(default = "default_text")]
+    front_type: String,
+    #[serde(default = "default_text")]
+    back_type: String,
+    front_language: Option<String>,
+    back_language: Option<String>,
+    notes: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl From<JsonCard> for ImportedCard {
+    fn from(card: JsonCard) -> Self {
+        ImportedCard {
+            request: CreateCardRequest {
+                front: card.front,
+                front_type: Some(card.front_type),
+                front_language: card.front_language,
+                back: card.back,
+                back_type: Some(card.back_type),
+                back_language: card.back_language,
+                notes: card.notes,
+            },
+            tags: card.tags,
+            content_id: card.id,
+        }
+    }
+}
+
+/// A question in Kioku's JSON quiz files and course bundles
+#[derive(Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonQuestion {
+    id: Option<String>,
+    #[serde(rename = "type")]
+    question_type: String,
+    content: String,
+    #[serde(default = "default_text")]
+    content_type: String,
+    content_language: Option<String>,
+    #[serde(default)]
+    choices: Vec<JsonChoice>,
+    #[serde(default)]
+    multiple_answers: bool,
+    correct_answer: Option<String>,
+    explanation: Option<String>,
+    time_limit_seconds: Option<i32>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonChoice {
+    text: String,
+    #[serde(default)]
+    is_correct: bool,
+}
+
+impl From<JsonQuestion> for BankQuestion {
+    fn from(question: JsonQuestion) -> Self {
+        BankQuestion {
+            request: CreateQuestionRequest {
+                question_type: question.question_type,
+                content: question.content,
+                content_type: Some(question.content_type),
+                content_language: question.content_language,
+                correct_answer: question.correct_answer,
+                multiple_answers: Some(question.multiple_answers),
+                explanation: question.explanation,
+                time_limit_seconds: question.time_limit_seconds,
+                choices: Some(
+                    question
+                        .choices
+                        .into_iter()
+                        .map(|c| CreateChoiceRequest {
+                            text: c.text,
+                            is_correct: c.is_correct,
+                        })
+                        .collect(),
+                ),
+            },
+            tags: question.tags,
+            content_id: question.id,
+        }
+    }
+}
+
+fn default_text() -> String {
+    "TEXT".to_string()
+}
+
+// ============================================
+// Deck Import / Export
+// ============================================
+
+/// With `update_existing`, a deck imported from an earlier export of the same
+/// deck is updated in place instead of copied
+#[tauri::command]
+pub fn import_deck_from_file(
+    state: State<DbState>,
+    file_path: String,
+    options: Option<ImportOptions>,
+) -> Result<ImportResult, String> {
+    let options = options.unwrap_or_default();
+    let metadata = std::fs::metadata(&file_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    if metadata.len() > MAX_IMPORT_FILE_SIZE {
+        return Err(format!(
+            "File too large: {} MB (max {} MB)",
+            metadata.len() / (1024 * 1024),
+            MAX_IMPORT_FILE_SIZE / (1024 * 1024)
+        ));
+    }
+
+    let content = std::fs::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct DeckImport {
+        id: Option<String>,
+        name: String,
+        description: Option<String>,
+        #[serde(default)]
+        shuffle_cards: bool,
+        cards: Vec<JsonCard>,
+    }
+
+    let import_data: DeckImport = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+    let cards_count = import_data.cards.len();
+    let cards: Vec<ImportedCard> = import_data.cards.into_iter().map(ImportedCard::from).collect();
+
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+
+    in_transaction(&conn, || -> Result<ImportResult, String> {
+        let (deck, updated_existing) = merge::upsert_deck(
+            &conn,
+            &active_user.id,
+            import_data.id.as_deref(),
+            &import_data.name,
+            import_data.description.as_deref(),
+            import_data.shuffle_cards,
+            &options,
+        )?;
+        let changes = merge::save_cards(&conn, &deck.id, cards, options.delete_missing)?;
+
+        let final_deck = db::get_deck(&conn, &deck.id)?
+            .ok_or_else(|| "Failed to retrieve imported deck".to_string())?;
+        Ok(ImportResult {
+            deck: final_deck,
+            cards_imported: cards_count,
+            updated_existing,
+            changes,
+        })
+    })
+}
+
+#[tauri::command]
+pub fn export_deck_to_json(state: State<DbState>, deck_id: String) -> Result<String, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    let deck = db::get_deck(&conn, &deck_id)?
+        .ok_or_else(|| format!("Deck not found: {}", deck_id))?;
+    let cards = db::get_cards_for_deck(&conn, &deck_id)?;
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct DeckExport {
+        id: String,
+        name: String,
+        description: Option<String>,
+        shuffle_cards: bool,
+        cards: Vec<CardExport>,
+        exported_at: String,
+    }
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct CardExport {
+        id: String,
+        front: String,
+        back: String,
+        front_type: String,
+        back_type: String,
+        front_language: Option<String>,
+        back_language: Option<String>,
+        notes: Option<String>,
+        tags: Vec<String>,
+    }
+
+    let export = DeckExport {
+        id: deck.content_id,
+        name: deck.name,
+        description: deck.description,
+        shuffle_cards: deck.shuffle_cards,
+        cards: cards
+            .into_iter()
+            .map(|c| CardExport {
+                id: c.content_id,
+                front: c.front,
+                back: c.back,
+                front_type: c.front_type,
+                back_type: c.back_type,
+                front_language: c.front_language,
+                back_language: c.back_language,
+                notes: c.notes,
+                tags: c.tags.into_iter().map(|t| t.name).collect(),
+            })
+            .collect(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    serde_json::to_string_pretty(&export).map_err(|e| format!("Failed to serialize: {}", e))
+}
+
+// ============================================
+// Quiz Import / Export
+// ============================================
+
+#[tauri::command]
+pub fn export_quiz_to_json(state: State<DbState>, quiz_id: String) -> Result<String, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    let quiz = db::get_quiz(&conn, &quiz_id)?;
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct QuizExport {
+        id: String,
+        name: String,
+        description: Option<String>,
+        shuffle_questions: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pool_size: Option<i32>,
+        shuffle_choices: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        time_limit_seconds: Option<i32>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        pool_rules: Vec<PoolRuleExport>,
+        questions: Vec<QuestionExport>,
+        exported_at: String,
+    }
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct PoolRuleExport {
+        tag: String,
+        question_count: i32,
+    }
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct QuestionExport {
+        id: String,
+        #[serde(rename = "type")]
+        question_type: String,
+        content: String,
+        content_type: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        content_language: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        correct_answer: Option<String>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        choices: Vec<ChoiceExport>,
+        multiple_answers: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        explanation: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        time_limit_seconds: Option<i32>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+    }
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ChoiceExport {
+        text: String,
+        is_correct: bool,
+    }
+
+    let export = QuizExport {
+        id: quiz.content_id,
+        name: quiz.name,
+        description: quiz.description,
+        shuffle_questions: quiz.shuffle_questions,
+        pool_size: quiz.pool_size,
+        shuffle_choices: quiz.shuffle_choices,
+        time_limit_seconds: quiz.time_limit_seconds,
+        pool_rules: quiz
+            .pool_rules
+            .into_iter()
+            .map(|r| PoolRuleExport {
+                tag: r.tag_name,
+                question_count: r.question_count,
+            })
+            .collect(),
+        questions: quiz
+            .questions
+            .into_iter()
+            .map(|q| QuestionExport {
+                id: q.content_id,
+                question_type: match q.question_type {
+                    db::QuestionType::MultipleChoice => "multiple_choice".to_string(),
+                    db::QuestionType::FillInBlank => "fill_in_blank".to_string(),
+                },
+                content: q.content,
+                content_type: q.content_type,
+                content_language: q.content_language,
+                correct_answer: q.correct_answer,
+                choices: q
+                    .choices
+                    .into_iter()
+                    .map(|c| ChoiceExport {
+                        text: c.text,
+                        is_correct: c.is_correct,
+                    })
+                    .collect(),
+                multiple_answers: q.multiple_answers,
+                explanation: q.explanation,
+                time_limit_seconds: q.time_limit_seconds,
+                tags: q.tags.into_iter().map(|t| t.name).collect(),
+            })
+            .collect(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    serde_json::to_string_pretty(&export).map_err(|e| format!("Failed to serialize: {}", e))
+}
+
+/// With `update_existing`, a quiz imported from an earlier export of the same
+/// quiz is updated in place, keeping its attempt history
+#[tauri::command]
+pub fn import_quiz_from_file(
+    state: State<DbState>,
+    file_path: String,
+    options: Option<ImportOptions>,
+) -> Result<QuizImportResult, String> {
+    let options = options.unwrap_or_default();
+    let metadata = std::fs::metadata(&file_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    if metadata.len() > MAX_IMPORT_FILE_SIZE {
+        return Err(format!(
+            "File too large: {} MB (max {} MB)",
+            metadata.len() / (1024 * 1024),
+            MAX_IMPORT_FILE_SIZE / (1024 * 1024)
+        ));
+    }
+
+    let content = std::fs::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct QuizImport {
+        id: Option<String>,
+        name: String,
+        description: Option<String>,
+        #[serde(default)]
+        shuffle_questions: bool,
+        pool_size: Option<i32>,
+        #[serde(default)]
+        shuffle_choices: bool,
+        time_limit_seconds: Option<i32>,
+        #[serde(default)]
+        pool_rules: Vec<PoolRuleImport>,
+        questions: Vec<JsonQuestion>,
+    }
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct PoolRuleImport {
+        tag: String,
+        question_count: i32,
+    }
+
+    let import_data: QuizImport = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+    let questions_count = import_data.questions.len();
+    let questions: Vec<BankQuestion> = import_data.questions.into_iter().map(BankQuestion::from).collect();
+    let pool_rules: Vec<(String, i32)> = import_data
+        .pool_rules
+        .into_iter()
+        .map(|r| (r.tag, r.question_count))
+        .collect();
+
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+
+    in_transaction(&conn, || -> Result<QuizImportResult, String> {
+        let quiz_request = CreateQuizRequest {
+            name: import_data.name,
+            description: import_data.description,
+            shuffle_questions: Some(import_data.shuffle_questions),
+            pool_size: import_data.pool_size,
+            shuffle_choices: Some(import_data.shuffle_choices),
+            time_limit_seconds: import_data.time_limit_seconds,
+        };
+        let (quiz, updated_existing) = merge::upsert_quiz(
+            &conn,
+            &active_user.id,
+            import_data.id.as_deref(),
+            &quiz_request,
+            &options,
+        )?;
+        let changes = merge::save_questions(&conn, &quiz.id, questions, options.delete_missing)?;
+        merge::save_pool_rules(&conn, &quiz.id, &pool_rules, updated_existing)?;
+
+        let final_quiz = db::get_quiz(&conn, &quiz.id)?;
+        Ok(QuizImportResult {
+            quiz: final_quiz,
+            questions_imported: questions_count,
+            updated_existing,
+            changes,
+        })
+    })
+}
+
+// ============================================
+// Course Bundle Import / Export
+// ============================================
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseImportResult {
+    course: db::Course,
+    decks_imported: i32,
+    quizzes_imported: i32,
+    notebooks_imported: i32,
+    items_linked: i32,
+    updated_existing: bool,
+    decks_updated: i32,
+    quizzes_updated: i32,
+    notebooks_updated: i32,
+    card_changes: MergeSummary,
+    question_changes: MergeSummary,
+    page_changes: MergeSummary,
+}
+
+/// With `update_existing`, a course imported from an earlier export of the
+/// same course is updated in place along with its decks and quizzes. Its
+/// lessons are left as they are so learners keep their progress.
+#[tauri::command]
+pub fn import_course_from_file(
+    state: State<DbState>,
+    file_path: String,
+    options: Option<ImportOptions>,
+) -> Result<CourseImportResult, String> {
+    let options = options.unwrap_or_default();
+    let content = read_import_file(&file_path)?;
+    let bundle = course_bundle::parse(&content)?;
+
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+
+    in_transaction(&conn, || -> Result<CourseImportResult, String> {
+        let saved = course_bundle::save_content(&conn, &active_user.id, &bundle, &options)?;
+
+        let existing_course = match bundle.id.as_deref() {
+            Some(content_id) => db::find_course_by_content_id(&conn, &active_user.id, content_id)?,
+            None => None,
+        };
+
+        // An updated course keeps its lessons; only links to missing items are filled in
+        if let Some(existing) = existing_course.as_ref().filter(|_| options.update_existing) {
+            db::update_course(
+                &conn,
+                &active_user.id,
+                &existing.id,
+                &bundle.name,
+                bundle.description.as_deref(),
+            )?;
+            let items_linked = db::link_missing_lesson_items(&conn, &active_user.id, &existing.id)?.items_linked;
+
+            let final_course = db::get_course_with_lessons(&conn, &active_user.id, &existing.id)?
+                .ok_or_else(|| "Failed to retrieve imported course".to_string())?;
+
+            return Ok(CourseImportResult {
+                course: final_course,
+                decks_imported: bundle.decks.len() as i32,
+                quizzes_imported: bundle.quizzes.len() as i32,
+                notebooks_imported: bundle.notebooks.len() as i32,
+                items_linked,
+                updated_existing: true,
+                decks_updated: saved.decks_updated,
+                quizzes_updated: saved.quizzes_updated,
+                notebooks_updated: saved.notebooks_updated,
+                card_changes: saved.card_changes,
+                question_changes: saved.question_changes,
+                page_changes: saved.page_changes,
+            });
+        }
+
+        // Now create the course
+        let course = db::create_course(
+            &conn,
+            &active_user.id,
+            &bundle.name,
+            bundle.description.as_deref(),
+        )?;
+        if let Some(content_id) = bundle.id.as_deref().filter(|_| existing_course.is_none()) {
+            db::set_course_content_id(&conn, &course.id, content_id)?;
+        }
+        db::set_course_version(&conn, &course.id, bundle.version)?;
+
+        let items_linked = course_bundle::create_lessons(&conn, &course.id, &bundle, &saved)?;
+
+        let final_course = db::get_course_with_lessons(&conn, &active_user.id, &course.id)?
+            .ok_or_else(|| "Failed to retrieve imported course".to_string())?;
+
+        Ok(CourseImportResult {
+            course: final_course,
+            decks_imported: bundle.decks.len() as i32,
+            quizzes_imported: bundle.quizzes.len() as i32,
+            notebooks_imported: bundle.notebooks.len() as i32,
+            items_linked,
+            updated_existing: false,
+            decks_updated: saved.decks_updated,
+            quizzes_updated: saved.quizzes_updated,
+            notebooks_updated: saved.notebooks_updated,
+            card_changes: saved.card_changes,
+            question_changes: saved.question_changes,
+            page_changes: saved.page_changes,
+        })
+    })
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseUpdateResult {
+    course: db::Course,
+    previous_version: i32,
+    version: i32,
+    lessons: LessonChanges,
+    decks_updated: i32,
+    quizzes_updated: i32,
+    notebooks_updated: i32,
+    card_changes: MergeSummary,
+    question_changes: MergeSummary,
+    page_changes: MergeSummary,
+}
+
+/// Apply a newer bundle of a course to the course imported from an older one.
+/// Lessons and items are matched rather than recreated, so learners keep
+/// their progress; the result lists completed items the update removed or
+/// whose requirement it changed.
+#[tauri::command]
+pub fn apply_course_update(
+    state: State<DbState>,
+    course_id: String,
+    file_path: String,
+    options: Option<ImportOptions>,
+) -> Result<CourseUpdateResult, String> {
+    // Embedded decks and quizzes are always updated in place
+    let options = ImportOptions {
+        update_existing: true,
+        ..options.unwrap_or_default()
+    };
+    let content = read_import_file(&file_path)?;
+    let bundle = course_bundle::parse(&content)?;
+
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+
+    let course = db::get_course_with_lessons(&conn, &active_user.id, &course_id)?
+        .ok_or_else(|| format!("Course not found: {}", course_id))?;
+
+    // Courses imported before IDs existed are recognised by name instead
+    if bundle.id.as_deref().is_some_and(|id| id != course.content_id) && bundle.name != course.name {
+        return Err("This file is an export of a different course".to_string());
+    }
+    if bundle.version <= course.version {
+        return Err(format!(
+            "This file is version {} of the course, which is already at version {}",
+            bundle.version, course.version
+        ));
+    }
+
+    in_transaction(&conn, || -> Result<CourseUpdateResult, String> {
+        course_bundle::adopt_content_ids(&conn, &active_user.id, &course, &bundle)?;
+        let saved = course_bundle::save_content(&conn, &active_user.id, &bundle, &options)?;
+
+        db::update_course(
+            &conn,
+            &active_user.id,
+            &course.id,
+            &bundle.name,
+            bundle.description.as_deref(),
+        )?;
+        if let Some(content_id) = bundle.id.as_deref() {
+            db::set_course_content_id(&conn, &course.id, content_id)?;
+        }
+        db::set_course_version(&conn, &course.id, bundle.version)?;
+
+        let lessons = course_bundle::update_lessons(&conn, &active_user.id, &course.id, &bundle, &saved)?;
+
+        let final_course = db::get_course_with_lessons(&conn, &active_user.id, &course.id)?
+            .ok_or_else(|| "Failed to retrieve updated course".to_string())?;
+
+        Ok(CourseUpdateResult {
+            course: final_course,
+            previous_version: course.version,
+            version: bundle.version,
+            lessons,
+            decks_updated: saved.decks_updated,
+            quizzes_updated: saved.quizzes_updated,
+            notebooks_updated: saved.notebooks_updated,
+            card_changes: saved.card_changes,
+            question_changes: saved.question_changes,
+            page_changes: saved.page_changes,
+        })
+    })
+}
+
+#[tauri::command]
+pub fn export_course_to_json(state: State<DbState>, course_id: String) -> Result<String, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+
+    let course = db::get_course_with_lessons(&conn, &active_user.id, &course_id)?
+        .ok_or_else(|| format!("Course not found: {}", course_id))?;
+
+    // Bundle export types
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct CourseBundleExport {
+        id: String,
+        version: i32,
+        name: String,
+        description: Option<String>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        elective_groups: Vec<ElectiveGroupExport>,
+        lessons: Vec<LessonExport>,
+        decks: Vec<DeckExport>,
+        quizzes: Vec<QuizExport>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        notebooks: Vec<NotebookExport>,
+        exported_at: String,
+    }
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ElectiveGroupExport {
+        id: String,
+        name: String,
+        required_count: i32,
+    }
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct LessonExport {
+        id: String,
+        title: String,
+        description: Option<String>,
+        requires_previous: bool,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        prerequisites: Vec<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        due_day: Option<i32>,
+        optional: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        elective_group: Option<String>,
+        items: Vec<LessonItemExport>,
+    }
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct LessonItemExport {
+        id: String,
+        #[serde(rename = "type")]
+        item_type: String,
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        target_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        requirement_type: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        requirement_value: Option<i32>,
+    }
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct DeckExport {
+        id: String,
+        name: String,
+        description: Option<String>,
+        shuffle_cards: bool,
+        cards: Vec<CardExport>,
+    }
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct CardExport {
+        id: String,
+        front: String,
+        back: String,
+        front_type: String,
+        back_type: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        front_language: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        back_language: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        notes: Option<String>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+    }
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct QuizExport {
+        id: String,
+        name: String,
+        description: Option<String>,
+        shuffle_questions: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pool_size: Option<i32>,
+        shuffle_choices: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        time_limit_seconds: Option<i32>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        pool_rules: Vec<PoolRuleExport>,
+        questions: Vec<QuestionExport>,
+    }
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct PoolRuleExport {
+        tag: String,
+        question_count: i32,
+    }
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct QuestionExport {
+        id: String,
+        #[serde(rename = "type")]
+        question_type: String,
+        content: String,
+        content_type: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        content_language: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        correct_answer: Option<String>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        choices: Vec<ChoiceExport>,
+        multiple_answers: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        explanation: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        time_limit_seconds: Option<i32>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+    }
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ChoiceExport {
+        text: String,
+        is_correct: bool,
+    }
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct NotebookExport {
+        id: String,
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        description: Option<String>,
+        icon: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        color: Option<String>,
+        pages: Vec<PageExport>,
+    }
+
+    #[derive(serde::Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct PageExport {
+        id: String,
+        title: String,
+        content: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        parent_id: Option<String>,
+    }
+
+    // Collect all deck, quiz, notebook and page IDs referenced in the course
+    let mut deck_ids: std::collections::HashSet<String> = std::collections::HashSet::new();
+    let mut quiz_ids: std::collections::HashSet<String> = std::collections::HashSet::new();
+    let mut notebook_ids: std::collections::HashSet<String> = std::collections::HashSet::new();
+    let mut page_ids: std::collections::HashSet<String> = std::collections::HashSet::new();
+
+    for lesson in &course.lessons {
+        for item in &lesson.items {
+            if let Some(ref item_id) = item.item_id {
+                match item.item_type {
+                    db::LessonItemType::Deck => { deck_ids.insert(item_id.clone()); }
+                    db::LessonItemType::Quiz => { quiz_ids.insert(item_id.clone()); }
+                    db::LessonItemType::Notebook => { notebook_ids.insert(item_id.clone()); }
+                    db::LessonItemType::Page => { page_ids.insert(item_id.clone()); }
+                }
+            }
+        }
+    }
+
+    // Embed whole notebooks, and single pages inside their notebook; items
+    // are exported under the current name so the import can find them
+    let mut current_names: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+    let mut pages_by_notebook: std::collections::HashMap<String, Vec<db::Page>> = std::collections::HashMap::new();
+    for notebook_id in &notebook_ids {
+        pages_by_notebook.insert(notebook_id.clone(), db::get_pages_for_notebook(&conn, notebook_id)?);
+    }
+    for page_id in &page_ids {
+        if let Some(page) = db::get_page(&conn, page_id)? {
+            current_names.insert(page.id.clone(), page.title.clone());
+            if !notebook_ids.contains(&page.notebook_id) {
+                pages_by_notebook.entry(page.notebook_id.clone()).or_default().push(page);
+            }
+        }
+    }
+
+    let mut notebooks_export: Vec<NotebookExport> = Vec::new();
+    for (notebook_id, mut pages) in pages_by_notebook {
+        let Some(notebook) = db::get_notebook(&conn, &notebook_id)? else { continue };
+        current_names.insert(notebook.id.clone(), notebook.name.clone());
+        pages.sort_by_key(|p| p.position);
+        // Sub-pages name their parent by content ID, when it is exported too
+        let content_ids: std::collections::HashMap<String, String> =
+            pages.iter().map(|p| (p.id.clone(), p.content_id.clone())).collect();
+        notebooks_export.push(NotebookExport {
+            id: notebook.content_id,
+            name: notebook.name,
+            description: notebook.description,
+            icon: notebook.icon,
+            color: notebook.color,
+            pages: pages
+                .into_iter()
+                .map(|p| PageExport {
+                    parent_id: p.parent_page_id.and_then(|id| content_ids.get(&id).cloned()),
+                    id: p.content_id,
+                    title: p.title,
+                    content: p.content,
+                })
+                .collect(),
+        });
+    }
+
+    // Export all referenced decks
+    let mut decks_export: Vec<DeckExport> = Vec::new();
+    for deck_id in deck_ids {
+        if let Some(deck) = db::get_deck(&conn, &deck_id)? {
+            let cards = db::get_cards_for_deck(&conn, &deck_id)?;
+            decks_export.push(DeckExport {
+                id: deck.content_id,
+                name: deck.name,
+                description: deck.description,
+                shuffle_cards: deck.shuffle_cards,
+                cards: cards
+                    .into_iter()
+                    .map(|c| CardExport {
+                        id: c.content_id,
+                        front: c.front,
+                        back: c.back,
+                        front_type: c.front_type,
+                        back_type: c.back_type,
+                        front_language: c.front_language,
+                        back_language: c.back_language,
+                        notes: c.notes,
+                        tags: c.tags.into_iter().map(|t| t.name).collect(),
+                    })
+                    .collect(),
+            });
+        }
+    }
+
+    // Export all referenced quizzes
+    let mut quizzes_export: Vec<QuizExport> = Vec::new();
+    for quiz_id in quiz_ids {
+        let quiz = db::get_quiz(&conn, &quiz_id)?;
+        quizzes_export.push(QuizExport {
+            id: quiz.content_id,
+            name: quiz.name,
+            description: quiz.description,
+            shuffle_questions: quiz.shuffle_questions,
+            pool_size: quiz.pool_size,
+            shuffle_choices: quiz.shuffle_choices,
+            time_limit_seconds: quiz.time_limit_seconds,
+            pool_rules: quiz
+                .pool_rules
+                .into_iter()
+                .map(|r| PoolRuleExport {
+                    tag: r.tag_name,
+                    question_count: r.question_count,
+                })
+                .collect(),
+            questions: quiz
+                .questions
+                .into_iter()
+                .map(|q| QuestionExport {
+                    id: q.content_id,
+                    question_type: match q.question_type {
+                        db::QuestionType::MultipleChoice => "multiple_choice".to_string(),
+                        db::QuestionType::FillInBlank => "fill_in_blank".to_string(),
+                    },
+                    content: q.content,
+                    content_type: q.content_type,
+                    content_language: q.content_language,
+                    correct_answer: q.correct_answer,
+                    choices: q
+                        .choices
+                        .into_iter()
+                        .map(|c| ChoiceExport {
+                            text: c.text,
+                            is_correct: c.is_correct,
+                        })
+                        .collect(),
+                    multiple_answers: q.multiple_answers,
+                    explanation: q.explanation,
+                    time_limit_seconds: q.time_limit_seconds,
+                    tags: q.tags.into_iter().map(|t| t.name).collect(),
+                })
+                .collect(),
+        });
+    }
+
+    // Prerequisites refer to lessons by their content ID
+    let lesson_content_ids: std::collections::HashMap<String, String> = course
+        .lessons
+        .iter()
+        .map(|l| (l.id.clone(), l.content_id.clone()))
+        .collect();
+
+    let group_content_ids: std::collections::HashMap<String, String> = course
+        .elective_groups
+        .iter()
+        .map(|g| (g.id.clone(), g.content_id.clone()))
+        .collect();
+
+    let export = CourseBundleExport {
+        id: course.content_id,
+        version: course.version,
+        name: course.name,
+        description: course.description,
+        elective_groups: course
+            .elective_groups
+            .into_iter()
+            .map(|group| ElectiveGroupExport {
+                id: group.content_id,
+                name: group.name,
+                required_count: group.required_count,
+            })
+            .collect(),
+        lessons: course
+            .lessons
+            .into_iter()
+            .map(|lesson| LessonExport {
+                id: lesson.content_id,
+                title: lesson.title,
+                description: lesson.description,
+                requires_previous: lesson.requires_previous,
+                prerequisites: lesson
+                    .prerequisite_ids
+                    .iter()
+                    .filter_map(|id| lesson_content_ids.get(id).cloned())
+                    .collect(),
+                due_day: lesson.due_day,
+                optional: lesson.is_optional,
+                elective_group: lesson
+                    .elective_group_id
+                    .as_ref()
+                    .and_then(|id| group_content_ids.get(id).cloned()),
+                items: lesson
+                    .items
+                    .into_iter()
+                    .map(|item| LessonItemExport {
+                        id: item.content_id,
+                        item_type: match item.item_type {
+                            db::LessonItemType::Deck => "deck".to_string(),
+                            db::LessonItemType::Quiz => "quiz".to_string(),
+                            db::LessonItemType::Page => "page".to_string(),
+                            db::LessonItemType::Notebook => "notebook".to_string(),
+                        },
+                        name: item
+                            .item_id
+                            .as_ref()
+                            .and_then(|id| current_names.get(id).cloned())
+                            .unwrap_or(item.item_name),
+                        target_id: item.target_content_id,
+                        requirement_type: item.requirement_type.map(|rt| match rt {
+                            db::RequirementType::Study => "study".to_string(),
+                            db::RequirementType::Review => "review".to_string(),
+                            db::RequirementType::Complete => "complete".to_string(),
+                            db::RequirementType::MinScore => "min_score".to_string(),
+                            db::RequirementType::Read => "read".to_string(),
+                        }),
+                        requirement_value: item.requirement_value,
+                    })
+                    .collect(),
+            })
+            .collect(),
+        decks: decks_export,
+        quizzes: quizzes_export,
+        notebooks: notebooks_export,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    serde_json::to_string_pretty(&export).map_err(|e| format!("Failed to serialize: {}", e))
+}
+
+// ============================================
+// Markdown Import / Export
+// ============================================
+
+#[tauri::command]
+pub fn import_deck_from_markdown(
+    state: State<DbState>,
+    file_path: String,
+) -> Result<ImportResult, String> {
+    let content = read_import_file(&file_path)?;
+    let document = markdown::parse(&content)?;
+
+    let name = document
+        .title
+        .clone()
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| name_from_file(&file_path));
+    let description = document.description.clone();
+    let cards = document.into_cards()?;
+
+    import_card_list(&state, &name, description.as_deref(), cards)
+}
+
+#[tauri::command]
+pub fn export_deck_to_markdown(state: State<DbState>, deck_id: String) -> Result<String, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+    let deck = db::get_deck(&conn, &deck_id)?
+        .ok_or_else(|| format!("Deck not found: {}", deck_id))?;
+    let cards = db::get_cards_for_deck(&conn, &deck_id)?;
+
+    Ok(markdown::write_deck(&deck, &cards))
+}
+
+#[tauri::command]
+pub fn import_quiz_from_markdown(
+    state: State<DbState>,
+    file_path: String,
+) -> Result<QuestionBankImportResult, String> {
+    let content = read_import_file(&file_path)?;
+    let document = markdown::parse(&content)?;
+
+    let mut quiz_request = quiz_request_for_file(&file_path);
+    if let Some(title) = document.title.clone().filter(|t| !t.is_empty()) {
+        quiz_request.name = title;
+    }
+    quiz_request.description = document.description.clone();
+    let questions = document.into_questions()?;
+
+    import_question_bank(&state, quiz_request, questions, Vec::new())
+}
+
+#[tauri::command]
+pub fn export_quiz_to_markdown(state: State<DbState>, quiz_id: String) -> Result<String, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let quiz = db::get_quiz(&conn, &quiz_id)?;
+    Ok(markdown::write_quiz(&quiz))
+}
+
+// ============================================
+// Pasted Text Import
+// ============================================
+
+#[tauri::command]
+pub fn preview_delimited_cards(
+    text: String,
+    term_separator: Option<String>,
+    row_separator: Option<String>,
+) -> Result<delimited::DelimitedPreview, String> {
+    check_pasted_text(&text)?;
+    delimited::parse(&text, term_separator.as_deref(), row_separator.as_deref())
+}
+
+#[tauri::command]
+pub fn import_deck_from_delimited(
+    state: State<DbState>,
+    name: String,
+    description: Option<String>,
+    text: String,
+    term_separator: Option<String>,
+    row_separator: Option<String>,
+) -> Result<ImportResult, String> {
+    check_pasted_text(&text)?;
+    let preview = delimited::parse(&text, term_separator.as_deref(), row_separator.as_deref())?;
+    if preview.cards.is_empty() {
+        return Err("No cards found in the pasted text".to_string());
+    }
+
+    let cards = preview
+        .cards
+        .into_iter()
+        .map(|card| ImportedCard::plain(card.front, card.back))
+        .collect();
+
+    import_card_list(&state, &name, description.as_deref(), cards)
+}
+
+fn check_pasted_text(text: &str) -> Result<(), String> {
+    if text.len() as u64 > MAX_IMPORT_FILE_SIZE {
+        return Err(format!(
+            "Text too large (max {} MB)",
+            MAX_IMPORT_FILE_SIZE / (1024 * 1024)
+        ));
+    }
+    Ok(())
+}
+
+// ============================================
+// Moodle Question Bank Import / Export
+// ============================================
+
+#[tauri::command]
+pub fn import_quiz_from_gift(
+    state: State<DbState>,
+    file_path: String,
+) -> Result<QuestionBankImportResult, String> {
+    let content = read_import_file(&file_path)?;
+    let (questions, warnings) = gift::parse(&content);
+    import_question_bank(&state, quiz_request_for_file(&file_path), questions, warnings)
+}
+
+#[tauri::command]
+pub fn export_quiz_to_gift(state: State<DbState>, quiz_id: String) -> Result<String, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let quiz = db::get_quiz(&conn, &quiz_id)?;
+    Ok(gift::write(&quiz))
+}
+
+#[tauri::command]
+pub fn import_quiz_from_moodle_xml(
+    state: State<DbState>,
+    file_path: String,
+) -> Result<QuestionBankImportResult, String> {
+    let content = read_import_file(&file_path)?;
+    let (questions, warnings) = moodle_xml::parse(&content)?;
+    import_question_bank(&state, quiz_request_for_file(&file_path), questions, warnings)
+}
+
+#[tauri::command]
+pub fn export_quiz_to_moodle_xml(state: State<DbState>, quiz_id: String) -> Result<String, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let quiz = db::get_quiz(&conn, &quiz_id)?;
+    Ok(moodle_xml::write(&quiz))
+}
+
+// ============================================
+// QTI Package Import / Export
+// ============================================
+
+#[tauri::command]
+pub fn import_quiz_from_qti(
+    app: AppHandle,
+    state: State<DbState>,
+    file_path: String,
+) -> Result<QuestionBankImportResult, String> {
+    let images_dir = super::images::get_images_dir(&app)?;
+    let package = qti::read_package(&file_path, &images_dir)?;
+
+    let mut quiz_request = quiz_request_for_file(&file_path);
+    if let Some(title) = package.title.filter(|t| !t.trim().is_empty()) {
+        quiz_request.name = title;
+    }
+    quiz_request.shuffle_questions = Some(package.shuffle_questions);
+    quiz_request.shuffle_choices = Some(package.shuffle_choices);
+    quiz_request.time_limit_seconds = package.time_limit_seconds;
+
+    let remove_images = |written: &[(std::path::PathBuf, Vec<u8>)]| {
+        for (path, _) in written {
+            let _ = std::fs::remove_file(path);
+        }
+    };
+
+    for (index, (path, data)) in package.images.iter().enumerate() {
+        if let Err(e) = std::fs::write(path, data) {
+            remove_images(&package.images[..=index]);
+            return Err(format!("Failed to write image: {}", e));
+        }
+    }
+
+    let result = import_question_bank(&state, quiz_request, package.questions, package.warnings);
+    if result.is_err() {
+        remove_images(&package.images);
+    }
+    result
+}
+
+#[tauri::command]
+pub fn export_quiz_to_qti(
+    state: State<DbState>,
+    quiz_id: String,
+    file_path: String,
+) -> Result<(), String> {
+    let package = {
+        let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let quiz = db::get_quiz(&conn, &quiz_id)?;
+        qti::write_package(&quiz)?
+    };
+
+    std::fs::write(&file_path, package).map_err(|e| format!("Failed to write package: {}", e))
+}
+
+// ============================================
+// Shared Import Helpers
+// ============================================
+
+/// Save parsed cards as a new deck
+fn import_card_list(
+    state: &State<DbState>,
+    name: &str,
+    description: Option<&str>,
+    cards: Vec<ImportedCard>,
+) -> Result<ImportResult, String> {
+    let cards_count = cards.len();
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+
+    in_transaction(&conn, || -> Result<ImportResult, String> {
+        let deck = db::create_deck(&conn, &active_user.id, name, description, false)?;
+        let changes = merge::save_cards(&conn, &deck.id, cards, false)?;
+
+        let final_deck = db::get_deck(&conn, &deck.id)?
+            .ok_or_else(|| "Failed to retrieve imported deck".to_string())?;
+        Ok(ImportResult {
+            deck: final_deck,
+            cards_imported: cards_count,
+            updated_existing: false,
+            changes,
+        })
+    })
+}
+
+fn name_from_file(file_path: &str) -> String {
+    std::path::Path::new(file_path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Imported")
+        .to_string()
+}
+
+/// A quiz with default settings named after the imported file
+fn quiz_request_for_file(file_path: &str) -> CreateQuizRequest {
+    CreateQuizRequest {
+        name: name_from_file(file_path),
+        description: None,
+        shuffle_questions: None,
+        pool_size: None,
+        shuffle_choices: None,
+        time_limit_seconds: None,
+    }
+}
+
+/// Save parsed questions as a new quiz
+fn import_question_bank(
+    state: &State<DbState>,
+    quiz_request: CreateQuizRequest,
+    questions: Vec<BankQuestion>,
+    warnings: Vec<ImportWarning>,
+) -> Result<QuestionBankImportResult, String> {
+    if questions.is_empty() {
+        return Err(match warnings.first() {
+            Some(w) => format!("No supported questions found ({}: {})", w.question, w.message),
+            None => "No questions found in file".to_string(),
+        });
+    }
+
+    let questions_count = questions.len();
+
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+
+    let quiz = in_transaction(&conn, || -> Result<Quiz, String> {
+        let quiz = db::create_quiz(&conn, &active_user.id, &quiz_request)?;
+        merge::save_questions(&conn, &quiz.id, questions, false)?;
+
+        db::get_quiz(&conn, &quiz.id)
+    })?;
+
+    Ok(QuestionBankImportResult {
+        quiz,
+        questions_imported: questions_count,
+        warnings,
+    })
+}
+
+// ============================================
+// Shared Helpers
+// ============================================
+
+/// Run an import's writes in one transaction, rolled back if any fails
+fn in_transaction<T>(
+    conn: &Connection,
+    work: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    conn.execute("BEGIN TRANSACTION", [])
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+
+    match work() {
+        Ok(value) => {
+            conn.execute("COMMIT", [])
+                .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            Ok(value)
+        }
+        Err(e) => {
+            let _ = conn.execute("ROLLBACK", []);
+            Err(e)
+        }
+    }
+}
+
+fn read_import_file(file_path: &str) -> Result<String, String> {
+    let metadata = std::fs::metadata(file_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    if metadata.len() > MAX_IMPORT_FILE_SIZE {
+        return Err(format!(
+            "File too large: {} MB (max {} MB)",
+            metadata.len() / (1024 * 1024),
+            MAX_IMPORT_FILE_SIZE / (1024 * 1024)
+        ));
+    }
+
+    std::fs::read_to_string(file_path).map_err(|e| format!("Failed to read file: {}", e))
+}
+
+/// Moodle category paths look like `$course$/top/Networking/Routing`; the part
+/// after the context and `top` becomes the tag. The bare top category has none.
+fn category_to_tag(category: &str) -> Option<String> {
+    let mut parts: Vec<&str> = category.split('/').map(|p| p.trim()).collect();
+    if parts.first().is_some_and(|p| p.starts_with('$') && p.ends_with('$')) {
+        parts.remove(0);
+    }
+    if parts.first() == Some(&"top") {
+        parts.remove(0);
+    }
+
+    let tag = parts.join("/");
+    if tag.is_empty() {
+        None
+    } else {
+        Some(tag)
+    }
+}
+
+fn tag_to_category(tag: Option<&str>) -> String {
+    match tag {
+        Some(tag) => format!("$course$/top/{}", tag),
+        None => "$course$/top".to_string(),
+    }
+}
+
+/// Split a Markdown fenced code block into its language and body. Only text that
+/// is a single fence and nothing else counts.
+fn parse_code_fence(text: &str) -> Option<(Option<String>, String)> {
+    let text = text.trim();
+    let rest = text.strip_prefix("```")?;
+    let body = rest.strip_suffix("```")?;
+    let (info, code) = body.split_once('\n')?;
+    if code.contains("\n```") {
+        return None;
+    }
+
+    let language = info.trim();
+    let language = if language.is_empty() {
+        None
+    } else {
+        Some(language.to_string())
+    };
+    Some((language, code.trim_end_matches('\n').to_string()))
+}
+
+fn code_fence(language: Option<&str>, code: &str) -> String {
+    format!("```{}\n{}\n```", language.unwrap_or(""), code)
+}
+
+/// Reduce an HTML fragment to plain text, keeping line breaks
+fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim().to_ascii_lowercase();
+        let name = tag.trim_start_matches('/').split_whitespace().next().unwrap_or("");
+        let name = name.trim_end_matches('/');
+        if name == "br" || (tag.starts_with('/') && matches!(name, "p" | "div" | "li" | "pre" | "tr")) {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+
+    let text = decode_html_entities(&text);
+    let mut lines: Vec<&str> = text.lines().map(|l| l.trim_end()).collect();
+    lines.dedup_by(|a, b| a.is_empty() && b.is_empty());
+    lines.join("\n").trim().to_string()
+}
+
+fn decode_html_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity_end = rest.find(';').filter(|&end| end <= 10);
+        let replacement = entity_end.and_then(|end| match &rest[1..end] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            entity => {
+                let code = if let Some(hex) = entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                    u32::from_str_radix(hex, 16).ok()
+                } else {
+                    entity.strip_prefix('#').and_then(|dec| dec.parse().ok())
+                };
+                code.and_then(char::from_u32)
+            }
+        });
+
+        match (replacement, entity_end) {
+            (Some(ch), Some(end)) => {
+                decoded.push(ch);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Moodle fractions are percentages; print whole numbers without decimals
+fn format_fraction(fraction: f64) -> String {
+    if fraction.fract() == 0.0 {
+        format!("{}", fraction as i64)
+    } else {
+        format!("{:.5}", fraction)
+    }
+}
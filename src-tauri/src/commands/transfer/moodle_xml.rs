@@ -0,0 +1,345 @@
+// Moodle XML question bank format
+//
+// A `<quiz>` root holding `<question type="...">` elements. Category
+// pseudo-questions set the category for the questions that follow them.
+
+use super::{
+    category_to_tag, code_fence, format_fraction, html_to_text, parse_code_fence,
+    tag_to_category, xml_escape,
+};
+use super::{BankQuestion, ImportWarning};
+use crate::db::{CreateChoiceRequest, CreateQuestionRequest, Question, QuestionType, Quiz};
+
+pub fn parse(text: &str) -> Result<(Vec<BankQuestion>, Vec<ImportWarning>), String> {
+    let document = roxmltree::Document::parse(text)
+        .map_err(|e| format!("Failed to parse Moodle XML: {}", e))?;
+
+    let root = document.root_element();
+    if root.tag_name().name() != "quiz" {
+        return Err("Not a Moodle XML file: missing <quiz> root".to_string());
+    }
+
+    let mut questions = Vec::new();
+    let mut warnings = Vec::new();
+    let mut category: Option<String> = None;
+
+    let nodes = root.children().filter(|n| n.has_tag_name("question"));
+    for (idx, node) in nodes.enumerate() {
+        let question_type = node.attribute("type").unwrap_or("");
+        if question_type == "category" {
+            category = child_text(node, &["category", "text"]).and_then(|c| category_to_tag(&c));
+            continue;
+        }
+
+        let label = child_text(node, &["name", "text"])
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| format!("Question {}", idx + 1));
+
+        match parse_question(node, question_type, &label, &mut warnings) {
+            Ok(Some(mut question)) => {
+                if let Some(category) = &category {
+                    if !question.tags.contains(category) {
+                        question.tags.insert(0, category.clone());
+                    }
+                }
+                questions.push(question);
+            }
+            Ok(None) => {}
+            Err(message) => warnings.push(ImportWarning::new(&label, message)),
+        }
+    }
+
+    Ok((questions, warnings))
+}
+
+/// Returns `Ok(None)` for questions skipped with a warning already recorded
+fn parse_question(
+    node: roxmltree::Node,
+    question_type: &str,
+    label: &str,
+    warnings: &mut Vec<ImportWarning>,
+) -> Result<Option<BankQuestion>, String> {
+    let supported = ["multichoice", "truefalse", "shortanswer", "numerical"];
+    if !supported.contains(&question_type) {
+        warnings.push(ImportWarning::new(
+            label,
+            format!("Unsupported question type: {}", question_type),
+        ));
+        return Ok(None);
+    }
+
+    let question_text = child(node, "questiontext").ok_or("Question has no text")?;
+    if question_text.children().any(|n| n.has_tag_name("file")) {
+        warnings.push(ImportWarning::new(label, "Embedded files were not imported"));
+    }
+    let (content, content_type, content_language) = read_formatted_text(question_text);
+    if content.is_empty() {
+        return Err("Question has no text".to_string());
+    }
+
+    let explanation = child(node, "generalfeedback")
+        .map(|n| read_formatted_text(n).0)
+        .filter(|f| !f.is_empty());
+
+    let answers: Vec<(f64, String)> = node
+        .children()
+        .filter(|n| n.has_tag_name("answer"))
+        .map(|n| {
+            let fraction = n
+                .attribute("fraction")
+                .and_then(|f| f.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            (fraction, read_formatted_text(n).0)
+        })
+        .collect();
+
+    let has_answer_feedback = node
+        .children()
+        .filter(|n| n.has_tag_name("answer"))
+        .any(|n| child(n, "feedback").is_some_and(|f| !read_formatted_text(f).0.is_empty()));
+    if has_answer_feedback {
+        warnings.push(ImportWarning::new(label, "Answer feedback was dropped"));
+    }
+
+    let (kind, correct_answer, multiple_answers, choices) = match question_type {
+        "multichoice" => {
+            let single = child_text(node, &["single"]).map_or(true, |s| s.trim() != "false");
+            let choices: Vec<CreateChoiceRequest> = answers
+                .iter()
+                .map(|(fraction, text)| CreateChoiceRequest {
+                    text: text.clone(),
+                    is_correct: if single {
+                        *fraction >= 99.99
+                    } else {
+                        *fraction > 0.0
+                    },
+                })
+                .collect();
+
+            if !choices.iter().any(|c| c.is_correct) {
+                return Err("Multiple choice question has no fully correct answer".to_string());
+            }
+            if single && answers.iter().any(|(f, _)| *f > 0.0 && *f < 99.99) {
+                warnings.push(ImportWarning::new(label, "Partial credit answers were dropped"));
+            }
+            (QuestionType::MultipleChoice, None, !single, choices)
+        }
+        "truefalse" => {
+            let answer_is_true = answers
+                .iter()
+                .find(|(fraction, _)| *fraction >= 99.99)
+                .map(|(_, text)| text.trim().eq_ignore_ascii_case("true"))
+                .ok_or("True/false question has no correct answer")?;
+            let choices = vec![
+                CreateChoiceRequest {
+                    text: "True".to_string(),
+                    is_correct: answer_is_true,
+                },
+                CreateChoiceRequest {
+                    text: "False".to_string(),
+                    is_correct: !answer_is_true,
+                },
+            ];
+            (QuestionType::MultipleChoice, None, false, choices)
+        }
+        _ => {
+            // shortanswer and numerical both become fill-in-the-blank
+            let mut accepted = answers.iter().filter(|(fraction, _)| *fraction >= 99.99);
+            let (_, first) = accepted.next().ok_or("Question has no fully correct answer")?;
+            if accepted.next().is_some() {
+                warnings.push(ImportWarning::new(label, "Only the first accepted answer was kept"));
+            }
+            if answers.iter().any(|(f, _)| *f > 0.0 && *f < 99.99) {
+                warnings.push(ImportWarning::new(label, "Partial credit answers were dropped"));
+            }
+
+            if question_type == "numerical" {
+                let has_tolerance = node
+                    .children()
+                    .filter(|n| n.has_tag_name("answer"))
+                    .filter_map(|n| child_text(n, &["tolerance"]))
+                    .any(|t| t.trim().parse::<f64>().is_ok_and(|t| t != 0.0));
+                if has_tolerance {
+                    warnings.push(ImportWarning::new(
+                        label,
+                        "Numeric tolerance is not supported; only the exact value is accepted",
+                    ));
+                }
+                if child(node, "units").is_some_and(|u| u.children().any(|n| n.is_element())) {
+                    warnings.push(ImportWarning::new(label, "Units were dropped"));
+                }
+            }
+            (QuestionType::FillInBlank, Some(first.clone()), false, vec![])
+        }
+    };
+
+    let tags = child(node, "tags")
+        .map(|tags| {
+            tags.children()
+                .filter(|n| n.has_tag_name("tag"))
+                .filter_map(|n| child_text(n, &["text"]))
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(Some(BankQuestion {
+        request: CreateQuestionRequest {
+            question_type: match kind {
+                QuestionType::MultipleChoice => "multiple_choice".to_string(),
+                QuestionType::FillInBlank => "fill_in_blank".to_string(),
+            },
+            content,
+            content_type: Some(content_type.to_string()),
+            content_language,
+            correct_answer,
+            multiple_answers: Some(multiple_answers),
+            explanation,
+            time_limit_seconds: None,
+            choices: Some(choices),
+        },
+        tags,
+        content_id: None,
+    }))
+}
+
+/// Read a `<x format="..."><text>...</text></x>` element as
+/// (content, content type, language)
+fn read_formatted_text(node: roxmltree::Node) -> (String, &'static str, Option<String>) {
+    let raw = child_text(node, &["text"]).unwrap_or_default();
+
+    match node.attribute("format").unwrap_or("html") {
+        "markdown" => match parse_code_fence(&raw) {
+            Some((language, code)) => (code, "CODE", language),
+            None => (raw.trim().to_string(), "TEXT", None),
+        },
+        "html" | "moodle_auto_format" => {
+            let trimmed = raw.trim();
+            if trimmed.starts_with("<pre") && trimmed.ends_with("</pre>") {
+                // `<pre><code class="language-x">` is the usual code markup
+                let language = trimmed
+                    .split("language-")
+                    .nth(1)
+                    .and_then(|rest| rest.split(['"', ' ']).next())
+                    .filter(|l| !l.is_empty())
+                    .map(|l| l.to_string());
+                (html_to_text(trimmed), "CODE", language)
+            } else {
+                (html_to_text(trimmed), "TEXT", None)
+            }
+        }
+        _ => (raw.trim().to_string(), "TEXT", None),
+    }
+}
+
+fn child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+fn child_text(node: roxmltree::Node, path: &[&str]) -> Option<String> {
+    let mut current = node;
+    for name in path {
+        current = child(current, name)?;
+    }
+    Some(current.text().unwrap_or("").to_string())
+}
+
+// ============================================
+// Export
+// ============================================
+
+pub fn write(quiz: &Quiz) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<quiz>\n");
+
+    let mut current_category: Option<String> = None;
+    for question in &quiz.questions {
+        let category = tag_to_category(question.tags.first().map(|t| t.name.as_str()));
+        if current_category.as_deref() != Some(category.as_str()) {
+            out.push_str("  <question type=\"category\">\n");
+            out.push_str(&format!(
+                "    <category><text>{}</text></category>\n",
+                xml_escape(&category)
+            ));
+            out.push_str("  </question>\n");
+            current_category = Some(category);
+        }
+
+        write_question(&mut out, question, quiz.shuffle_choices);
+    }
+
+    out.push_str("</quiz>\n");
+    out
+}
+
+fn write_question(out: &mut String, question: &Question, shuffle_choices: bool) {
+    let question_type = match question.question_type {
+        QuestionType::MultipleChoice => "multichoice",
+        QuestionType::FillInBlank => "shortanswer",
+    };
+    let text = if question.content_type == "CODE" {
+        code_fence(question.content_language.as_deref(), &question.content)
+    } else {
+        question.content.clone()
+    };
+    let name: String = question.content.lines().next().unwrap_or("").chars().take(60).collect();
+
+    out.push_str(&format!("  <question type=\"{}\">\n", question_type));
+    out.push_str(&format!("    <name><text>{}</text></name>\n", xml_escape(&name)));
+    out.push_str(&format!(
+        "    <questiontext format=\"markdown\"><text>{}</text></questiontext>\n",
+        xml_escape(&text)
+    ));
+    out.push_str(&format!(
+        "    <generalfeedback format=\"markdown\"><text>{}</text></generalfeedback>\n",
+        xml_escape(question.explanation.as_deref().unwrap_or(""))
+    ));
+    out.push_str("    <defaultgrade>1</defaultgrade>\n");
+
+    match question.question_type {
+        QuestionType::FillInBlank => {
+            // Kioku grades fill-in-the-blank answers exactly
+            out.push_str("    <usecase>1</usecase>\n");
+            out.push_str(&format!(
+                "    <answer fraction=\"100\" format=\"plain_text\"><text>{}</text></answer>\n",
+                xml_escape(question.correct_answer.as_deref().unwrap_or(""))
+            ));
+        }
+        QuestionType::MultipleChoice => {
+            out.push_str(&format!("    <single>{}</single>\n", !question.multiple_answers));
+            out.push_str(&format!(
+                "    <shuffleanswers>{}</shuffleanswers>\n",
+                shuffle_choices as i32
+            ));
+            out.push_str("    <answernumbering>abc</answernumbering>\n");
+
+            let correct = question.choices.iter().filter(|c| c.is_correct).count().max(1);
+            for choice in &question.choices {
+                let fraction = match (choice.is_correct, question.multiple_answers) {
+                    (true, true) => format_fraction(100.0 / correct as f64),
+                    (true, false) => "100".to_string(),
+                    (false, true) => "-100".to_string(),
+                    (false, false) => "0".to_string(),
+                };
+                out.push_str(&format!(
+                    "    <answer fraction=\"{}\" format=\"plain_text\"><text>{}</text></answer>\n",
+                    fraction,
+                    xml_escape(&choice.text)
+                ));
+            }
+        }
+    }
+
+    if !question.tags.is_empty() {
+        out.push_str("    <tags>\n");
+        for tag in &question.tags {
+            out.push_str(&format!("      <tag><text>{}</text></tag>\n", xml_escape(&tag.name)));
+        }
+        out.push_str("    </tags>\n");
+    }
+    out.push_str("  </question>\n");
+}
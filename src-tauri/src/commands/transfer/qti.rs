@@ -0,0 +1,772 @@
+// IMS QTI 2.1 content packages
+//
+// A zip holding `imsmanifest.xml`, an assessmentTest that lists the items in
+// order, and one assessmentItem file per question. Images referenced from
+// question text travel inside the package under `images/`.
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+
+use zip::write::FileOptions;
+
+use super::{xml_escape, BankQuestion, ImportWarning};
+use crate::db::{CreateChoiceRequest, CreateQuestionRequest, Question, QuestionType, Quiz};
+
+const QTI_NAMESPACE: &str = "http://www.imsglobal.org/xsd/imsqti_v2p1";
+const MAX_PACKAGE_SIZE: u64 = 100 * 1024 * 1024;
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "webp", "svg"];
+
+/// Everything read from a package, before it is saved
+pub struct QtiPackage {
+    pub title: Option<String>,
+    pub shuffle_questions: bool,
+    pub shuffle_choices: bool,
+    pub time_limit_seconds: Option<i32>,
+    pub questions: Vec<BankQuestion>,
+    pub warnings: Vec<ImportWarning>,
+    /// Image files to write into the images directory, with their final paths
+    pub images: Vec<(PathBuf, Vec<u8>)>,
+}
+
+// ============================================
+// Import
+// ============================================
+
+pub fn read_package(file_path: &str, images_dir: &Path) -> Result<QtiPackage, String> {
+    let metadata = std::fs::metadata(file_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    if metadata.len() > MAX_PACKAGE_SIZE {
+        return Err(format!(
+            "File too large: {} MB (max {} MB)",
+            metadata.len() / (1024 * 1024),
+            MAX_PACKAGE_SIZE / (1024 * 1024)
+        ));
+    }
+
+    let file = std::fs::File::open(file_path)
+        .map_err(|e| format!("Failed to open package: {}", e))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read package: {}", e))?;
+
+    let manifest = read_entry_text(&mut archive, "imsmanifest.xml")
+        .map_err(|_| "Not a QTI package: missing imsmanifest.xml".to_string())?;
+    let manifest = roxmltree::Document::parse(&manifest)
+        .map_err(|e| format!("Failed to parse imsmanifest.xml: {}", e))?;
+
+    let resources: Vec<(String, String, String)> = manifest
+        .descendants()
+        .filter(|n| n.has_tag_name("resource"))
+        .filter_map(|n| {
+            Some((
+                n.attribute("identifier").unwrap_or("").to_string(),
+                n.attribute("type")?.to_string(),
+                n.attribute("href")?.to_string(),
+            ))
+        })
+        .collect();
+
+    let mut package = QtiPackage {
+        title: None,
+        shuffle_questions: false,
+        shuffle_choices: false,
+        time_limit_seconds: None,
+        questions: Vec::new(),
+        warnings: Vec::new(),
+        images: Vec::new(),
+    };
+
+    // The test fixes item order; without one, use manifest order
+    let mut item_hrefs: Vec<String> = Vec::new();
+    if let Some((_, _, test_href)) = resources.iter().find(|(_, t, _)| t.starts_with("imsqti_test_xmlv2p1")) {
+        let text = read_entry_text(&mut archive, test_href)?;
+        let test = roxmltree::Document::parse(&text)
+            .map_err(|e| format!("Failed to parse {}: {}", test_href, e))?;
+        let root = test.root_element();
+
+        package.title = root.attribute("title").map(|t| t.to_string());
+        package.shuffle_questions = test
+            .descendants()
+            .any(|n| n.has_tag_name("ordering") && n.attribute("shuffle") == Some("true"));
+        package.time_limit_seconds = test
+            .descendants()
+            .find(|n| n.has_tag_name("timeLimits"))
+            .and_then(|n| n.attribute("maxTime"))
+            .and_then(|t| t.parse::<f64>().ok())
+            .map(|t| t.round() as i32)
+            .filter(|t| *t > 0);
+        item_hrefs = test
+            .descendants()
+            .filter(|n| n.has_tag_name("assessmentItemRef"))
+            .filter_map(|n| n.attribute("href"))
+            .map(|href| resolve_href(test_href, href))
+            .collect();
+    }
+    if item_hrefs.is_empty() {
+        item_hrefs = resources
+            .iter()
+            .filter(|(_, t, _)| t.starts_with("imsqti_item_xmlv2p1"))
+            .map(|(_, _, href)| href.clone())
+            .collect();
+    }
+    if item_hrefs.is_empty() {
+        return Err("No assessment items found in package".to_string());
+    }
+
+    let mut images = ImageImporter {
+        images_dir,
+        saved: HashMap::new(),
+        files: Vec::new(),
+    };
+
+    for (idx, href) in item_hrefs.iter().enumerate() {
+        let text = match read_entry_text(&mut archive, href) {
+            Ok(text) => text,
+            Err(message) => {
+                package.warnings.push(ImportWarning::new(href, message));
+                continue;
+            }
+        };
+        let document = match roxmltree::Document::parse(&text) {
+            Ok(document) => document,
+            Err(e) => {
+                package
+                    .warnings
+                    .push(ImportWarning::new(href, format!("Failed to parse item: {}", e)));
+                continue;
+            }
+        };
+
+        let root = document.root_element();
+        let label = root
+            .attribute("title")
+            .filter(|t| !t.trim().is_empty())
+            .map(|t| t.to_string())
+            .unwrap_or_else(|| format!("Question {}", idx + 1));
+
+        let mut context = ItemContext {
+            href,
+            archive: &mut archive,
+            images: &mut images,
+            warnings: Vec::new(),
+        };
+        let parsed = parse_item(root, &mut context);
+        let mut item_warnings = std::mem::take(&mut context.warnings);
+
+        match parsed {
+            Ok((question, shuffle)) => {
+                package.shuffle_choices |= shuffle;
+                package.questions.push(question);
+            }
+            Err(message) => item_warnings.push(message),
+        }
+        package
+            .warnings
+            .extend(item_warnings.into_iter().map(|m| ImportWarning::new(&label, m)));
+    }
+
+    package.images = images.files;
+    Ok(package)
+}
+
+struct ItemContext<'a, 'd> {
+    href: &'a str,
+    archive: &'a mut zip::ZipArchive<std::fs::File>,
+    images: &'a mut ImageImporter<'d>,
+    warnings: Vec<String>,
+}
+
+/// Copies package images into the images directory under fresh names
+struct ImageImporter<'d> {
+    images_dir: &'d Path,
+    saved: HashMap<String, String>,
+    files: Vec<(PathBuf, Vec<u8>)>,
+}
+
+impl ItemContext<'_, '_> {
+    /// Returns the local path an `<img src>` was saved to
+    fn import_image(&mut self, src: &str) -> Option<String> {
+        let entry = resolve_href(self.href, src);
+        if let Some(path) = self.images.saved.get(&entry) {
+            return Some(path.clone());
+        }
+
+        let extension = Path::new(&entry)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .filter(|e| IMAGE_EXTENSIONS.contains(&e.as_str()))?;
+        let data = read_entry_bytes(self.archive, &entry).ok()?;
+
+        let path = self
+            .images
+            .images_dir
+            .join(format!("{}.{}", uuid::Uuid::new_v4(), extension));
+        let path_string = path.to_string_lossy().to_string();
+        self.images.saved.insert(entry, path_string.clone());
+        self.images.files.push((path, data));
+        Some(path_string)
+    }
+}
+
+/// Returns the question and whether its choices ask to be shuffled
+fn parse_item(
+    root: roxmltree::Node,
+    context: &mut ItemContext,
+) -> Result<(BankQuestion, bool), String> {
+    if !root.has_tag_name("assessmentItem") {
+        return Err("Not an assessmentItem".to_string());
+    }
+    let body = root
+        .children()
+        .find(|n| n.has_tag_name("itemBody"))
+        .ok_or("Item has no itemBody")?;
+
+    let interactions: Vec<roxmltree::Node> = body
+        .descendants()
+        .filter(|n| n.is_element() && n.tag_name().name().ends_with("Interaction"))
+        .collect();
+    let interaction = match interactions.as_slice() {
+        [] => return Err("Item has no interaction".to_string()),
+        [only] => *only,
+        _ => return Err("Items with more than one interaction are not supported".to_string()),
+    };
+
+    let response_id = interaction.attribute("responseIdentifier").unwrap_or("RESPONSE");
+    let declaration = root
+        .children()
+        .find(|n| n.has_tag_name("responseDeclaration") && n.attribute("identifier") == Some(response_id));
+    let mut correct_values: Vec<String> = declaration
+        .and_then(|d| d.children().find(|n| n.has_tag_name("correctResponse")))
+        .map(|c| {
+            c.children()
+                .filter(|n| n.has_tag_name("value"))
+                .map(|n| n.text().unwrap_or("").trim().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    if correct_values.is_empty() {
+        // Fall back to any positively scored mapping entries
+        correct_values = declaration
+            .into_iter()
+            .flat_map(|d| d.descendants())
+            .filter(|n| n.has_tag_name("mapEntry"))
+            .filter(|n| {
+                n.attribute("mappedValue")
+                    .and_then(|v| v.parse::<f64>().ok())
+                    .is_some_and(|v| v > 0.0)
+            })
+            .filter_map(|n| n.attribute("mapKey").map(|k| k.to_string()))
+            .collect();
+        if !correct_values.is_empty() {
+            context
+                .warnings
+                .push("Partial credit mapping was dropped".to_string());
+        }
+    }
+
+    let (content, content_type, content_language) = render_body(body, context);
+    if content.is_empty() {
+        return Err("Question has no text".to_string());
+    }
+
+    let feedback: Vec<String> = root
+        .children()
+        .filter(|n| n.has_tag_name("modalFeedback"))
+        .map(|n| render_text(n, context))
+        .filter(|f| !f.is_empty())
+        .collect();
+    let explanation = if feedback.is_empty() {
+        None
+    } else {
+        Some(feedback.join("\n\n"))
+    };
+
+    let (request, shuffle) = match interaction.tag_name().name() {
+        "choiceInteraction" => {
+            let choices: Vec<CreateChoiceRequest> = interaction
+                .children()
+                .filter(|n| n.has_tag_name("simpleChoice"))
+                .map(|n| CreateChoiceRequest {
+                    text: render_text(n, context),
+                    is_correct: n
+                        .attribute("identifier")
+                        .is_some_and(|id| correct_values.iter().any(|v| v == id)),
+                })
+                .collect();
+            if !choices.iter().any(|c| c.is_correct) {
+                return Err("Choice interaction has no correct response".to_string());
+            }
+
+            // maxChoices defaults to 1; 0 allows any number
+            let multiple_answers = declaration.and_then(|d| d.attribute("cardinality")) == Some("multiple")
+                || interaction.attribute("maxChoices").is_some_and(|max| max != "1");
+            let shuffle = interaction.attribute("shuffle") == Some("true");
+            (
+                CreateQuestionRequest {
+                    question_type: "multiple_choice".to_string(),
+                    content,
+                    content_type: Some(content_type.to_string()),
+                    content_language,
+                    correct_answer: None,
+                    multiple_answers: Some(multiple_answers),
+                    explanation,
+                    time_limit_seconds: None,
+                    choices: Some(choices),
+                },
+                shuffle,
+            )
+        }
+        "textEntryInteraction" => {
+            let mut values = correct_values.into_iter();
+            let answer = values.next().ok_or("Text entry has no correct response")?;
+            if values.next().is_some() {
+                context
+                    .warnings
+                    .push("Only the first accepted answer was kept".to_string());
+            }
+            (
+                CreateQuestionRequest {
+                    question_type: "fill_in_blank".to_string(),
+                    content,
+                    content_type: Some(content_type.to_string()),
+                    content_language,
+                    correct_answer: Some(answer),
+                    multiple_answers: Some(false),
+                    explanation,
+                    time_limit_seconds: None,
+                    choices: None,
+                },
+                false,
+            )
+        }
+        other => return Err(format!("Unsupported interaction: {}", other)),
+    };
+
+    Ok((
+        BankQuestion {
+            request,
+            tags: Vec::new(),
+            content_id: None,
+        },
+        shuffle,
+    ))
+}
+
+/// The question text of an item body. A body that is a single `<pre>` block
+/// (plus the interaction) becomes a CODE question.
+fn render_body(
+    body: roxmltree::Node,
+    context: &mut ItemContext,
+) -> (String, &'static str, Option<String>) {
+    let blocks: Vec<roxmltree::Node> = body
+        .children()
+        .filter(|n| n.is_element() && !is_interaction_block(*n))
+        .collect();
+
+    if let [pre] = blocks.as_slice() {
+        if pre.has_tag_name("pre") {
+            let language = pre
+                .descendants()
+                .filter_map(|n| n.attribute("class"))
+                .flat_map(|c| c.split_whitespace())
+                .find_map(|c| c.strip_prefix("language-"))
+                .map(|l| l.to_string());
+            let code: String = pre.descendants().filter(|n| n.is_text()).filter_map(|n| n.text()).collect();
+            return (code.trim_matches('\n').to_string(), "CODE", language);
+        }
+    }
+
+    (render_text(body, context), "TEXT", None)
+}
+
+/// A block that only wraps the interaction, e.g. `<p><textEntryInteraction/></p>`
+fn is_interaction_block(node: roxmltree::Node) -> bool {
+    if node.tag_name().name().ends_with("Interaction") {
+        return true;
+    }
+    let has_text = node
+        .children()
+        .any(|n| n.is_text() && !n.text().unwrap_or("").trim().is_empty());
+    let elements: Vec<roxmltree::Node> = node.children().filter(|n| n.is_element()).collect();
+    !has_text && elements.len() == 1 && is_interaction_block(elements[0])
+}
+
+/// Flatten XHTML content into Kioku's Markdown-flavoured plain text
+fn render_text(node: roxmltree::Node, context: &mut ItemContext) -> String {
+    let mut out = String::new();
+    render_node(node, context, &mut out);
+
+    let mut lines: Vec<&str> = out.lines().map(|l| l.trim()).collect();
+    lines.dedup_by(|a, b| a.is_empty() && b.is_empty());
+    lines.join("\n").trim().to_string()
+}
+
+fn render_node(node: roxmltree::Node, context: &mut ItemContext, out: &mut String) {
+    for child in node.children() {
+        if child.is_text() {
+            let text = child.text().unwrap_or("");
+            let mut collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if text.starts_with(char::is_whitespace) && !out.ends_with([' ', '\n']) {
+                collapsed.insert(0, ' ');
+            }
+            if text.ends_with(char::is_whitespace) && !collapsed.is_empty() {
+                collapsed.push(' ');
+            }
+            out.push_str(&collapsed);
+            continue;
+        }
+        if !child.is_element() {
+            continue;
+        }
+
+        match child.tag_name().name() {
+            "br" => out.push('\n'),
+            "img" => {
+                let alt = child.attribute("alt").unwrap_or("");
+                match child.attribute("src").and_then(|src| context.import_image(src)) {
+                    Some(path) => out.push_str(&format!("![{}]({})", alt, path)),
+                    None => context
+                        .warnings
+                        .push("An image could not be read from the package".to_string()),
+                }
+            }
+            "pre" => {
+                let language = child
+                    .descendants()
+                    .filter_map(|n| n.attribute("class"))
+                    .flat_map(|c| c.split_whitespace())
+                    .find_map(|c| c.strip_prefix("language-"))
+                    .unwrap_or("");
+                let code: String = child.descendants().filter(|n| n.is_text()).filter_map(|n| n.text()).collect();
+                out.push_str(&format!("\n```{}\n{}\n```\n", language, code.trim_matches('\n')));
+            }
+            "textEntryInteraction" => {
+                // Only a blank inside a sentence needs a placeholder
+                if !node.has_tag_name("itemBody") && !is_interaction_block(node) {
+                    out.push_str("_____");
+                }
+            }
+            "simpleChoice" | "feedbackInline" | "feedbackBlock" | "rubricBlock" => {}
+            "p" | "div" | "li" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "blockquote" | "tr"
+            | "prompt" | "choiceInteraction" => {
+                render_node(child, context, out);
+                out.push('\n');
+            }
+            _ => render_node(child, context, out),
+        }
+    }
+}
+
+fn read_entry_bytes(archive: &mut zip::ZipArchive<std::fs::File>, name: &str) -> Result<Vec<u8>, String> {
+    let entry = archive
+        .by_name(name)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    let mut data = Vec::new();
+    entry
+        .take(MAX_PACKAGE_SIZE)
+        .read_to_end(&mut data)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    Ok(data)
+}
+
+fn read_entry_text(archive: &mut zip::ZipArchive<std::fs::File>, name: &str) -> Result<String, String> {
+    let data = read_entry_bytes(archive, name)?;
+    String::from_utf8(data).map_err(|_| format!("{} is not valid UTF-8", name))
+}
+
+/// Resolve an href relative to the package file that contains it
+fn resolve_href(base: &str, href: &str) -> String {
+    let href = href.split(['?', '#']).next().unwrap_or("");
+    let mut parts: Vec<&str> = match base.rsplit_once('/') {
+        Some((dir, _)) if !href.starts_with('/') => dir.split('/').collect(),
+        _ => Vec::new(),
+    };
+    for part in href.trim_start_matches('/').split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+// ============================================
+// Export
+// ============================================
+
+pub fn write_package(quiz: &Quiz) -> Result<Vec<u8>, String> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    let mut images = ImageExporter {
+        by_path: HashMap::new(),
+        files: Vec::new(),
+    };
+    let mut item_resources = String::new();
+
+    for (idx, question) in quiz.questions.iter().enumerate() {
+        let identifier = format!("item-{}", idx + 1);
+        let href = format!("{}.xml", identifier);
+        let first_image = images.files.len();
+
+        let item = write_item(&identifier, question, quiz.shuffle_choices, &mut images);
+        add_file(&mut zip, options, &href, item.as_bytes())?;
+
+        item_resources.push_str(&format!(
+            "    <resource identifier=\"{}\" type=\"imsqti_item_xmlv2p1\" href=\"{}\">\n      <file href=\"{}\"/>\n",
+            identifier, href, href
+        ));
+        for (name, _) in &images.files[first_image..] {
+            item_resources.push_str(&format!("      <file href=\"{}\"/>\n", xml_escape(name)));
+        }
+        item_resources.push_str("    </resource>\n");
+    }
+
+    for (name, path) in &images.files {
+        let data = std::fs::read(path).map_err(|e| format!("Failed to read image: {}", e))?;
+        add_file(&mut zip, options, name, &data)?;
+    }
+
+    add_file(&mut zip, options, "assessment.xml", write_test(quiz).as_bytes())?;
+
+    let mut manifest = String::new();
+    manifest.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    manifest.push_str(&format!(
+        "<manifest xmlns=\"http://www.imsglobal.org/xsd/imscp_v1p1\" identifier=\"kioku-{}\">\n",
+        xml_escape(&quiz.id)
+    ));
+    manifest.push_str("  <metadata>\n    <schema>QTIv2.1 Package</schema>\n    <schemaversion>1.0.0</schemaversion>\n  </metadata>\n");
+    manifest.push_str("  <organizations/>\n  <resources>\n");
+    manifest.push_str("    <resource identifier=\"test\" type=\"imsqti_test_xmlv2p1\" href=\"assessment.xml\">\n");
+    manifest.push_str("      <file href=\"assessment.xml\"/>\n");
+    for idx in 0..quiz.questions.len() {
+        manifest.push_str(&format!("      <dependency identifierref=\"item-{}\"/>\n", idx + 1));
+    }
+    manifest.push_str("    </resource>\n");
+    manifest.push_str(&item_resources);
+    manifest.push_str("  </resources>\n</manifest>\n");
+    add_file(&mut zip, options, "imsmanifest.xml", manifest.as_bytes())?;
+
+    let cursor = zip
+        .finish()
+        .map_err(|e| format!("Failed to write package: {}", e))?;
+    Ok(cursor.into_inner())
+}
+
+fn add_file(
+    zip: &mut zip::ZipWriter<Cursor<Vec<u8>>>,
+    options: FileOptions,
+    name: &str,
+    data: &[u8],
+) -> Result<(), String> {
+    zip.start_file(name, options)
+        .map_err(|e| format!("Failed to write {}: {}", name, e))?;
+    zip.write_all(data)
+        .map_err(|e| format!("Failed to write {}: {}", name, e))
+}
+
+/// Collects local image files referenced from questions
+struct ImageExporter {
+    by_path: HashMap<String, String>,
+    /// (name inside the package, local path)
+    files: Vec<(String, PathBuf)>,
+}
+
+impl ImageExporter {
+    fn package_name(&mut self, path: &str) -> Option<String> {
+        if let Some(name) = self.by_path.get(path) {
+            return Some(name.clone());
+        }
+
+        let local = PathBuf::from(path);
+        let extension = local
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .filter(|e| IMAGE_EXTENSIONS.contains(&e.as_str()))?;
+        if !local.is_file() {
+            return None;
+        }
+
+        let name = format!("images/image-{}.{}", self.files.len() + 1, extension);
+        self.by_path.insert(path.to_string(), name.clone());
+        self.files.push((name.clone(), local));
+        Some(name)
+    }
+}
+
+fn write_test(quiz: &Quiz) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<assessmentTest xmlns=\"{}\" identifier=\"test\" title=\"{}\">\n",
+        QTI_NAMESPACE,
+        xml_escape(&quiz.name)
+    ));
+    out.push_str("  <testPart identifier=\"part-1\" navigationMode=\"nonlinear\" submissionMode=\"simultaneous\">\n");
+    if let Some(limit) = quiz.time_limit_seconds {
+        out.push_str(&format!("    <timeLimits maxTime=\"{}\"/>\n", limit));
+    }
+    out.push_str(&format!(
+        "    <assessmentSection identifier=\"section-1\" title=\"{}\" visible=\"true\">\n",
+        xml_escape(&quiz.name)
+    ));
+    if quiz.shuffle_questions {
+        out.push_str("      <ordering shuffle=\"true\"/>\n");
+    }
+    for idx in 0..quiz.questions.len() {
+        out.push_str(&format!(
+            "      <assessmentItemRef identifier=\"item-{0}\" href=\"item-{0}.xml\"/>\n",
+            idx + 1
+        ));
+    }
+    out.push_str("    </assessmentSection>\n  </testPart>\n</assessmentTest>\n");
+    out
+}
+
+fn write_item(
+    identifier: &str,
+    question: &Question,
+    shuffle_choices: bool,
+    images: &mut ImageExporter,
+) -> String {
+    let title: String = question.content.lines().next().unwrap_or("").chars().take(60).collect();
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<assessmentItem xmlns=\"{}\" identifier=\"{}\" title=\"{}\" adaptive=\"false\" timeDependent=\"false\">\n",
+        QTI_NAMESPACE,
+        identifier,
+        xml_escape(&title)
+    ));
+
+    let (cardinality, base_type, correct): (&str, &str, Vec<String>) = match question.question_type {
+        QuestionType::MultipleChoice => (
+            if question.multiple_answers { "multiple" } else { "single" },
+            "identifier",
+            question
+                .choices
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.is_correct)
+                .map(|(i, _)| format!("choice-{}", i + 1))
+                .collect(),
+        ),
+        QuestionType::FillInBlank => (
+            "single",
+            "string",
+            question.correct_answer.iter().cloned().collect(),
+        ),
+    };
+
+    out.push_str(&format!(
+        "  <responseDeclaration identifier=\"RESPONSE\" cardinality=\"{}\" baseType=\"{}\">\n    <correctResponse>\n",
+        cardinality, base_type
+    ));
+    for value in &correct {
+        out.push_str(&format!("      <value>{}</value>\n", xml_escape(value)));
+    }
+    out.push_str("    </correctResponse>\n  </responseDeclaration>\n");
+    out.push_str("  <outcomeDeclaration identifier=\"SCORE\" cardinality=\"single\" baseType=\"float\">\n    <defaultValue><value>0</value></defaultValue>\n  </outcomeDeclaration>\n");
+    out.push_str("  <outcomeDeclaration identifier=\"FEEDBACK\" cardinality=\"single\" baseType=\"identifier\"/>\n");
+
+    out.push_str("  <itemBody>\n");
+    if question.content_type == "CODE" {
+        out.push_str(&format!(
+            "    <pre><code class=\"language-{}\">{}</code></pre>\n",
+            xml_escape(question.content_language.as_deref().unwrap_or("plaintext")),
+            xml_escape(&question.content)
+        ));
+    } else {
+        out.push_str(&format!("    <div>{}</div>\n", text_to_xhtml(&question.content, images)));
+    }
+
+    match question.question_type {
+        QuestionType::MultipleChoice => {
+            let max_choices = if question.multiple_answers { 0 } else { 1 };
+            out.push_str(&format!(
+                "    <choiceInteraction responseIdentifier=\"RESPONSE\" shuffle=\"{}\" maxChoices=\"{}\">\n",
+                shuffle_choices, max_choices
+            ));
+            for (i, choice) in question.choices.iter().enumerate() {
+                out.push_str(&format!(
+                    "      <simpleChoice identifier=\"choice-{}\">{}</simpleChoice>\n",
+                    i + 1,
+                    text_to_xhtml(&choice.text, images)
+                ));
+            }
+            out.push_str("    </choiceInteraction>\n");
+        }
+        QuestionType::FillInBlank => {
+            let expected = question.correct_answer.as_deref().map_or(0, |a| a.chars().count());
+            out.push_str(&format!(
+                "    <p><textEntryInteraction responseIdentifier=\"RESPONSE\" expectedLength=\"{}\"/></p>\n",
+                expected.max(10)
+            ));
+        }
+    }
+    out.push_str("  </itemBody>\n");
+
+    // Score with an exact match and always show the explanation afterwards
+    out.push_str("  <responseProcessing>\n    <responseCondition>\n      <responseIf>\n");
+    out.push_str("        <match><variable identifier=\"RESPONSE\"/><correct identifier=\"RESPONSE\"/></match>\n");
+    out.push_str("        <setOutcomeValue identifier=\"SCORE\"><baseValue baseType=\"float\">1</baseValue></setOutcomeValue>\n");
+    out.push_str("      </responseIf>\n      <responseElse>\n");
+    out.push_str("        <setOutcomeValue identifier=\"SCORE\"><baseValue baseType=\"float\">0</baseValue></setOutcomeValue>\n");
+    out.push_str("      </responseElse>\n    </responseCondition>\n");
+    if question.explanation.is_some() {
+        out.push_str("    <setOutcomeValue identifier=\"FEEDBACK\"><baseValue baseType=\"identifier\">explanation</baseValue></setOutcomeValue>\n");
+    }
+    out.push_str("  </responseProcessing>\n");
+
+    if let Some(explanation) = &question.explanation {
+        out.push_str(&format!(
+            "  <modalFeedback outcomeIdentifier=\"FEEDBACK\" identifier=\"explanation\" showHide=\"show\">{}</modalFeedback>\n",
+            text_to_xhtml(explanation, images)
+        ));
+    }
+    out.push_str("</assessmentItem>\n");
+    out
+}
+
+/// Escape text for an XHTML body, turning line breaks into `<br/>` and
+/// Markdown images of local files into `<img>` elements
+fn text_to_xhtml(text: &str, images: &mut ImageExporter) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("![") {
+        let image = rest[start + 2..].split_once("](").and_then(|(alt, after)| {
+            let (src, tail) = after.split_once(')')?;
+            Some((alt, src.trim(), tail))
+        });
+        let Some((alt, src, tail)) = image else {
+            break;
+        };
+        match images.package_name(src) {
+            Some(name) => {
+                out.push_str(&escape_lines(&rest[..start]));
+                out.push_str(&format!(
+                    "<img src=\"{}\" alt=\"{}\"/>",
+                    xml_escape(&name),
+                    xml_escape(alt)
+                ));
+            }
+            None => {
+                let consumed = rest.len() - tail.len();
+                out.push_str(&escape_lines(&rest[..consumed]));
+            }
+        }
+        rest = tail;
+    }
+    out.push_str(&escape_lines(rest));
+    out
+}
+
+fn escape_lines(text: &str) -> String {
+    xml_escape(text).replace('\n', "<br/>")
+}
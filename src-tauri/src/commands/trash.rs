@@ -0,0 +1,53 @@
+use tauri::State;
+
+use crate::db::{self, DbState, TrashItem, TrashRestoreResult};
+
+// ============================================
+// Trash Commands
+// ============================================
+
+#[tauri::command]
+pub fn get_trash(state: State<DbState>) -> Result<Vec<TrashItem>, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+    db::get_trash(&conn, &active_user.id)
+}
+
+#[tauri::command]
+pub fn restore_from_trash(state: State<DbState>, id: String) -> Result<TrashRestoreResult, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+    db::restore_from_trash(&conn, &active_user.id, &id)
+}
+
+#[tauri::command]
+pub fn delete_from_trash(state: State<DbState>, id: String) -> Result<(), String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+    db::delete_from_trash(&conn, &active_user.id, &id)
+}
+
+/// Returns how many items were deleted
+#[tauri::command]
+pub fn empty_trash(state: State<DbState>) -> Result<usize, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let active_user = db::get_active_user(&conn)?
+        .ok_or_else(|| "No active user".to_string())?;
+    db::empty_trash(&conn, &active_user.id)
+}
+
+#[tauri::command]
+pub fn get_trash_retention_days(state: State<DbState>) -> Result<i64, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::get_trash_retention_days(&conn)
+}
+
+/// Returns how many items were purged by the shorter retention
+#[tauri::command]
+pub fn set_trash_retention_days(state: State<DbState>, days: i64) -> Result<usize, String> {
+    let conn = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    db::set_trash_retention_days(&conn, days)
+}
@@ -0,0 +1,204 @@
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use rusqlite::{params, Connection};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use super::courses::get_course_with_lessons;
+use super::electives::course_completion;
+use super::models::{Certificate, CertificateScore, LessonItemType};
+use super::users::get_user;
+
+/// Where the install's certificate signing key is kept
+const CERTIFICATE_SECRET_KEY: &str = "certificate_secret";
+
+// ============================================
+// Certificate Operations
+// ============================================
+
+/// Issue a certificate for a course the user has completed. A course only
+/// ever gets one certificate per user; asking again returns the first.
+pub fn issue_certificate(conn: &Connection, user_id: &str, course_id: &str) -> Result<Certificate, String> {
+    if let Some(existing) = find_certificate_for_course(conn, user_id, course_id)? {
+        return Ok(existing);
+    }
+
+    let mut course = get_course_with_lessons(conn, user_id, course_id)?
+        .ok_or_else(|| format!("Course not found: {}", course_id))?;
+    let lessons = std::mem::take(&mut course.lessons);
+
+    let completion = course_completion(&lessons, &mut course.elective_groups);
+    if completion.required == 0 {
+        return Err("Course has nothing to complete".to_string());
+    }
+    if let Some(unmet) = completion.unmet {
+        return Err(format!("Course is not complete: {}", unmet));
+    }
+
+    // A course completed only through elective rules may have no progress rows
+    let completed_at: Option<String> = conn
+        .query_row(
+            "SELECT MAX(completed_at) FROM lesson_progress WHERE user_id = ?1 AND course_id = ?2",
+            params![user_id, course_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to get completion date: {}", e))?;
+    let completed_at = completed_at.unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+
+    let scores: Vec<CertificateScore> = lessons
+        .iter()
+        .filter(|l| l.is_completed == Some(true))
+        .flat_map(|l| &l.items)
+        .filter(|i| i.item_type == LessonItemType::Quiz)
+        .map(|i| CertificateScore {
+            quiz_name: i.item_name.clone(),
+            score_percentage: i.best_score,
+        })
+        .collect();
+
+    let mut certificate = Certificate {
+        id: Uuid::new_v4().to_string(),
+        user_id: user_id.to_string(),
+        course_id: Some(course.id),
+        learner_name: get_user(conn, user_id)?.name,
+        course_name: course.name,
+        completed_at,
+        scores,
+        verification_hash: String::new(),
+        issued_at: chrono::Utc::now().to_rfc3339(),
+    };
+    certificate.verification_hash = verification_hash(&certificate_secret(conn)?, &certificate);
+
+    let scores_json = serde_json::to_string(&certificate.scores)
+        .map_err(|e| format!("Failed to serialize scores: {}", e))?;
+    conn.execute(
+        "INSERT INTO certificates (id, user_id, course_id, learner_name, course_name, completed_at,
+                                   scores, verification_hash, issued_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            certificate.id,
+            certificate.user_id,
+            certificate.course_id,
+            certificate.learner_name,
+            certificate.course_name,
+            certificate.completed_at,
+            scores_json,
+            certificate.verification_hash,
+            certificate.issued_at,
+        ],
+    )
+    .map_err(|e| format!("Failed to save certificate: {}", e))?;
+
+    Ok(certificate)
+}
+
+pub fn get_certificates(conn: &Connection, user_id: &str) -> Result<Vec<Certificate>, String> {
+    query_certificates(conn, "user_id = ?1 ORDER BY issued_at DESC", user_id)
+}
+
+pub fn get_certificate(conn: &Connection, user_id: &str, id: &str) -> Result<Option<Certificate>, String> {
+    Ok(get_certificates(conn, user_id)?.into_iter().find(|c| c.id == id))
+}
+
+fn find_certificate_for_course(
+    conn: &Connection,
+    user_id: &str,
+    course_id: &str,
+) -> Result<Option<Certificate>, String> {
+    Ok(get_certificates(conn, user_id)?
+        .into_iter()
+        .find(|c| c.course_id.as_deref() == Some(course_id)))
+}
+
+/// Look up a certificate by the hash printed on it. Only a certificate whose
+/// details still produce that hash is returned, so an edited row fails.
+pub fn verify_certificate(conn: &Connection, hash: &str) -> Result<Option<Certificate>, String> {
+    let hash = hash.trim().to_ascii_lowercase();
+    let certificate = query_certificates(conn, "verification_hash = ?1", &hash)?.into_iter().next();
+    let secret = certificate_secret(conn)?;
+    Ok(certificate.filter(|c| verification_hash(&secret, c) == c.verification_hash))
+}
+
+fn query_certificates(conn: &Connection, condition: &str, value: &str) -> Result<Vec<Certificate>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id, user_id, course_id, learner_name, course_name, completed_at, scores,
+                    verification_hash, issued_at
+             FROM certificates WHERE {}",
+            condition
+        ))
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let certificates = stmt
+        .query_map(params![value], |row| {
+            let scores: String = row.get(6)?;
+            Ok(Certificate {
+                id: row.get(0)?,
+                user_id: row.get(1)?,
+                course_id: row.get(2)?,
+                learner_name: row.get(3)?,
+                course_name: row.get(4)?,
+                completed_at: row.get(5)?,
+                scores: serde_json::from_str(&scores).unwrap_or_default(),
+                verification_hash: row.get(7)?,
+                issued_at: row.get(8)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query certificates: {}", e))?;
+
+    certificates
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect certificates: {}", e))
+}
+
+/// This install's key for signing certificates, created on first use. Without
+/// it, a matching hash cannot be worked out from a certificate's details.
+fn certificate_secret(conn: &Connection) -> Result<Vec<u8>, String> {
+    let stored = conn.query_row(
+        "SELECT value FROM app_state WHERE key = ?1",
+        params![CERTIFICATE_SECRET_KEY],
+        |row| row.get::<_, String>(0),
+    );
+    let secret = match stored {
+        Ok(secret) => secret,
+        Err(rusqlite::Error::QueryReturnedNoRows) => {
+            let mut secret = [0u8; 32];
+            rand::thread_rng().fill_bytes(&mut secret);
+            let secret: String = secret.iter().map(|byte| format!("{:02x}", byte)).collect();
+            conn.execute(
+                "INSERT INTO app_state (key, value) VALUES (?1, ?2)",
+                params![CERTIFICATE_SECRET_KEY, secret],
+            )
+            .map_err(|e| format!("Failed to save certificate key: {}", e))?;
+            secret
+        }
+        Err(e) => return Err(format!("Failed to get certificate key: {}", e)),
+    };
+    Ok(secret.into_bytes())
+}
+
+/// HMAC-SHA256 over everything the certificate states. Each field goes in
+/// behind its length, so no name can pass for the start of the next field.
+fn verification_hash(secret: &[u8], certificate: &Certificate) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    let mut field = |value: &str| {
+        mac.update(&(value.len() as u64).to_be_bytes());
+        mac.update(value.as_bytes());
+    };
+
+    field(&certificate.id);
+    field(&certificate.learner_name);
+    field(&certificate.course_name);
+    field(&certificate.completed_at);
+    for score in &certificate.scores {
+        let value = score.score_percentage.map(|s| s.to_string()).unwrap_or_default();
+        field(&score.quiz_name);
+        field(&value);
+    }
+
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
@@ -1,7 +1,17 @@
+use std::collections::HashMap;
+
 use rusqlite::{params, Connection};
 use uuid::Uuid;
 
-use super::models::{Course, Lesson, LessonItem, LessonItemType, LessonProgress, RequirementType};
+use super::electives::{
+    course_completion, get_elective_groups, get_elective_groups_by_course, is_mandatory,
+};
+use super::models::{
+    Course, Lesson, LessonItem, LessonItemType, LessonPrerequisites, LessonProgress,
+    RequirementCheck, RequirementType,
+};
+use super::notebooks::{get_page_read, get_pages_for_notebook};
+use super::pacing::{apply_pacing, get_enrollment, today};
 
 // ============================================
 // Course Operations
@@ -32,19 +42,8 @@ pub fn get_all_courses(conn: &Connection, user_id: &str) -> Result<Vec<Course>,
         .prepare(
             "SELECT c.id, c.user_id, c.name, c.description, c.created_at, c.updated_at,
                     (SELECT COUNT(*) FROM lessons WHERE course_id = c.id) as lesson_count,
-                    (SELECT COUNT(DISTINCT l.id) FROM lessons l
-                     JOIN lesson_items li ON li.lesson_id = l.id
-                     WHERE l.course_id = c.id
-                     AND NOT EXISTS (
-                         SELECT 1 FROM lesson_items li2
-                         WHERE li2.lesson_id = l.id
-                         AND NOT EXISTS (
-                             SELECT 1 FROM lesson_progress lp
-                             WHERE lp.lesson_item_id = li2.id AND lp.user_id = ?1 AND lp.completed_at IS NOT NULL
-                         )
-                     )
-                    ) as completed_lesson_count,
-                    EXISTS(SELECT 1 FROM course_favorites WHERE course_id = c.id AND user_id = ?1) as is_favorite
+                    EXISTS(SELECT 1 FROM course_favorites WHERE course_id = c.id AND user_id = ?1) as is_favorite,
+                    COALESCE(c.content_id, c.id), c.version
              FROM courses c WHERE c.user_id = ?1 ORDER BY c.updated_at DESC",
         )
         .map_err(|e| format!("Failed to prepare query: {}", e))?;
@@ -53,43 +52,76 @@ pub fn get_all_courses(conn: &Connection, user_id: &str) -> Result<Vec<Course>,
         .query_map(params![user_id], |row| {
             Ok(Course {
                 id: row.get(0)?,
+                content_id: row.get(8)?,
                 user_id: row.get(1)?,
                 name: row.get(2)?,
                 description: row.get(3)?,
+                version: row.get(9)?,
                 created_at: row.get(4)?,
                 updated_at: row.get(5)?,
                 lesson_count: Some(row.get(6)?),
-                completed_lesson_count: Some(row.get(7)?),
-                is_favorite: Some(row.get::<_, i32>(8)? == 1),
+                completed_lesson_count: None,
+                required_lesson_count: None,
+                is_completed: None,
+                is_favorite: Some(row.get::<_, i32>(7)? == 1),
                 lessons: vec![],
+                elective_groups: vec![],
+                enrollment: None,
+                overdue_lesson_count: None,
             })
         })
         .map_err(|e| format!("Failed to query courses: {}", e))?;
 
-    courses
+    let mut courses = courses
         .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect courses: {}", e))
+        .map_err(|e| format!("Failed to collect courses: {}", e))?;
+
+    // Optional and elective lessons make progress a rule, not a count;
+    // lessons and groups of every course are loaded at once
+    let mut lessons_by_course: HashMap<String, Vec<Lesson>> = HashMap::new();
+    for lesson in load_lessons(conn, user_id, None)? {
+        lessons_by_course.entry(lesson.course_id.clone()).or_default().push(lesson);
+    }
+    let mut groups_by_course = get_elective_groups_by_course(conn, user_id)?;
+    for course in &mut courses {
+        let lessons = lessons_by_course.remove(&course.id).unwrap_or_default();
+        let mut groups = groups_by_course.remove(&course.id).unwrap_or_default();
+        let completion = course_completion(&lessons, &mut groups);
+        course.completed_lesson_count = Some(completion.completed);
+        course.required_lesson_count = Some(completion.required);
+        course.is_completed = Some(completion.is_complete());
+    }
+
+    Ok(courses)
 }
 
 pub fn get_course(conn: &Connection, user_id: &str, id: &str) -> Result<Option<Course>, String> {
     match conn.query_row(
         "SELECT c.id, c.user_id, c.name, c.description, c.created_at, c.updated_at,
                 (SELECT COUNT(*) FROM lessons WHERE course_id = c.id) as lesson_count,
-                EXISTS(SELECT 1 FROM course_favorites WHERE course_id = c.id AND user_id = ?1) as is_favorite
+                EXISTS(SELECT 1 FROM course_favorites WHERE course_id = c.id AND user_id = ?1) as is_favorite,
+                COALESCE(c.content_id, c.id), c.version
          FROM courses c WHERE c.id = ?2",
         params![user_id, id],
         |row| {
             Ok(Course {
                 id: row.get(0)?,
+                content_id: row.get(8)?,
                 user_id: row.get(1)?,
                 name: row.get(2)?,
                 description: row.get(3)?,
+                version: row.get(9)?,
                 created_at: row.get(4)?,
                 updated_at: row.get(5)?,
                 lesson_count: Some(row.get(6)?),
                 completed_lesson_count: None, // Will be calculated when loading lessons
+                required_lesson_count: None,
+                is_completed: None,
                 is_favorite: Some(row.get::<_, i32>(7)? == 1),
                 lessons: vec![],
+                elective_groups: vec![],
+                enrollment: None,
+                overdue_lesson_count: None,
             })
         },
     ) {
@@ -106,15 +138,22 @@ pub fn get_course_with_lessons(conn: &Connection, user_id: &str, id: &str) -> Re
     };
 
     let lessons = get_lessons(conn, user_id, id)?;
+    let mut elective_groups = get_elective_groups(conn, id)?;
+    let completion = course_completion(&lessons, &mut elective_groups);
 
-    // Calculate completed lessons count
-    let completed_count = lessons.iter()
-        .filter(|l| l.is_completed.unwrap_or(false))
-        .count() as i32;
+    let enrollment = get_enrollment(conn, user_id, id)?;
+    let overdue_count = enrollment.as_ref().map(|_| {
+        lessons.iter().filter(|l| l.is_overdue == Some(true)).count() as i32
+    });
 
     Ok(Some(Course {
         lessons,
-        completed_lesson_count: Some(completed_count),
+        completed_lesson_count: Some(completion.completed),
+        required_lesson_count: Some(completion.required),
+        is_completed: Some(completion.is_complete()),
+        elective_groups,
+        enrollment,
+        overdue_lesson_count: overdue_count,
         ..course
     }))
 }
@@ -152,6 +191,73 @@ pub fn delete_course(conn: &Connection, user_id: &str, id: &str) -> Result<(), S
     Ok(())
 }
 
+/// Find the course an earlier import created from the same source course
+pub fn find_course_by_content_id(
+    conn: &Connection,
+    user_id: &str,
+    content_id: &str,
+) -> Result<Option<Course>, String> {
+    match conn.query_row(
+        "SELECT id FROM courses WHERE user_id = ?1 AND COALESCE(content_id, id) = ?2
+         ORDER BY updated_at DESC LIMIT 1",
+        params![user_id, content_id],
+        |row| row.get::<_, String>(0),
+    ) {
+        Ok(id) => get_course(conn, user_id, &id),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(format!("Database error: {}", e)),
+    }
+}
+
+pub fn set_course_content_id(conn: &Connection, id: &str, content_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE courses SET content_id = ?1 WHERE id = ?2",
+        params![content_id, id],
+    )
+    .map_err(|e| format!("Failed to set course content ID: {}", e))?;
+    Ok(())
+}
+
+pub fn set_course_version(conn: &Connection, id: &str, version: i32) -> Result<(), String> {
+    conn.execute(
+        "UPDATE courses SET version = ?1 WHERE id = ?2",
+        params![version, id],
+    )
+    .map_err(|e| format!("Failed to set course version: {}", e))?;
+    Ok(())
+}
+
+/// Give a revised course a higher version, so its next export can be applied
+/// as an update to copies imported from earlier ones
+pub fn raise_course_version(
+    conn: &Connection,
+    user_id: &str,
+    id: &str,
+    version: Option<i32>,
+) -> Result<Course, String> {
+    let course = get_course(conn, user_id, id)?
+        .filter(|c| c.user_id == user_id)
+        .ok_or_else(|| "Course not found or access denied".to_string())?;
+
+    let version = version.unwrap_or(course.version + 1);
+    if version <= course.version {
+        return Err(format!(
+            "The course is already at version {}; the new version must be higher",
+            course.version
+        ));
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE courses SET version = ?1, updated_at = ?2 WHERE id = ?3",
+        params![version, now, id],
+    )
+    .map_err(|e| format!("Failed to set course version: {}", e))?;
+
+    get_course(conn, user_id, id)?
+        .ok_or_else(|| format!("Course not found after update: {}", id))
+}
+
 // ============================================
 // Lesson Operations
 // ============================================
@@ -197,63 +303,102 @@ pub fn create_lesson(
     }
 
     Ok(Lesson {
+        content_id: id.clone(),
         id,
         course_id: course_id.to_string(),
         title: title.to_string(),
         description: description.map(|s| s.to_string()),
         position: pos,
+        requires_previous: false,
+        prerequisite_ids: vec![],
+        due_day: None,
+        is_optional: false,
+        elective_group_id: None,
         created_at: now.clone(),
         updated_at: now,
         items: vec![],
         is_completed: Some(false),
         completed_item_count: Some(0),
+        is_locked: Some(false),
+        unlock_reason: None,
+        due_date: None,
+        is_overdue: None,
     })
 }
 
 pub fn get_lessons(conn: &Connection, user_id: &str, course_id: &str) -> Result<Vec<Lesson>, String> {
-    use std::collections::HashMap;
+    let mut lessons = load_lessons(conn, user_id, Some(course_id))?;
+    let mut prerequisites = get_course_prerequisites(conn, course_id)?;
+    for lesson in &mut lessons {
+        lesson.prerequisite_ids = prerequisites.remove(&lesson.id).unwrap_or_default();
+    }
+
+    apply_lesson_locks(&mut lessons);
+    if let Some(enrollment) = get_enrollment(conn, user_id, course_id)? {
+        apply_pacing(&mut lessons, &enrollment, today());
+    }
+    Ok(lessons)
+}
 
-    // Query 1: Get all lessons for the course
+/// Lessons of one course, or of all the user's courses when `course_id` is
+/// None, with their items and completion but no locks or pacing
+fn load_lessons(conn: &Connection, user_id: &str, course_id: Option<&str>) -> Result<Vec<Lesson>, String> {
+    // Query 1: Get all lessons in scope
     let mut stmt = conn
         .prepare(
-            "SELECT id, course_id, title, description, position, created_at, updated_at
+            "SELECT id, course_id, title, description, position, created_at, updated_at,
+                    COALESCE(content_id, id), requires_previous, due_day, is_optional,
+                    elective_group_id
              FROM lessons
-             WHERE course_id = ?1
+             WHERE (?2 IS NULL AND course_id IN (SELECT id FROM courses WHERE user_id = ?1))
+                OR course_id = ?2
              ORDER BY position ASC",
         )
         .map_err(|e| format!("Failed to prepare query: {}", e))?;
 
     let mut lessons: Vec<Lesson> = stmt
-        .query_map(params![course_id], |row| {
+        .query_map(params![user_id, course_id], |row| {
             Ok(Lesson {
                 id: row.get(0)?,
+                content_id: row.get(7)?,
                 course_id: row.get(1)?,
                 title: row.get(2)?,
                 description: row.get(3)?,
                 position: row.get(4)?,
+                requires_previous: row.get::<_, i32>(8)? == 1,
+                prerequisite_ids: vec![],
+                due_day: row.get(9)?,
+                is_optional: row.get::<_, i32>(10)? == 1,
+                elective_group_id: row.get(11)?,
                 created_at: row.get(5)?,
                 updated_at: row.get(6)?,
                 items: vec![],
                 is_completed: None,
                 completed_item_count: None,
+                is_locked: None,
+                unlock_reason: None,
+                due_date: None,
+                is_overdue: None,
             })
         })
         .map_err(|e| format!("Failed to query lessons: {}", e))?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| format!("Failed to collect lessons: {}", e))?;
 
-    // Query 2: Get all lesson items for all lessons in this course (single query instead of N)
+    // Query 2: Get all lesson items for those lessons (single query instead of N)
     let mut items_stmt = conn
         .prepare(
             "SELECT li.lesson_id, li.id, li.item_type, li.item_id, li.item_name,
                     li.requirement_type, li.requirement_value, li.position, li.created_at,
                     lp.completed_at IS NOT NULL as is_completed,
                     li.item_id IS NULL as is_missing,
-                    lp.score_percentage as best_score
+                    lp.score_percentage as best_score,
+                    COALESCE(li.content_id, li.id), li.target_content_id
              FROM lesson_items li
              INNER JOIN lessons l ON l.id = li.lesson_id
              LEFT JOIN lesson_progress lp ON lp.lesson_item_id = li.id AND lp.user_id = ?1
-             WHERE l.course_id = ?2
+             WHERE (?2 IS NULL AND l.course_id IN (SELECT id FROM courses WHERE user_id = ?1))
+                OR l.course_id = ?2
              ORDER BY li.position ASC",
         )
         .map_err(|e| format!("Failed to prepare items query: {}", e))?;
@@ -268,10 +413,12 @@ pub fn get_lessons(conn: &Connection, user_id: &str, course_id: &str) -> Result<
                 lesson_id,
                 LessonItem {
                     id: row.get(1)?,
+                    content_id: row.get(12)?,
                     lesson_id: row.get(0)?,
                     item_type: LessonItemType::from_str(&item_type_str),
                     item_id: row.get(3)?,
                     item_name: row.get(4)?,
+                    target_content_id: row.get(13)?,
                     requirement_type: req_type_str.and_then(|s| RequirementType::from_str(&s)),
                     requirement_value: row.get(6)?,
                     position: row.get(7)?,
@@ -307,21 +454,33 @@ pub fn get_lessons(conn: &Connection, user_id: &str, course_id: &str) -> Result<
 
 pub fn get_lesson(conn: &Connection, user_id: &str, lesson_id: &str) -> Result<Option<Lesson>, String> {
     match conn.query_row(
-        "SELECT id, course_id, title, description, position, created_at, updated_at
+        "SELECT id, course_id, title, description, position, created_at, updated_at,
+                COALESCE(content_id, id), requires_previous, due_day, is_optional,
+                elective_group_id
          FROM lessons WHERE id = ?1",
         params![lesson_id],
         |row| {
             Ok(Lesson {
                 id: row.get(0)?,
+                content_id: row.get(7)?,
                 course_id: row.get(1)?,
                 title: row.get(2)?,
                 description: row.get(3)?,
                 position: row.get(4)?,
+                requires_previous: row.get::<_, i32>(8)? == 1,
+                prerequisite_ids: vec![],
+                due_day: row.get(9)?,
+                is_optional: row.get::<_, i32>(10)? == 1,
+                elective_group_id: row.get(11)?,
                 created_at: row.get(5)?,
                 updated_at: row.get(6)?,
                 items: vec![],
                 is_completed: None,
                 completed_item_count: None,
+                is_locked: None,
+                unlock_reason: None,
+                due_date: None,
+                is_overdue: None,
             })
         },
     ) {
@@ -335,6 +494,16 @@ pub fn get_lesson(conn: &Connection, user_id: &str, lesson_id: &str) -> Result<O
             lesson.items = items;
             lesson.completed_item_count = Some(completed_count);
             lesson.is_completed = Some(all_completed);
+
+            // Locks depend on the rest of the course
+            let course_lessons = get_lessons(conn, user_id, &lesson.course_id)?;
+            if let Some(course_lesson) = course_lessons.into_iter().find(|l| l.id == lesson.id) {
+                lesson.prerequisite_ids = course_lesson.prerequisite_ids;
+                lesson.is_locked = course_lesson.is_locked;
+                lesson.unlock_reason = course_lesson.unlock_reason;
+                lesson.due_date = course_lesson.due_date;
+                lesson.is_overdue = course_lesson.is_overdue;
+            }
             Ok(Some(lesson))
         }
         Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
@@ -394,33 +563,11 @@ pub fn delete_lesson(conn: &Connection, lesson_id: &str) -> Result<(), String> {
 }
 
 pub fn reorder_lessons(conn: &Connection, course_id: &str, lesson_ids: &[String]) -> Result<(), String> {
-    let now = chrono::Utc::now().to_rfc3339();
-
     // Begin transaction for atomic reordering
     conn.execute("BEGIN TRANSACTION", [])
         .map_err(|e| format!("Failed to begin transaction: {}", e))?;
 
-    let result = (|| -> Result<(), String> {
-        for (i, lesson_id) in lesson_ids.iter().enumerate() {
-            conn.execute(
-                "UPDATE lessons SET position = ?1, updated_at = ?2 WHERE id = ?3 AND course_id = ?4",
-                params![i as i32, now, lesson_id, course_id],
-            )
-            .map_err(|e| format!("Failed to reorder lesson: {}", e))?;
-        }
-
-        // Update course's updated_at
-        if let Err(e) = conn.execute(
-            "UPDATE courses SET updated_at = ?1 WHERE id = ?2",
-            params![now, course_id],
-        ) {
-            eprintln!("Warning: Failed to update course timestamp: {}", e);
-        }
-
-        Ok(())
-    })();
-
-    match result {
+    match set_lesson_order(conn, course_id, lesson_ids) {
         Ok(()) => {
             conn.execute("COMMIT", [])
                 .map_err(|e| format!("Failed to commit transaction: {}", e))?;
@@ -435,6 +582,253 @@ pub fn reorder_lessons(conn: &Connection, course_id: &str, lesson_ids: &[String]
     }
 }
 
+/// Renumber a course's lessons without opening a transaction of its own
+pub fn set_lesson_order(conn: &Connection, course_id: &str, lesson_ids: &[String]) -> Result<(), String> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    for (i, lesson_id) in lesson_ids.iter().enumerate() {
+        conn.execute(
+            "UPDATE lessons SET position = ?1, updated_at = ?2 WHERE id = ?3 AND course_id = ?4",
+            params![i as i32, now, lesson_id, course_id],
+        )
+        .map_err(|e| format!("Failed to reorder lesson: {}", e))?;
+    }
+
+    // Update course's updated_at
+    if let Err(e) = conn.execute(
+        "UPDATE courses SET updated_at = ?1 WHERE id = ?2",
+        params![now, course_id],
+    ) {
+        eprintln!("Warning: Failed to update course timestamp: {}", e);
+    }
+
+    Ok(())
+}
+
+pub fn set_lesson_content_id(conn: &Connection, lesson_id: &str, content_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE lessons SET content_id = ?1 WHERE id = ?2",
+        params![content_id, lesson_id],
+    )
+    .map_err(|e| format!("Failed to set lesson content ID: {}", e))?;
+    Ok(())
+}
+
+// ============================================
+// Lesson Prerequisites
+// ============================================
+
+/// Prerequisite lesson IDs for every lesson in a course, in course order
+fn get_course_prerequisites(conn: &Connection, course_id: &str) -> Result<HashMap<String, Vec<String>>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT lp.lesson_id, lp.prerequisite_id
+             FROM lesson_prerequisites lp
+             INNER JOIN lessons l ON l.id = lp.lesson_id
+             INNER JOIN lessons p ON p.id = lp.prerequisite_id
+             WHERE l.course_id = ?1
+             ORDER BY p.position ASC",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![course_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| format!("Failed to query lesson prerequisites: {}", e))?;
+
+    let mut prerequisites: HashMap<String, Vec<String>> = HashMap::new();
+    for row in rows {
+        let (lesson_id, prerequisite_id) = row.map_err(|e| format!("Failed to read prerequisite: {}", e))?;
+        prerequisites.entry(lesson_id).or_default().push(prerequisite_id);
+    }
+    Ok(prerequisites)
+}
+
+/// Replace a lesson's unlock rules. Prerequisites must be other lessons of
+/// the same course and may not loop back to the lesson.
+pub fn set_lesson_prerequisites(
+    conn: &Connection,
+    lesson_id: &str,
+    requires_previous: bool,
+    prerequisite_ids: &[String],
+) -> Result<(), String> {
+    let rule = LessonPrerequisites {
+        lesson_id: lesson_id.to_string(),
+        requires_previous,
+        prerequisite_ids: prerequisite_ids.to_vec(),
+    };
+    set_course_prerequisites(conn, std::slice::from_ref(&rule))
+}
+
+/// Replace the unlock rules of several lessons of one course together. The
+/// rules are checked as a whole, so an update may reverse a dependency that
+/// one lesson at a time would see as a loop.
+pub fn set_course_prerequisites(conn: &Connection, rules: &[LessonPrerequisites]) -> Result<(), String> {
+    let Some(first) = rules.first() else {
+        return Ok(());
+    };
+    let course_id: String = conn
+        .query_row(
+            "SELECT course_id FROM lessons WHERE id = ?1",
+            params![first.lesson_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Lesson not found: {}", e))?;
+
+    let in_course = |id: &str| -> Result<bool, String> {
+        conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM lessons WHERE id = ?1 AND course_id = ?2)",
+            params![id, course_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to check prerequisite: {}", e))
+    };
+
+    let mut graph = get_course_prerequisites(conn, &course_id)?;
+    for rule in rules {
+        if !in_course(&rule.lesson_id)? {
+            return Err("Lesson not found in this course".to_string());
+        }
+        for prerequisite_id in &rule.prerequisite_ids {
+            if prerequisite_id == &rule.lesson_id {
+                return Err("A lesson cannot be its own prerequisite".to_string());
+            }
+            if !in_course(prerequisite_id)? {
+                return Err("Prerequisites must be lessons of the same course".to_string());
+            }
+        }
+        graph.insert(rule.lesson_id.clone(), rule.prerequisite_ids.clone());
+    }
+
+    if has_prerequisite_loop(&graph) {
+        return Err("Prerequisites cannot form a loop".to_string());
+    }
+
+    // Imports call this inside their own transaction; only open one when nobody else has
+    let owns_transaction = conn.is_autocommit();
+    if owns_transaction {
+        conn.execute("BEGIN TRANSACTION", [])
+            .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    }
+
+    let result = (|| -> Result<(), String> {
+        let now = chrono::Utc::now().to_rfc3339();
+        for rule in rules {
+            conn.execute(
+                "UPDATE lessons SET requires_previous = ?1, updated_at = ?2 WHERE id = ?3",
+                params![rule.requires_previous, now, rule.lesson_id],
+            )
+            .map_err(|e| format!("Failed to update lesson: {}", e))?;
+
+            conn.execute(
+                "DELETE FROM lesson_prerequisites WHERE lesson_id = ?1",
+                params![rule.lesson_id],
+            )
+            .map_err(|e| format!("Failed to clear lesson prerequisites: {}", e))?;
+
+            for prerequisite_id in &rule.prerequisite_ids {
+                conn.execute(
+                    "INSERT OR IGNORE INTO lesson_prerequisites (lesson_id, prerequisite_id) VALUES (?1, ?2)",
+                    params![rule.lesson_id, prerequisite_id],
+                )
+                .map_err(|e| format!("Failed to save lesson prerequisite: {}", e))?;
+            }
+        }
+        Ok(())
+    })();
+
+    if owns_transaction {
+        match result {
+            Ok(()) => {
+                conn.execute("COMMIT", [])
+                    .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            }
+            Err(e) => {
+                if let Err(rollback_err) = conn.execute("ROLLBACK", []) {
+                    eprintln!("Warning: Failed to rollback transaction: {}", rollback_err);
+                }
+                return Err(e);
+            }
+        }
+    } else {
+        result?;
+    }
+
+    Ok(())
+}
+
+/// Whether following prerequisites from any lesson can lead back to it
+fn has_prerequisite_loop(graph: &HashMap<String, Vec<String>>) -> bool {
+    // Lessons on the current path are `false`, finished ones `true`
+    let mut state: HashMap<&str, bool> = HashMap::new();
+    for start in graph.keys() {
+        if state.contains_key(start.as_str()) {
+            continue;
+        }
+        let mut stack: Vec<(&str, usize)> = vec![(start, 0)];
+        state.insert(start, false);
+        while let Some((id, next)) = stack.last_mut() {
+            let prerequisites = graph.get(*id).map(|p| p.as_slice()).unwrap_or_default();
+            match prerequisites.get(*next) {
+                Some(prerequisite) => {
+                    *next += 1;
+                    match state.get(prerequisite.as_str()) {
+                        Some(false) => return true,
+                        Some(true) => {}
+                        None => {
+                            state.insert(prerequisite, false);
+                            stack.push((prerequisite, 0));
+                        }
+                    }
+                }
+                None => {
+                    state.insert(id, true);
+                    stack.pop();
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Lock each lesson until the previous mandatory lesson (when it asks for
+/// that) and all of its prerequisites are complete. Lessons must be in course
+/// order and carry their completion. A lesson with no items never holds
+/// others back.
+fn apply_lesson_locks(lessons: &mut [Lesson]) {
+    let done: HashMap<String, (bool, String)> = lessons
+        .iter()
+        .map(|l| {
+            let is_done = l.items.is_empty() || l.is_completed == Some(true);
+            (l.id.clone(), (is_done, l.title.clone()))
+        })
+        .collect();
+
+    let mut previous: Option<String> = None;
+    for lesson in lessons.iter_mut() {
+        let mut waiting_on: Vec<&str> = Vec::new();
+        let previous_required = previous.as_ref().filter(|_| lesson.requires_previous);
+        for id in previous_required.into_iter().chain(&lesson.prerequisite_ids) {
+            if let Some((false, title)) = done.get(id) {
+                if !waiting_on.contains(&title.as_str()) {
+                    waiting_on.push(title);
+                }
+            }
+        }
+
+        lesson.is_locked = Some(!waiting_on.is_empty());
+        lesson.unlock_reason = if waiting_on.is_empty() {
+            None
+        } else {
+            let titles: Vec<String> = waiting_on.iter().map(|t| format!("\"{}\"", t)).collect();
+            Some(format!("Complete {} first", titles.join(", ")))
+        };
+        // Optional and elective lessons never hold back the next one
+        if is_mandatory(lesson) {
+            previous = Some(lesson.id.clone());
+        }
+    }
+}
+
 // ============================================
 // Lesson Item Operations
 // ============================================
@@ -446,7 +840,8 @@ pub fn get_lesson_items(conn: &Connection, user_id: &str, lesson_id: &str) -> Re
                     li.requirement_type, li.requirement_value, li.position, li.created_at,
                     lp.completed_at IS NOT NULL as is_completed,
                     li.item_id IS NULL as is_missing,
-                    lp.score_percentage as best_score
+                    lp.score_percentage as best_score,
+                    COALESCE(li.content_id, li.id), li.target_content_id
              FROM lesson_items li
              LEFT JOIN lesson_progress lp ON lp.lesson_item_id = li.id AND lp.user_id = ?1
              WHERE li.lesson_id = ?2
@@ -460,10 +855,12 @@ pub fn get_lesson_items(conn: &Connection, user_id: &str, lesson_id: &str) -> Re
             let req_type_str: Option<String> = row.get(5)?;
             Ok(LessonItem {
                 id: row.get(0)?,
+                content_id: row.get(12)?,
                 lesson_id: row.get(1)?,
                 item_type: LessonItemType::from_str(&item_type_str),
                 item_id: row.get(3)?,
                 item_name: row.get(4)?,
+                target_content_id: row.get(13)?,
                 requirement_type: req_type_str.and_then(|s| RequirementType::from_str(&s)),
                 requirement_value: row.get(6)?,
                 position: row.get(7)?,
@@ -508,10 +905,27 @@ pub fn add_lesson_item(
         }
     };
 
+    let target_content_id = match item_id {
+        Some(item_id) => target_content_id(conn, item_type, item_id)?,
+        None => None,
+    };
+
     conn.execute(
-        "INSERT INTO lesson_items (id, lesson_id, item_type, item_id, item_name, requirement_type, requirement_value, position, created_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-        params![id, lesson_id, item_type, item_id, item_name, requirement_type, requirement_value, pos, now],
+        "INSERT INTO lesson_items (id, lesson_id, item_type, item_id, item_name, target_content_id,
+                                   requirement_type, requirement_value, position, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            id,
+            lesson_id,
+            item_type,
+            item_id,
+            item_name,
+            target_content_id,
+            requirement_type,
+            requirement_value,
+            pos,
+            now
+        ],
     )
     .map_err(|e| format!("Failed to add lesson item: {}", e))?;
 
@@ -531,11 +945,13 @@ pub fn add_lesson_item(
     }
 
     Ok(LessonItem {
+        content_id: id.clone(),
         id,
         lesson_id: lesson_id.to_string(),
         item_type: LessonItemType::from_str(item_type),
         item_id: item_id.map(|s| s.to_string()),
         item_name: item_name.to_string(),
+        target_content_id,
         requirement_type: requirement_type.and_then(|s| RequirementType::from_str(s)),
         requirement_value,
         position: pos,
@@ -552,10 +968,19 @@ pub fn update_lesson_item_reference(
     item_id: &str,
 ) -> Result<(), String> {
     let now = chrono::Utc::now().to_rfc3339();
+    let item_type: String = conn
+        .query_row(
+            "SELECT item_type FROM lesson_items WHERE id = ?1",
+            params![lesson_item_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Lesson item not found: {}", e))?;
+    let target_content_id = target_content_id(conn, &item_type, item_id)?;
 
     conn.execute(
-        "UPDATE lesson_items SET item_id = ?1 WHERE id = ?2",
-        params![item_id, lesson_item_id],
+        "UPDATE lesson_items SET item_id = ?1, target_content_id = COALESCE(?2, target_content_id)
+         WHERE id = ?3",
+        params![item_id, target_content_id, lesson_item_id],
     )
     .map_err(|e| format!("Failed to update lesson item reference: {}", e))?;
 
@@ -570,6 +995,99 @@ pub fn update_lesson_item_reference(
     Ok(())
 }
 
+/// Change what an item asks for while keeping its row, and so its progress
+pub fn update_lesson_item(
+    conn: &Connection,
+    lesson_item_id: &str,
+    item_name: &str,
+    item_id: Option<&str>,
+    requirement_type: Option<&str>,
+    requirement_value: Option<i32>,
+) -> Result<(), String> {
+    let target_content_id = match item_id {
+        Some(item_id) => {
+            let item_type: String = conn
+                .query_row(
+                    "SELECT item_type FROM lesson_items WHERE id = ?1",
+                    params![lesson_item_id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Lesson item not found: {}", e))?;
+            target_content_id(conn, &item_type, item_id)?
+        }
+        None => None,
+    };
+
+    conn.execute(
+        "UPDATE lesson_items SET item_name = ?1, item_id = ?2, target_content_id = COALESCE(?3, target_content_id),
+                                 requirement_type = ?4, requirement_value = ?5
+         WHERE id = ?6",
+        params![item_name, item_id, target_content_id, requirement_type, requirement_value, lesson_item_id],
+    )
+    .map_err(|e| format!("Failed to update lesson item: {}", e))?;
+    Ok(())
+}
+
+/// Remember what a missing item pointed to, for relinking by content ID
+pub fn set_lesson_item_target(conn: &Connection, lesson_item_id: &str, target_content_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE lesson_items SET target_content_id = ?1 WHERE id = ?2",
+        params![target_content_id, lesson_item_id],
+    )
+    .map_err(|e| format!("Failed to set lesson item target: {}", e))?;
+    Ok(())
+}
+
+/// The content ID of the deck, quiz, page or notebook an item links to
+fn target_content_id(conn: &Connection, item_type: &str, item_id: &str) -> Result<Option<String>, String> {
+    let table = match item_type {
+        "deck" => "decks",
+        "quiz" => "quizzes",
+        "page" => "pages",
+        "notebook" => "notebooks",
+        _ => return Ok(None),
+    };
+    match conn.query_row(
+        &format!("SELECT COALESCE(content_id, id) FROM {} WHERE id = ?1", table),
+        params![item_id],
+        |row| row.get(0),
+    ) {
+        Ok(content_id) => Ok(Some(content_id)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(format!("Failed to get content ID: {}", e)),
+    }
+}
+
+/// Move an item to a position in any lesson of the same course. Progress
+/// follows the item; returns how many progress rows changed lesson.
+pub fn move_lesson_item(
+    conn: &Connection,
+    lesson_item_id: &str,
+    lesson_id: &str,
+    position: i32,
+) -> Result<usize, String> {
+    conn.execute(
+        "UPDATE lesson_items SET lesson_id = ?1, position = ?2 WHERE id = ?3",
+        params![lesson_id, position, lesson_item_id],
+    )
+    .map_err(|e| format!("Failed to move lesson item: {}", e))?;
+
+    conn.execute(
+        "UPDATE lesson_progress SET lesson_id = ?1 WHERE lesson_item_id = ?2 AND lesson_id != ?1",
+        params![lesson_id, lesson_item_id],
+    )
+    .map_err(|e| format!("Failed to move lesson progress: {}", e))
+}
+
+pub fn set_lesson_item_content_id(conn: &Connection, lesson_item_id: &str, content_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE lesson_items SET content_id = ?1 WHERE id = ?2",
+        params![content_id, lesson_item_id],
+    )
+    .map_err(|e| format!("Failed to set lesson item content ID: {}", e))?;
+    Ok(())
+}
+
 pub fn remove_lesson_item(conn: &Connection, lesson_item_id: &str) -> Result<(), String> {
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -641,10 +1159,174 @@ pub fn reorder_lesson_items(conn: &Connection, lesson_id: &str, item_ids: &[Stri
     }
 }
 
+// ============================================
+// Requirement Checks
+// ============================================
+
+/// Judge an item's requirement from the quiz attempts and study sessions
+/// behind it. A given attempt or session is judged on its own; otherwise the
+/// best one so far counts. Items without a requirement need a finished quiz
+/// or a study session with at least one card.
+pub fn check_requirement(
+    conn: &Connection,
+    item: &LessonItem,
+    attempt_id: Option<&str>,
+    session_id: Option<&str>,
+) -> Result<RequirementCheck, String> {
+    let unmet = |score_percentage: Option<i32>, reason: String| RequirementCheck {
+        is_met: false,
+        score_percentage,
+        reason: Some(reason),
+    };
+    let met = |score_percentage: Option<i32>| RequirementCheck {
+        is_met: true,
+        score_percentage,
+        reason: None,
+    };
+
+    let Some(target_id) = item.item_id.as_deref() else {
+        return Ok(unmet(None, format!("\"{}\" has not been imported", item.item_name)));
+    };
+
+    match item.item_type {
+        LessonItemType::Quiz => {
+            // Retry and adaptive attempts are practice; only full attempts are scored
+            let score: Option<i32> = match attempt_id {
+                Some(attempt_id) => match conn.query_row(
+                    "SELECT score_percentage FROM quiz_attempts
+                     WHERE id = ?1 AND quiz_id = ?2 AND mode = 'standard'
+                       AND completed_at IS NOT NULL AND abandoned_at IS NULL",
+                    params![attempt_id, target_id],
+                    |row| row.get(0),
+                ) {
+                    Ok(score) => Some(score),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                    Err(e) => return Err(format!("Failed to get quiz attempt: {}", e)),
+                },
+                None => conn
+                    .query_row(
+                        "SELECT MAX(score_percentage) FROM quiz_attempts
+                         WHERE quiz_id = ?1 AND mode = 'standard'
+                           AND completed_at IS NOT NULL AND abandoned_at IS NULL",
+                        params![target_id],
+                        |row| row.get(0),
+                    )
+                    .map_err(|e| format!("Failed to get best quiz score: {}", e))?,
+            };
+
+            match (score, &item.requirement_type) {
+                (None, _) => Ok(unmet(None, "Finish the quiz".to_string())),
+                (Some(score), Some(RequirementType::MinScore)) => {
+                    let required = item.requirement_value.unwrap_or(0);
+                    if score >= required {
+                        Ok(met(Some(score)))
+                    } else {
+                        Ok(unmet(Some(score), format!("Score at least {}% (got {}%)", required, score)))
+                    }
+                }
+                (Some(score), _) => Ok(met(Some(score))),
+            }
+        }
+        LessonItemType::Deck if item.requirement_type == Some(RequirementType::Review) => {
+            let required = item.requirement_value.unwrap_or(1).max(1);
+            let opened: i32 = match session_id {
+                Some(_) => 1,
+                None => conn
+                    .query_row(
+                        "SELECT COUNT(*) FROM study_sessions WHERE deck_id = ?1",
+                        params![target_id],
+                        |row| row.get(0),
+                    )
+                    .map_err(|e| format!("Failed to count study sessions: {}", e))?,
+            };
+
+            if opened >= required {
+                Ok(met(None))
+            } else {
+                Ok(unmet(None, format!("Open the deck {} more time(s)", required - opened)))
+            }
+        }
+        LessonItemType::Deck => {
+            let required = item.requirement_value.unwrap_or(1).max(1);
+            let studied: Option<i32> = match session_id {
+                Some(session_id) => match conn.query_row(
+                    "SELECT cards_studied FROM study_sessions
+                     WHERE id = ?1 AND deck_id = ?2 AND ended_at IS NOT NULL",
+                    params![session_id, target_id],
+                    |row| row.get(0),
+                ) {
+                    Ok(studied) => Some(studied),
+                    Err(rusqlite::Error::QueryReturnedNoRows) => None,
+                    Err(e) => return Err(format!("Failed to get study session: {}", e)),
+                },
+                None => conn
+                    .query_row(
+                        "SELECT MAX(cards_studied) FROM study_sessions
+                         WHERE deck_id = ?1 AND ended_at IS NOT NULL",
+                        params![target_id],
+                        |row| row.get(0),
+                    )
+                    .map_err(|e| format!("Failed to get study sessions: {}", e))?,
+            };
+
+            if studied.unwrap_or(0) >= required {
+                Ok(met(None))
+            } else {
+                Ok(unmet(None, format!("Study at least {} card(s) in one session", required)))
+            }
+        }
+        LessonItemType::Page => {
+            if page_is_read(conn, target_id, item.requirement_value)? {
+                Ok(met(None))
+            } else {
+                Ok(unmet(None, read_reason(item.requirement_value)))
+            }
+        }
+        LessonItemType::Notebook => {
+            let pages = get_pages_for_notebook(conn, target_id)?;
+            if pages.is_empty() {
+                return Ok(unmet(None, "The notebook has no pages to read".to_string()));
+            }
+
+            let mut unread = 0;
+            for page in pages {
+                if !page_is_read(conn, &page.id, item.requirement_value)? {
+                    unread += 1;
+                }
+            }
+
+            if unread == 0 {
+                Ok(met(None))
+            } else {
+                Ok(unmet(None, format!("{} more page(s) to read", unread)))
+            }
+        }
+    }
+}
+
+/// A page counts as read once marked, or after `min_seconds` on it when set
+fn page_is_read(conn: &Connection, page_id: &str, min_seconds: Option<i32>) -> Result<bool, String> {
+    let Some(read) = get_page_read(conn, page_id)? else {
+        return Ok(false);
+    };
+    Ok(read.marked_read_at.is_some() || min_seconds.is_some_and(|s| read.seconds_read >= s))
+}
+
+fn read_reason(min_seconds: Option<i32>) -> String {
+    match min_seconds {
+        Some(seconds) => format!("Read the page for {} second(s) or mark it as read", seconds),
+        None => "Mark the page as read".to_string(),
+    }
+}
+
 // ============================================
 // Lesson Progress Operations
 // ============================================
 
+/// Record progress on an item, judging its requirement from the learner's
+/// quiz attempts and study sessions. Progress on an unmet requirement keeps
+/// the score but is not complete; an item once completed stays completed,
+/// and the best score is kept along with the attempt that earned it.
 pub fn record_lesson_progress(
     conn: &Connection,
     user_id: &str,
@@ -657,35 +1339,81 @@ pub fn record_lesson_progress(
 ) -> Result<LessonProgress, String> {
     let now = chrono::Utc::now().to_rfc3339();
 
+    let lesson = get_lesson(conn, user_id, lesson_id)?
+        .ok_or_else(|| format!("Lesson not found: {}", lesson_id))?;
+    if lesson.is_locked == Some(true) {
+        return Err(format!(
+            "Lesson is locked: {}",
+            lesson.unlock_reason.unwrap_or_default()
+        ));
+    }
+    let item = lesson
+        .items
+        .iter()
+        .find(|i| i.id == lesson_item_id)
+        .ok_or_else(|| format!("Lesson item not found: {}", lesson_item_id))?;
+
+    let check = check_requirement(conn, item, attempt_id, session_id)?;
+    let score_percentage = check.score_percentage.or(score_percentage);
+    let completed_at = check.is_met.then(|| now.clone());
+
     // Check if progress already exists
-    let existing: Option<String> = conn
+    let existing = conn
         .query_row(
-            "SELECT id FROM lesson_progress WHERE user_id = ?1 AND lesson_item_id = ?2",
+            "SELECT id, completed_at, created_at, score_percentage, attempt_id
+             FROM lesson_progress WHERE user_id = ?1 AND lesson_item_id = ?2",
             params![user_id, lesson_item_id],
-            |row| row.get(0),
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<i32>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                ))
+            },
         )
         .ok();
 
-    let id = if let Some(existing_id) = existing {
+    let (id, completed_at, created_at, score_percentage, attempt_id) = if let Some((
+        existing_id,
+        existing_completed_at,
+        created_at,
+        existing_score,
+        existing_attempt_id,
+    )) = existing
+    {
+        // A lower score never replaces a better one, nor its attempt
+        let improved = match (score_percentage, existing_score) {
+            (Some(score), Some(best)) => score > best,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        let (score_percentage, attempt_id) = if improved {
+            (score_percentage, attempt_id.map(|s| s.to_string()).or(existing_attempt_id))
+        } else {
+            (existing_score, existing_attempt_id.or(attempt_id.map(|s| s.to_string())))
+        };
+
         // Update existing progress
         conn.execute(
-            "UPDATE lesson_progress SET completed_at = ?1, score_percentage = COALESCE(?2, score_percentage),
-             attempt_id = COALESCE(?3, attempt_id), session_id = COALESCE(?4, session_id), updated_at = ?5
+            "UPDATE lesson_progress SET completed_at = COALESCE(completed_at, ?1), score_percentage = ?2,
+             attempt_id = ?3, session_id = COALESCE(?4, session_id), updated_at = ?5
              WHERE id = ?6",
-            params![now, score_percentage, attempt_id, session_id, now, existing_id],
+            params![completed_at, score_percentage, attempt_id, session_id, now, existing_id],
         )
         .map_err(|e| format!("Failed to update lesson progress: {}", e))?;
-        existing_id
+        (existing_id, existing_completed_at.or(completed_at), created_at, score_percentage, attempt_id)
     } else {
         // Create new progress
         let new_id = Uuid::new_v4().to_string();
         conn.execute(
             "INSERT INTO lesson_progress (id, user_id, course_id, lesson_id, lesson_item_id, completed_at, score_percentage, attempt_id, session_id, created_at, updated_at)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
-            params![new_id, user_id, course_id, lesson_id, lesson_item_id, now, score_percentage, attempt_id, session_id, now, now],
+            params![new_id, user_id, course_id, lesson_id, lesson_item_id, completed_at, score_percentage, attempt_id, session_id, now, now],
         )
         .map_err(|e| format!("Failed to create lesson progress: {}", e))?;
-        new_id
+        (new_id, completed_at, now.clone(), score_percentage, attempt_id.map(|s| s.to_string()))
     };
 
     Ok(LessonProgress {
@@ -694,15 +1422,47 @@ pub fn record_lesson_progress(
         course_id: course_id.to_string(),
         lesson_id: lesson_id.to_string(),
         lesson_item_id: lesson_item_id.to_string(),
-        completed_at: Some(now.clone()),
+        completed_at,
         score_percentage,
-        attempt_id: attempt_id.map(|s| s.to_string()),
+        attempt_id,
         session_id: session_id.map(|s| s.to_string()),
-        created_at: now.clone(),
+        created_at,
         updated_at: now,
     })
 }
 
+/// The course of an item a learner is working on, refusing items in lessons
+/// that are still locked
+pub fn get_open_lesson_item(
+    conn: &Connection,
+    user_id: &str,
+    lesson_item_id: &str,
+) -> Result<(String, LessonItem), String> {
+    let lesson_id: String = conn
+        .query_row(
+            "SELECT lesson_id FROM lesson_items WHERE id = ?1",
+            params![lesson_item_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Lesson item not found: {}", e))?;
+
+    let lesson = get_lesson(conn, user_id, &lesson_id)?
+        .ok_or_else(|| format!("Lesson not found: {}", lesson_id))?;
+    if lesson.is_locked == Some(true) {
+        return Err(format!(
+            "Lesson is locked: {}",
+            lesson.unlock_reason.unwrap_or_default()
+        ));
+    }
+
+    let item = lesson
+        .items
+        .into_iter()
+        .find(|i| i.id == lesson_item_id)
+        .ok_or_else(|| format!("Lesson item not found: {}", lesson_item_id))?;
+    Ok((lesson.course_id, item))
+}
+
 pub fn clear_lesson_item_progress(
     conn: &Connection,
     user_id: &str,
@@ -784,63 +1544,3 @@ pub fn toggle_course_favorite(conn: &Connection, user_id: &str, course_id: &str)
         Ok(true)
     }
 }
-
-// ============================================
-// Utility: Link items by name
-// ============================================
-
-pub fn link_lesson_items_by_name(
-    conn: &Connection,
-    user_id: &str,
-    course_id: &str,
-) -> Result<(i32, Vec<String>), String> {
-    // Get all lesson items for this course that are missing (item_id is NULL)
-    let mut stmt = conn
-        .prepare(
-            "SELECT li.id, li.item_type, li.item_name
-             FROM lesson_items li
-             JOIN lessons l ON l.id = li.lesson_id
-             WHERE l.course_id = ?1 AND li.item_id IS NULL",
-        )
-        .map_err(|e| format!("Failed to prepare query: {}", e))?;
-
-    let missing_items: Vec<(String, String, String)> = stmt
-        .query_map(params![course_id], |row| {
-            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
-        })
-        .map_err(|e| format!("Failed to query missing items: {}", e))?
-        .filter_map(|r| r.ok())
-        .collect();
-
-    let mut linked = 0;
-    let mut not_found = Vec::new();
-
-    for (item_id, item_type, item_name) in missing_items {
-        let found_id: Option<String> = match item_type.as_str() {
-            "deck" => conn
-                .query_row(
-                    "SELECT id FROM decks WHERE user_id = ?1 AND name = ?2",
-                    params![user_id, item_name],
-                    |row| row.get(0),
-                )
-                .ok(),
-            "quiz" => conn
-                .query_row(
-                    "SELECT id FROM quizzes WHERE user_id = ?1 AND name = ?2",
-                    params![user_id, item_name],
-                    |row| row.get(0),
-                )
-                .ok(),
-            _ => None,
-        };
-
-        if let Some(ref_id) = found_id {
-            update_lesson_item_reference(conn, &item_id, &ref_id)?;
-            linked += 1;
-        } else {
-            not_found.push(format!("{}: {}", item_type, item_name));
-        }
-    }
-
-    Ok((linked, not_found))
-}
@@ -43,7 +43,8 @@ pub fn get_all_decks(conn: &Connection, user_id: &str) -> Result<Vec<Deck>, Stri
             "SELECT d.id, d.name, d.description, d.shuffle_cards,
                     d.created_at, d.updated_at,
                     (SELECT COUNT(*) FROM cards WHERE deck_id = d.id) as card_count,
-                    (SELECT COUNT(*) FROM deck_favorites WHERE deck_id = d.id AND user_id = ?1) as is_fav
+                    (SELECT COUNT(*) FROM deck_favorites WHERE deck_id = d.id AND user_id = ?1) as is_fav,
+                    COALESCE(d.content_id, d.id)
              FROM decks d WHERE d.user_id = ?1 ORDER BY d.updated_at DESC",
         )
         .map_err(|e| format!("Failed to prepare query: {}", e))?;
@@ -52,6 +53,7 @@ pub fn get_all_decks(conn: &Connection, user_id: &str) -> Result<Vec<Deck>, Stri
         .query_map(params![user_id], |row| {
             Ok(Deck {
                 id: row.get(0)?,
+                content_id: row.get(8)?,
                 name: row.get(1)?,
                 description: row.get(2)?,
                 shuffle_cards: row.get::<_, i32>(3)? != 0,
@@ -70,12 +72,14 @@ pub fn get_all_decks(conn: &Connection, user_id: &str) -> Result<Vec<Deck>, Stri
 
 pub fn get_deck(conn: &Connection, id: &str) -> Result<Option<Deck>, String> {
     match conn.query_row(
-        "SELECT id, name, description, shuffle_cards, created_at, updated_at
+        "SELECT id, name, description, shuffle_cards, created_at, updated_at,
+                COALESCE(content_id, id)
          FROM decks WHERE id = ?1",
         params![id],
         |row| {
             Ok(Deck {
                 id: row.get(0)?,
+                content_id: row.get(6)?,
                 name: row.get(1)?,
                 description: row.get(2)?,
                 shuffle_cards: row.get::<_, i32>(3)? != 0,
@@ -126,6 +130,33 @@ pub fn delete_deck(conn: &Connection, user_id: &str, id: &str) -> Result<(), Str
     Ok(())
 }
 
+/// Find the deck an earlier import created from the same source deck
+pub fn find_deck_by_content_id(
+    conn: &Connection,
+    user_id: &str,
+    content_id: &str,
+) -> Result<Option<Deck>, String> {
+    match conn.query_row(
+        "SELECT id FROM decks WHERE user_id = ?1 AND COALESCE(content_id, id) = ?2
+         ORDER BY updated_at DESC LIMIT 1",
+        params![user_id, content_id],
+        |row| row.get::<_, String>(0),
+    ) {
+        Ok(id) => get_deck(conn, &id),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(format!("Database error: {}", e)),
+    }
+}
+
+pub fn set_deck_content_id(conn: &Connection, id: &str, content_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE decks SET content_id = ?1 WHERE id = ?2",
+        params![content_id, id],
+    )
+    .map_err(|e| format!("Failed to set deck content ID: {}", e))?;
+    Ok(())
+}
+
 // ============================================
 // Card Operations
 // ============================================
@@ -161,7 +192,7 @@ pub fn get_cards_for_deck(conn: &Connection, deck_id: &str) -> Result<Vec<Card>,
         .prepare(
             "SELECT id, deck_id, front, front_type, front_language,
                     back, back_type, back_language, notes,
-                    created_at, updated_at
+                    created_at, updated_at, COALESCE(content_id, id), source_page_id
              FROM cards WHERE deck_id = ?1 ORDER BY created_at ASC",
         )
         .map_err(|e| format!("Failed to prepare query: {}", e))?;
@@ -170,6 +201,7 @@ pub fn get_cards_for_deck(conn: &Connection, deck_id: &str) -> Result<Vec<Card>,
         .query_map(params![deck_id], |row| {
             Ok(Card {
                 id: row.get(0)?,
+                content_id: row.get(11)?,
                 deck_id: row.get(1)?,
                 front: row.get(2)?,
                 front_type: row.get(3)?,
@@ -178,6 +210,7 @@ pub fn get_cards_for_deck(conn: &Connection, deck_id: &str) -> Result<Vec<Card>,
                 back_type: row.get(6)?,
                 back_language: row.get(7)?,
                 notes: row.get(8)?,
+                source_page_id: row.get(12)?,
                 created_at: row.get(9)?,
                 updated_at: row.get(10)?,
                 tags: vec![],
@@ -232,12 +265,13 @@ pub fn get_card(conn: &Connection, id: &str, deck_id: &str) -> Result<Card, Stri
         .query_row(
             "SELECT id, deck_id, front, front_type, front_language,
                     back, back_type, back_language, notes,
-                    created_at, updated_at
+                    created_at, updated_at, COALESCE(content_id, id), source_page_id
              FROM cards WHERE id = ?1 AND deck_id = ?2",
             params![id, deck_id],
             |row| {
                 Ok(Card {
                     id: row.get(0)?,
+                    content_id: row.get(11)?,
                     deck_id: row.get(1)?,
                     front: row.get(2)?,
                     front_type: row.get(3)?,
@@ -246,6 +280,7 @@ pub fn get_card(conn: &Connection, id: &str, deck_id: &str) -> Result<Card, Stri
                     back_type: row.get(6)?,
                     back_language: row.get(7)?,
                     notes: row.get(8)?,
+                    source_page_id: row.get(12)?,
                     created_at: row.get(9)?,
                     updated_at: row.get(10)?,
                     tags: vec![],
@@ -281,6 +316,15 @@ pub fn update_card(
     get_card(conn, id, deck_id)
 }
 
+pub fn set_card_content_id(conn: &Connection, id: &str, content_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE cards SET content_id = ?1 WHERE id = ?2",
+        params![content_id, id],
+    )
+    .map_err(|e| format!("Failed to set card content ID: {}", e))?;
+    Ok(())
+}
+
 pub fn delete_card(conn: &Connection, id: &str, deck_id: &str) -> Result<(), String> {
     conn.execute(
         "DELETE FROM cards WHERE id = ?1 AND deck_id = ?2",
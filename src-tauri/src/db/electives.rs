@@ -0,0 +1,247 @@
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection};
+use uuid::Uuid;
+
+use super::models::{ElectiveGroup, Lesson};
+
+// ============================================
+// Elective Group Operations
+// ============================================
+
+pub fn get_elective_groups(conn: &Connection, course_id: &str) -> Result<Vec<ElectiveGroup>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, course_id, name, required_count, created_at, updated_at, COALESCE(content_id, id)
+             FROM elective_groups WHERE course_id = ?1 ORDER BY created_at, name",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let groups = stmt
+        .query_map(params![course_id], group_from_row)
+        .map_err(|e| format!("Failed to query elective groups: {}", e))?;
+
+    groups
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect elective groups: {}", e))
+}
+
+/// Elective groups of all the user's courses, by course
+pub fn get_elective_groups_by_course(
+    conn: &Connection,
+    user_id: &str,
+) -> Result<HashMap<String, Vec<ElectiveGroup>>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT g.id, g.course_id, g.name, g.required_count, g.created_at, g.updated_at,
+                    COALESCE(g.content_id, g.id)
+             FROM elective_groups g INNER JOIN courses c ON c.id = g.course_id
+             WHERE c.user_id = ?1 ORDER BY g.created_at, g.name",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![user_id], group_from_row)
+        .map_err(|e| format!("Failed to query elective groups: {}", e))?;
+
+    let mut groups: HashMap<String, Vec<ElectiveGroup>> = HashMap::new();
+    for row in rows {
+        let group = row.map_err(|e| format!("Failed to read elective group: {}", e))?;
+        groups.entry(group.course_id.clone()).or_default().push(group);
+    }
+    Ok(groups)
+}
+
+fn group_from_row(row: &rusqlite::Row) -> rusqlite::Result<ElectiveGroup> {
+    Ok(ElectiveGroup {
+        id: row.get(0)?,
+        content_id: row.get(6)?,
+        course_id: row.get(1)?,
+        name: row.get(2)?,
+        required_count: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+        completed_count: None,
+        is_satisfied: None,
+    })
+}
+
+pub fn get_elective_group(conn: &Connection, id: &str) -> Result<Option<ElectiveGroup>, String> {
+    let course_id: String = match conn.query_row(
+        "SELECT course_id FROM elective_groups WHERE id = ?1",
+        params![id],
+        |row| row.get(0),
+    ) {
+        Ok(course_id) => course_id,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+        Err(e) => return Err(format!("Database error: {}", e)),
+    };
+    Ok(get_elective_groups(conn, &course_id)?.into_iter().find(|g| g.id == id))
+}
+
+pub fn create_elective_group(
+    conn: &Connection,
+    course_id: &str,
+    name: &str,
+    required_count: i32,
+) -> Result<ElectiveGroup, String> {
+    let name = validate_group(name, required_count)?;
+    let id = Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        "INSERT INTO elective_groups (id, course_id, name, required_count, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+        params![id, course_id, name, required_count, now],
+    )
+    .map_err(|e| format!("Failed to create elective group: {}", e))?;
+
+    get_elective_group(conn, &id)?.ok_or_else(|| "Failed to retrieve created elective group".to_string())
+}
+
+pub fn update_elective_group(
+    conn: &Connection,
+    id: &str,
+    name: &str,
+    required_count: i32,
+) -> Result<ElectiveGroup, String> {
+    let name = validate_group(name, required_count)?;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        "UPDATE elective_groups SET name = ?1, required_count = ?2, updated_at = ?3 WHERE id = ?4",
+        params![name, required_count, now, id],
+    )
+    .map_err(|e| format!("Failed to update elective group: {}", e))?;
+
+    get_elective_group(conn, id)?.ok_or_else(|| format!("Elective group not found: {}", id))
+}
+
+/// Delete a group; its lessons stay in the course as mandatory lessons
+pub fn delete_elective_group(conn: &Connection, id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM elective_groups WHERE id = ?1", params![id])
+        .map_err(|e| format!("Failed to delete elective group: {}", e))?;
+    Ok(())
+}
+
+pub fn set_elective_group_content_id(conn: &Connection, id: &str, content_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE elective_groups SET content_id = ?1 WHERE id = ?2",
+        params![content_id, id],
+    )
+    .map_err(|e| format!("Failed to set elective group content ID: {}", e))?;
+    Ok(())
+}
+
+fn validate_group(name: &str, required_count: i32) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Elective group name cannot be empty".to_string());
+    }
+    if required_count < 1 {
+        return Err("An elective group must require at least one lesson".to_string());
+    }
+    Ok(name)
+}
+
+/// Make a lesson mandatory, optional, or one of an elective group's choices
+pub fn set_lesson_completion_rule(
+    conn: &Connection,
+    lesson_id: &str,
+    is_optional: bool,
+    elective_group_id: Option<&str>,
+) -> Result<(), String> {
+    if is_optional && elective_group_id.is_some() {
+        return Err("A lesson cannot be both optional and elective".to_string());
+    }
+
+    let course_id: String = conn
+        .query_row(
+            "SELECT course_id FROM lessons WHERE id = ?1",
+            params![lesson_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Lesson not found: {}", e))?;
+    if let Some(group_id) = elective_group_id {
+        let group = get_elective_group(conn, group_id)?
+            .ok_or_else(|| format!("Elective group not found: {}", group_id))?;
+        if group.course_id != course_id {
+            return Err("Elective groups must belong to the lesson's course".to_string());
+        }
+    }
+
+    conn.execute(
+        "UPDATE lessons SET is_optional = ?1, elective_group_id = ?2, updated_at = ?3 WHERE id = ?4",
+        params![is_optional, elective_group_id, chrono::Utc::now().to_rfc3339(), lesson_id],
+    )
+    .map_err(|e| format!("Failed to update lesson: {}", e))?;
+    Ok(())
+}
+
+// ============================================
+// Completion Rules
+// ============================================
+
+/// Where a learner stands against a course's completion rules
+pub struct CourseCompletion {
+    /// Lessons the rules ask for
+    pub required: i32,
+    /// Completed lessons that count towards `required`
+    pub completed: i32,
+    /// The first rule not yet met, worded for the learner
+    pub unmet: Option<String>,
+}
+
+impl CourseCompletion {
+    pub fn is_complete(&self) -> bool {
+        self.required > 0 && self.unmet.is_none()
+    }
+}
+
+/// A lesson that must be completed for the course to be
+pub fn is_mandatory(lesson: &Lesson) -> bool {
+    !lesson.is_optional && lesson.elective_group_id.is_none()
+}
+
+/// Check the course against its rules: every mandatory lesson and enough of
+/// each elective group. Optional lessons never count, nor do lessons without
+/// items, which have nothing to complete; a group never asks for more
+/// lessons than it has. Fills in each group's progress.
+pub fn course_completion(lessons: &[Lesson], groups: &mut [ElectiveGroup]) -> CourseCompletion {
+    let mut completion = CourseCompletion {
+        required: 0,
+        completed: 0,
+        unmet: None,
+    };
+    let mut group_lessons: HashMap<&str, (i32, i32)> = HashMap::new();
+
+    for lesson in lessons.iter().filter(|l| !l.items.is_empty()) {
+        let is_done = lesson.is_completed == Some(true);
+        if let Some(group_id) = &lesson.elective_group_id {
+            let (total, done) = group_lessons.entry(group_id.as_str()).or_default();
+            *total += 1;
+            *done += is_done as i32;
+        } else if !lesson.is_optional {
+            completion.required += 1;
+            if is_done {
+                completion.completed += 1;
+            } else if completion.unmet.is_none() {
+                completion.unmet = Some(format!("\"{}\" is unfinished", lesson.title));
+            }
+        }
+    }
+
+    for group in groups.iter_mut() {
+        let (total, done) = group_lessons.get(group.id.as_str()).copied().unwrap_or_default();
+        let needed = group.required_count.min(total);
+        completion.required += needed;
+        completion.completed += done.min(needed);
+        if done < needed && completion.unmet.is_none() {
+            completion.unmet = Some(format!("{} more from \"{}\" needed", needed - done, group.name));
+        }
+        group.completed_count = Some(done);
+        group.is_satisfied = Some(done >= needed);
+    }
+
+    completion
+}
@@ -0,0 +1,303 @@
+use std::collections::{HashMap, HashSet};
+
+use rusqlite::{params, Connection};
+
+use super::courses::update_lesson_item_reference;
+use super::models::{
+    ItemLink, LessonItemType, LinkCandidate, LinkItemsResult, LinkMatch, UnlinkedItem,
+};
+
+/// Names less alike than this are not suggested
+const MIN_FUZZY_CONFIDENCE: f64 = 0.5;
+const MAX_CANDIDATES: usize = 5;
+
+/// A lesson item whose deck, quiz, page or notebook has not been found
+struct MissingItem {
+    id: String,
+    lesson_id: String,
+    lesson_title: String,
+    item_type: String,
+    item_name: String,
+    target_content_id: Option<String>,
+}
+
+/// The user's own content an item could link to
+struct Linkable {
+    id: String,
+    content_id: String,
+    name: String,
+    notebook_name: Option<String>,
+}
+
+// ============================================
+// Relinking
+// ============================================
+
+/// Link a course's missing items to the user's content. An item is linked when
+/// exactly one deck, quiz, page or notebook matches it by content ID, or failing
+/// that by name, exactly or ignoring case and whitespace. Anything else is
+/// returned with its best candidates, fuzzy matches included, to be confirmed.
+pub fn link_missing_lesson_items(
+    conn: &Connection,
+    user_id: &str,
+    course_id: &str,
+) -> Result<LinkItemsResult, String> {
+    let mut linkables: HashMap<String, Vec<Linkable>> = HashMap::new();
+    let mut items_linked = 0;
+    let mut unlinked_items = Vec::new();
+
+    for item in get_missing_items(conn, course_id)? {
+        if !linkables.contains_key(&item.item_type) {
+            let found = get_linkables(conn, user_id, &item.item_type)?;
+            linkables.insert(item.item_type.clone(), found);
+        }
+        let candidates = rank_candidates(&item, &linkables[&item.item_type]);
+
+        if let Some(item_id) = clear_winner(&candidates) {
+            update_lesson_item_reference(conn, &item.id, item_id)?;
+            items_linked += 1;
+            continue;
+        }
+
+        unlinked_items.push(UnlinkedItem {
+            lesson_item_id: item.id,
+            lesson_id: item.lesson_id,
+            lesson_title: item.lesson_title,
+            item_type: LessonItemType::from_str(&item.item_type),
+            item_name: item.item_name,
+            candidates: candidates.into_iter().take(MAX_CANDIDATES).collect(),
+        });
+    }
+
+    Ok(LinkItemsResult {
+        items_linked,
+        unlinked_items,
+    })
+}
+
+/// Link items to the content the user picked for them. Every link is checked
+/// before any is saved; returns how many items were linked.
+pub fn confirm_item_links(
+    conn: &Connection,
+    user_id: &str,
+    course_id: &str,
+    links: &[ItemLink],
+) -> Result<i32, String> {
+    conn.execute("BEGIN TRANSACTION", [])
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+
+    match save_item_links(conn, user_id, course_id, links) {
+        Ok(linked) => {
+            conn.execute("COMMIT", [])
+                .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            Ok(linked)
+        }
+        Err(e) => {
+            if let Err(rollback_err) = conn.execute("ROLLBACK", []) {
+                eprintln!("Warning: Failed to rollback transaction: {}", rollback_err);
+            }
+            Err(e)
+        }
+    }
+}
+
+fn save_item_links(
+    conn: &Connection,
+    user_id: &str,
+    course_id: &str,
+    links: &[ItemLink],
+) -> Result<i32, String> {
+    let mut linkables: HashMap<String, HashSet<String>> = HashMap::new();
+
+    for link in links {
+        let item_type: String = match conn.query_row(
+            "SELECT li.item_type FROM lesson_items li
+             JOIN lessons l ON l.id = li.lesson_id
+             WHERE li.id = ?1 AND l.course_id = ?2",
+            params![link.lesson_item_id, course_id],
+            |row| row.get(0),
+        ) {
+            Ok(item_type) => item_type,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                return Err(format!("Lesson item not found in course: {}", link.lesson_item_id))
+            }
+            Err(e) => return Err(format!("Database error: {}", e)),
+        };
+
+        if !linkables.contains_key(&item_type) {
+            let ids = get_linkables(conn, user_id, &item_type)?.into_iter().map(|l| l.id).collect();
+            linkables.insert(item_type.clone(), ids);
+        }
+        if !linkables[&item_type].contains(&link.item_id) {
+            return Err(format!("No {} found with ID {}", item_type, link.item_id));
+        }
+
+        update_lesson_item_reference(conn, &link.lesson_item_id, &link.item_id)?;
+    }
+
+    Ok(links.len() as i32)
+}
+
+fn get_missing_items(conn: &Connection, course_id: &str) -> Result<Vec<MissingItem>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT li.id, li.lesson_id, l.title, li.item_type, li.item_name, li.target_content_id
+             FROM lesson_items li
+             JOIN lessons l ON l.id = li.lesson_id
+             WHERE l.course_id = ?1 AND li.item_id IS NULL
+             ORDER BY l.position, li.position",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let items = stmt
+        .query_map(params![course_id], |row| {
+            Ok(MissingItem {
+                id: row.get(0)?,
+                lesson_id: row.get(1)?,
+                lesson_title: row.get(2)?,
+                item_type: row.get(3)?,
+                item_name: row.get(4)?,
+                target_content_id: row.get(5)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query missing items: {}", e))?;
+
+    items
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect missing items: {}", e))
+}
+
+fn get_linkables(conn: &Connection, user_id: &str, item_type: &str) -> Result<Vec<Linkable>, String> {
+    let sql = match item_type {
+        "deck" => "SELECT id, COALESCE(content_id, id), name, NULL FROM decks WHERE user_id = ?1",
+        "quiz" => "SELECT id, COALESCE(content_id, id), name, NULL FROM quizzes WHERE user_id = ?1",
+        "notebook" => "SELECT id, COALESCE(content_id, id), name, NULL FROM notebooks WHERE user_id = ?1",
+        "page" => {
+            "SELECT p.id, COALESCE(p.content_id, p.id), p.title, n.name
+             FROM pages p JOIN notebooks n ON n.id = p.notebook_id
+             WHERE n.user_id = ?1"
+        }
+        _ => return Ok(Vec::new()),
+    };
+
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let linkables = stmt
+        .query_map(params![user_id], |row| {
+            Ok(Linkable {
+                id: row.get(0)?,
+                content_id: row.get(1)?,
+                name: row.get(2)?,
+                notebook_name: row.get(3)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query {} names: {}", item_type, e))?;
+
+    linkables
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect {} names: {}", item_type, e))
+}
+
+// ============================================
+// Matching
+// ============================================
+
+/// Every candidate for an item, strongest match first
+fn rank_candidates(item: &MissingItem, linkables: &[Linkable]) -> Vec<LinkCandidate> {
+    let wanted = normalize_name(&item.item_name);
+
+    let mut candidates: Vec<LinkCandidate> = linkables
+        .iter()
+        .filter_map(|linkable| {
+            let (match_type, confidence) =
+                if item.target_content_id.as_deref() == Some(linkable.content_id.as_str()) {
+                    (LinkMatch::ContentId, 1.0)
+                } else if linkable.name == item.item_name {
+                    (LinkMatch::ExactName, 1.0)
+                } else {
+                    let name = normalize_name(&linkable.name);
+                    if name == wanted {
+                        (LinkMatch::NormalizedName, 0.95)
+                    } else {
+                        // Kept below a normalized match however alike the names are
+                        let similarity = name_similarity(&wanted, &name) * 0.9;
+                        if similarity < MIN_FUZZY_CONFIDENCE {
+                            return None;
+                        }
+                        (LinkMatch::Fuzzy, (similarity * 100.0).round() / 100.0)
+                    }
+                };
+
+            Some(LinkCandidate {
+                item_id: linkable.id.clone(),
+                name: linkable.name.clone(),
+                notebook_name: linkable.notebook_name.clone(),
+                match_type,
+                confidence,
+            })
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| {
+        a.match_type
+            .cmp(&b.match_type)
+            .then_with(|| b.confidence.total_cmp(&a.confidence))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    candidates
+}
+
+/// The candidate to link without asking: the only one found by content ID or
+/// name at the strongest match there is. Fuzzy matches always need confirming.
+fn clear_winner(candidates: &[LinkCandidate]) -> Option<&str> {
+    let best = candidates.first()?;
+    if best.match_type == LinkMatch::Fuzzy {
+        return None;
+    }
+    let tied = candidates.iter().filter(|c| c.match_type == best.match_type).count();
+    (tied == 1).then_some(best.item_id.as_str())
+}
+
+fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// How alike two normalized names are, from 0.0 to 1.0: the better of their
+/// edit distance and the words they share, so reordered words still match
+fn name_similarity(a: &str, b: &str) -> f64 {
+    let a_chars: Vec<char> = a.chars().collect();
+    let b_chars: Vec<char> = b.chars().collect();
+    let longest = a_chars.len().max(b_chars.len());
+    if longest == 0 {
+        return 0.0;
+    }
+    let by_edits = 1.0 - edit_distance(&a_chars, &b_chars) as f64 / longest as f64;
+
+    let a_words: HashSet<&str> = a.split(' ').collect();
+    let b_words: HashSet<&str> = b.split(' ').collect();
+    let shared = a_words.intersection(&b_words).count();
+    let by_words = 2.0 * shared as f64 / (a_words.len() + b_words.len()) as f64;
+
+    by_edits.max(by_words)
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection};
+
+use super::models::{DanglingLink, Page, PageGraph, PageGraphEdge, PageGraphNode};
+use super::notebooks::{get_page, rewrite_page_content};
+
+/// A [[Title]] or [[Title|alias]] link, with its byte range in the content
+struct WikiLink {
+    title: String,
+    alias: Option<String>,
+    start: usize,
+    end: usize,
+}
+
+// ============================================
+// Parsing
+// ============================================
+
+/// Find the links in page content the way the editor does: `[[`, a title
+/// without `]` or `|`, optionally `|` and display text without `]`, then `]]`
+fn parse_links(content: &str) -> Vec<WikiLink> {
+    let mut links = Vec::new();
+    let mut from = 0;
+
+    while let Some(offset) = content[from..].find("[[") {
+        let start = from + offset;
+        match parse_link_at(content, start) {
+            Some(link) => {
+                from = link.end;
+                if !link.title.is_empty() {
+                    links.push(link);
+                }
+            }
+            None => from = start + 1,
+        }
+    }
+
+    links
+}
+
+fn parse_link_at(content: &str, start: usize) -> Option<WikiLink> {
+    let inner = &content[start + 2..];
+    let title_len = inner.find([']', '|']).unwrap_or(inner.len());
+    if title_len == 0 {
+        return None;
+    }
+    let title = inner[..title_len].trim().to_string();
+    let rest = &inner[title_len..];
+
+    if let Some(after_pipe) = rest.strip_prefix('|') {
+        let alias_len = after_pipe.find(']').unwrap_or(after_pipe.len());
+        if alias_len > 0 && after_pipe[alias_len..].starts_with("]]") {
+            let alias = after_pipe[..alias_len].trim();
+            return Some(WikiLink {
+                title,
+                alias: (!alias.is_empty()).then(|| alias.to_string()),
+                start,
+                end: start + 2 + title_len + 1 + alias_len + 2,
+            });
+        }
+        return None;
+    }
+
+    rest.starts_with("]]").then(|| WikiLink {
+        title,
+        alias: None,
+        start,
+        end: start + 2 + title_len + 2,
+    })
+}
+
+// ============================================
+// Indexing
+// ============================================
+
+/// Replace a page's indexed links with those in its current content
+pub fn index_page_links(conn: &Connection, page_id: &str) -> Result<(), String> {
+    let page = get_page(conn, page_id)?.ok_or_else(|| format!("Page not found: {}", page_id))?;
+    let user_id = page_owner(conn, &page)?;
+
+    conn.execute("DELETE FROM page_links WHERE source_page_id = ?1", params![page_id])
+        .map_err(|e| format!("Failed to clear page links: {}", e))?;
+
+    let mut resolved: HashMap<String, Option<String>> = HashMap::new();
+    for (position, link) in parse_links(&page.content).into_iter().enumerate() {
+        let key = link.title.to_lowercase();
+        if !resolved.contains_key(&key) {
+            let target = resolve_title(conn, &user_id, &page.notebook_id, &link.title)?;
+            resolved.insert(key.clone(), target);
+        }
+
+        conn.execute(
+            "INSERT INTO page_links (source_page_id, position, target_title, alias, target_page_id)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![page_id, position as i64, link.title, link.alias, resolved[&key]],
+        )
+        .map_err(|e| format!("Failed to save page link: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Index a page that has just been created or restored, and point links
+/// that were waiting for its title at it
+pub fn link_new_page(conn: &Connection, page_id: &str) -> Result<(), String> {
+    index_page_links(conn, page_id)?;
+    resolve_dangling_links(conn, page_id)
+}
+
+/// Keep links right after a page is saved. When its title has changed, links
+/// to the page are rewritten to the new title in every page that has them.
+pub fn update_links_after_edit(conn: &Connection, previous: &Page) -> Result<(), String> {
+    let page = get_page(conn, &previous.id)?.ok_or_else(|| format!("Page not found: {}", previous.id))?;
+
+    if page.title != previous.title {
+        rewrite_links_to(conn, &page.id, &previous.title, &page.title)?;
+        index_page_links(conn, &page.id)?;
+        resolve_dangling_links(conn, &page.id)
+    } else {
+        index_page_links(conn, &page.id)
+    }
+}
+
+/// Rewrite `[[old_title]]` to `[[new_title]]` in the pages linking to a page,
+/// keeping any display text
+fn rewrite_links_to(conn: &Connection, page_id: &str, old_title: &str, new_title: &str) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare("SELECT DISTINCT source_page_id FROM page_links WHERE target_page_id = ?1")
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let sources = stmt
+        .query_map(params![page_id], |row| row.get::<_, String>(0))
+        .map_err(|e| format!("Failed to query linking pages: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect linking pages: {}", e))?;
+
+    let old_title = old_title.trim().to_lowercase();
+    for source_id in sources {
+        let Some(source) = get_page(conn, &source_id)? else {
+            continue;
+        };
+
+        let mut content = source.content.clone();
+        for link in parse_links(&source.content).iter().rev() {
+            if link.title.to_lowercase() != old_title {
+                continue;
+            }
+            let replacement = match &link.alias {
+                Some(alias) => format!("[[{}|{}]]", new_title, alias),
+                None => format!("[[{}]]", new_title),
+            };
+            content.replace_range(link.start..link.end, &replacement);
+        }
+
+        if content != source.content {
+            rewrite_page_content(conn, &source, &content)?;
+            index_page_links(conn, &source_id)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Point the user's dangling links with this page's title at it
+fn resolve_dangling_links(conn: &Connection, page_id: &str) -> Result<(), String> {
+    let page = get_page(conn, page_id)?.ok_or_else(|| format!("Page not found: {}", page_id))?;
+    let user_id = page_owner(conn, &page)?;
+
+    conn.execute(
+        "UPDATE page_links SET target_page_id = ?1
+         WHERE target_page_id IS NULL AND target_title = ?2 COLLATE NOCASE
+           AND source_page_id IN (
+               SELECT p.id FROM pages p JOIN notebooks n ON n.id = p.notebook_id WHERE n.user_id = ?3
+           )",
+        params![page.id, page.title.trim(), user_id],
+    )
+    .map_err(|e| format!("Failed to resolve page links: {}", e))?;
+
+    Ok(())
+}
+
+/// Index every page, for databases whose pages predate the link index
+pub fn index_all_page_links(conn: &Connection) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare("SELECT id FROM pages")
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let page_ids = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| format!("Failed to query pages: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect pages: {}", e))?;
+
+    for page_id in page_ids {
+        index_page_links(conn, &page_id)?;
+    }
+    Ok(())
+}
+
+/// The user's page with the title, ignoring case: an exact match first, then
+/// one in the linking page's notebook, then the most recently edited
+fn resolve_title(conn: &Connection, user_id: &str, notebook_id: &str, title: &str) -> Result<Option<String>, String> {
+    match conn.query_row(
+        "SELECT p.id FROM pages p JOIN notebooks n ON n.id = p.notebook_id
+         WHERE n.user_id = ?1 AND TRIM(p.title) = ?2 COLLATE NOCASE
+         ORDER BY TRIM(p.title) = ?2 DESC, p.notebook_id = ?3 DESC, p.updated_at DESC
+         LIMIT 1",
+        params![user_id, title, notebook_id],
+        |row| row.get(0),
+    ) {
+        Ok(id) => Ok(Some(id)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(format!("Failed to resolve link: {}", e)),
+    }
+}
+
+fn page_owner(conn: &Connection, page: &Page) -> Result<String, String> {
+    conn.query_row(
+        "SELECT user_id FROM notebooks WHERE id = ?1",
+        params![page.notebook_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Failed to get notebook owner: {}", e))
+}
+
+// ============================================
+// Dangling Links and Graph
+// ============================================
+
+/// Links across the user's pages to titles no page has
+pub fn get_dangling_links(conn: &Connection, user_id: &str) -> Result<Vec<DanglingLink>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.id, p.title, n.id, n.name, l.target_title, COUNT(*)
+             FROM page_links l
+             JOIN pages p ON p.id = l.source_page_id
+             JOIN notebooks n ON n.id = p.notebook_id
+             WHERE n.user_id = ?1 AND l.target_page_id IS NULL
+             GROUP BY p.id, l.target_title COLLATE NOCASE
+             ORDER BY n.name, p.title, l.target_title",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let links = stmt
+        .query_map(params![user_id], |row| {
+            Ok(DanglingLink {
+                source_page_id: row.get(0)?,
+                source_title: row.get(1)?,
+                notebook_id: row.get(2)?,
+                notebook_name: row.get(3)?,
+                target_title: row.get(4)?,
+                link_count: row.get(5)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query dangling links: {}", e))?;
+
+    links
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect dangling links: {}", e))
+}
+
+/// The user's pages and the links between them, optionally within one notebook.
+/// A page's links to itself are left out.
+pub fn get_page_graph(conn: &Connection, user_id: &str, notebook_id: Option<&str>) -> Result<PageGraph, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.id, p.title, n.id, n.name FROM pages p
+             JOIN notebooks n ON n.id = p.notebook_id
+             WHERE n.user_id = ?1 AND (?2 IS NULL OR n.id = ?2)
+             ORDER BY n.name, p.position",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let mut nodes = stmt
+        .query_map(params![user_id, notebook_id], |row| {
+            Ok(PageGraphNode {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                notebook_id: row.get(2)?,
+                notebook_name: row.get(3)?,
+                outgoing_count: 0,
+                incoming_count: 0,
+            })
+        })
+        .map_err(|e| format!("Failed to query pages: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect pages: {}", e))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT l.source_page_id, l.target_page_id, COUNT(*)
+             FROM page_links l
+             JOIN pages s ON s.id = l.source_page_id
+             JOIN notebooks sn ON sn.id = s.notebook_id
+             JOIN pages t ON t.id = l.target_page_id
+             WHERE sn.user_id = ?1 AND l.source_page_id != l.target_page_id
+               AND (?2 IS NULL OR (s.notebook_id = ?2 AND t.notebook_id = ?2))
+             GROUP BY l.source_page_id, l.target_page_id",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let edges = stmt
+        .query_map(params![user_id, notebook_id], |row| {
+            Ok(PageGraphEdge {
+                source_page_id: row.get(0)?,
+                target_page_id: row.get(1)?,
+                link_count: row.get(2)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query page links: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect page links: {}", e))?;
+
+    let index: HashMap<String, usize> = nodes.iter().enumerate().map(|(i, n)| (n.id.clone(), i)).collect();
+    for edge in &edges {
+        if let Some(&i) = index.get(&edge.source_page_id) {
+            nodes[i].outgoing_count += 1;
+        }
+        if let Some(&i) = index.get(&edge.target_page_id) {
+            nodes[i].incoming_count += 1;
+        }
+    }
+
+    let dangling_link_count = conn
+        .query_row(
+            "SELECT COUNT(*) FROM page_links l
+             JOIN pages p ON p.id = l.source_page_id
+             JOIN notebooks n ON n.id = p.notebook_id
+             WHERE n.user_id = ?1 AND (?2 IS NULL OR n.id = ?2) AND l.target_page_id IS NULL",
+            params![user_id, notebook_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to count dangling links: {}", e))?;
+
+    Ok(PageGraph {
+        nodes,
+        edges,
+        dangling_link_count,
+    })
+}
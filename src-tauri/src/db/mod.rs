@@ -1,12 +1,21 @@
 // Database module - organized into submodules by domain
 
+pub mod certificates;
 pub mod courses;
 pub mod decks;
+pub mod electives;
+pub mod linking;
+pub mod links;
 pub mod models;
 pub mod notebooks;
+pub mod pacing;
+pub mod page_cards;
 pub mod quizzes;
+pub mod reports;
+pub mod revisions;
 pub mod state;
 pub mod stats;
+pub mod trash;
 pub mod users;
 
 // Re-export all public types and functions for convenient access
@@ -15,26 +24,52 @@ pub use state::{init_database, DbState};
 
 // Deck operations
 pub use decks::{
-    add_tag_to_card, create_card, create_deck, create_tag, delete_card, delete_deck, delete_tag,
-    get_all_decks, get_card, get_cards_for_deck, get_deck, get_tag_by_name,
-    get_tags_for_card, get_tags_for_deck, remove_tag_from_card, toggle_deck_favorite,
-    update_card, update_deck,
+    add_tag_to_card, create_card, create_deck, create_tag, delete_card, delete_tag,
+    find_deck_by_content_id, get_all_decks, get_card, get_cards_for_deck, get_deck,
+    get_tag_by_name, get_tags_for_card, get_tags_for_deck, remove_tag_from_card,
+    set_card_content_id, set_deck_content_id, toggle_deck_favorite, update_card, update_deck,
 };
 
+// Cards from pages
+pub use page_cards::generate_cards_from_page;
+
 // Course operations
 pub use courses::{
-    create_course, delete_course, get_all_courses, get_course, get_course_with_lessons,
-    toggle_course_favorite, update_course,
+    create_course, find_course_by_content_id, get_all_courses, get_course, get_course_with_lessons,
+    raise_course_version, set_course_content_id, set_course_version, toggle_course_favorite,
+    update_course,
 };
 
 // Lesson operations
 pub use courses::{
-    add_lesson_item, clear_lesson_item_progress, create_lesson, delete_lesson, get_lesson,
-    get_lesson_items, get_lesson_progress, get_lessons, link_lesson_items_by_name,
-    record_lesson_progress, remove_lesson_item, reorder_lesson_items, reorder_lessons,
-    update_lesson, update_lesson_item_reference,
+    add_lesson_item, check_requirement, clear_lesson_item_progress, create_lesson, delete_lesson,
+    get_lesson, get_lesson_items, get_lesson_progress, get_lessons, get_open_lesson_item,
+    move_lesson_item, record_lesson_progress, remove_lesson_item, reorder_lesson_items,
+    reorder_lessons, set_course_prerequisites, set_lesson_content_id, set_lesson_item_content_id,
+    set_lesson_item_target, set_lesson_order, set_lesson_prerequisites, update_lesson,
+    update_lesson_item, update_lesson_item_reference,
+};
+
+// Relinking operations
+pub use linking::{confirm_item_links, link_missing_lesson_items};
+
+// Elective operations
+pub use electives::{
+    create_elective_group, delete_elective_group, get_elective_groups,
+    set_elective_group_content_id, set_lesson_completion_rule, update_elective_group,
+};
+
+// Certificate operations
+pub use certificates::{get_certificate, get_certificates, issue_certificate, verify_certificate};
+
+// Enrollment operations
+pub use pacing::{
+    enroll_in_course, get_enrollment, get_today_plan, set_lesson_due_day, unenroll_from_course,
 };
 
+// Report operations
+pub use reports::get_course_report;
+
 // User operations
 pub use users::{
     create_user, delete_user, get_active_user, get_all_users, get_user, login_user, logout_user,
@@ -44,22 +79,39 @@ pub use users::{
 // Quiz operations
 pub use quizzes::{
     add_tag_to_question, create_question, create_quiz, create_quiz_tag, delete_question,
-    delete_quiz, delete_quiz_tag, get_all_quizzes, get_question, get_questions_for_quiz, get_quiz,
-    get_quiz_tag_by_name, get_tags_for_question, get_tags_for_quiz,
-    remove_tag_from_question, reorder_questions, toggle_quiz_favorite,
+    delete_quiz_tag, find_quiz_by_content_id, get_all_quizzes, get_pool_rules_for_quiz,
+    get_question, get_questions_for_quiz, get_quiz, get_quiz_tag_by_name, get_tags_for_question,
+    get_tags_for_quiz, remove_tag_from_question, reorder_questions, set_pool_rules_for_quiz,
+    set_question_content_id, set_question_order, set_quiz_content_id, toggle_quiz_favorite,
     update_choices_for_question, update_question, update_quiz,
 };
 
 // Statistics operations
 pub use stats::{
-    end_study_session, get_deck_study_stats, get_quiz_attempt, get_quiz_attempts, get_quiz_stats,
-    start_quiz_attempt, start_study_session, submit_quiz_attempt,
+    answer_adaptive_question, cleanup_stale_attempts, end_study_session, get_adaptive_progress,
+    get_attempt_questions, get_deck_study_stats, get_in_progress_attempts, get_quiz_attempt,
+    get_quiz_attempts, get_quiz_item_analysis, get_quiz_stats, get_study_session,
+    mark_question_shown, resume_quiz_attempt, save_attempt_answer, start_quiz_attempt,
+    start_retry_attempt, start_study_session, submit_quiz_attempt, STALE_ATTEMPT_HOURS,
 };
 
 // Notebook operations
 pub use notebooks::{
-    create_notebook, create_page, delete_notebook, delete_page, duplicate_page, get_all_notebooks,
-    get_all_page_titles, get_backlinks, get_notebook, get_page, get_pages_for_notebook,
-    get_recent_pages, move_page, reorder_pages, search_pages, toggle_notebook_favorite,
-    toggle_page_pin, update_notebook, update_page,
+    add_page_reading_time, create_notebook, create_page, delete_page, duplicate_page,
+    find_notebook_by_content_id, get_all_notebooks, get_all_page_titles, get_backlinks,
+    get_notebook, get_page, get_page_read, get_page_tree, get_pages_for_notebook, get_recent_pages,
+    mark_page_read, move_page, reorder_pages, search_pages, set_notebook_content_id,
+    set_page_content_id, toggle_notebook_favorite, toggle_page_pin, update_notebook, update_page,
+};
+
+// Page revision operations
+pub use revisions::{diff_page_revisions, get_page_revisions, restore_page_revision};
+
+// Page link operations
+pub use links::{get_dangling_links, get_page_graph};
+
+// Trash operations
+pub use trash::{
+    delete_from_trash, empty_trash, get_trash, get_trash_retention_days, move_page_to_trash,
+    move_to_trash, purge_expired_trash, restore_from_trash, set_trash_retention_days,
 };
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+// Exportable content carries a `content_id` that stays the same across export
+// and import; it is the row id unless the content was imported.
+
 // ============================================
 // Deck Models
 // ============================================
@@ -8,6 +11,7 @@ use serde::{Deserialize, Serialize};
 #[serde(rename_all = "camelCase")]
 pub struct Deck {
     pub id: String,
+    pub content_id: String,
     pub name: String,
     pub description: Option<String>,
     pub shuffle_cards: bool,
@@ -23,6 +27,7 @@ pub struct Deck {
 #[serde(rename_all = "camelCase")]
 pub struct Card {
     pub id: String,
+    pub content_id: String,
     pub deck_id: String,
     pub front: String,
     pub front_type: String,
@@ -31,6 +36,7 @@ pub struct Card {
     pub back_type: String,
     pub back_language: Option<String>,
     pub notes: Option<String>,
+    pub source_page_id: Option<String>,  // Set when generated from a notebook page
     pub created_at: String,
     pub updated_at: String,
     #[serde(default)]
@@ -76,13 +82,20 @@ impl QuestionType {
 #[serde(rename_all = "camelCase")]
 pub struct Quiz {
     pub id: String,
+    pub content_id: String,
     pub name: String,
     pub description: Option<String>,
     pub shuffle_questions: bool,
+    pub pool_size: Option<i32>,  // NULL means every question is asked
+    #[serde(default)]
+    pub shuffle_choices: bool,
+    pub time_limit_seconds: Option<i32>,  // NULL means untimed
     pub created_at: String,
     pub updated_at: String,
     #[serde(default)]
     pub questions: Vec<Question>,
+    #[serde(default)]
+    pub pool_rules: Vec<QuizPoolRule>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub question_count: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -93,6 +106,7 @@ pub struct Quiz {
 #[serde(rename_all = "camelCase")]
 pub struct Question {
     pub id: String,
+    pub content_id: String,
     pub quiz_id: String,
     pub question_type: QuestionType,
     pub content: String,
@@ -101,6 +115,7 @@ pub struct Question {
     pub correct_answer: Option<String>,
     pub multiple_answers: bool,
     pub explanation: Option<String>,
+    pub time_limit_seconds: Option<i32>,
     pub position: i32,
     pub created_at: String,
     pub updated_at: String,
@@ -135,6 +150,18 @@ pub struct QuestionTag {
     pub name: String,
 }
 
+/// Draw `question_count` questions carrying `tag_id` into each attempt
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QuizPoolRule {
+    pub id: String,
+    pub quiz_id: String,
+    pub tag_id: String,
+    pub tag_name: String,
+    pub question_count: i32,
+    pub position: i32,
+}
+
 // ============================================
 // Statistics Models
 // ============================================
@@ -150,10 +177,86 @@ pub struct QuizAttempt {
     pub total_questions: i32,
     pub correct_answers: i32,
     pub score_percentage: i32,
+    pub deadline: Option<String>,
+    #[serde(default)]
+    pub is_late: bool,
+    pub abandoned_at: Option<String>,
+    #[serde(default)]
+    pub mode: AttemptMode,
+    #[serde(default)]
+    pub questions: Vec<AttemptQuestion>,
+    #[serde(default)]
+    pub saved_answers: Vec<SavedAnswer>,
     #[serde(default)]
     pub question_results: Vec<QuestionResult>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttemptMode {
+    #[default]
+    Standard,
+    Retry,     // Only questions missed in earlier attempts
+    Adaptive,  // Missed questions re-queued until answered correctly enough times
+}
+
+impl AttemptMode {
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "retry" => AttemptMode::Retry,
+            "adaptive" => AttemptMode::Adaptive,
+            _ => AttemptMode::Standard,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttemptMode::Standard => "standard",
+            AttemptMode::Retry => "retry",
+            AttemptMode::Adaptive => "adaptive",
+        }
+    }
+}
+
+/// Where an adaptive attempt stands after each answer
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AdaptiveProgress {
+    pub attempt_id: String,
+    pub last_answer_correct: Option<bool>,
+    pub next_question_id: Option<String>,
+    pub mastered_questions: i32,
+    pub total_questions: i32,
+    pub completed: bool,
+}
+
+/// A provisional answer saved while the attempt is still open
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedAnswer {
+    pub question_id: String,
+    pub answer: String,
+    pub answered_at: String,
+    pub time_spent_seconds: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AttemptCleanupResult {
+    pub graded_attempts: i32,
+    pub abandoned_attempts: i32,
+}
+
+/// A question drawn for an attempt, with the choice order it was shown in
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AttemptQuestion {
+    pub question_id: String,
+    pub position: i32,
+    #[serde(default)]
+    pub choice_order: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct QuestionResult {
@@ -162,6 +265,7 @@ pub struct QuestionResult {
     pub question_id: String,
     pub user_answer: Option<String>,
     pub is_correct: bool,
+    pub time_spent_seconds: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -187,6 +291,40 @@ pub struct QuizStats {
     pub recent_scores: Vec<i32>,
 }
 
+/// Per-question difficulty and discrimination across completed attempts
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QuizItemAnalysis {
+    pub quiz_id: String,
+    pub attempts_analyzed: i32,
+    pub questions: Vec<QuestionItemStats>,
+    pub most_missed: Vec<String>,  // Question IDs, most often answered wrong first
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionItemStats {
+    pub question_id: String,
+    pub content: String,
+    pub times_answered: i32,
+    pub times_correct: i32,
+    pub percent_correct: f64,
+    pub average_time_seconds: Option<f64>,
+    pub discrimination_index: Option<f64>,  // Point-biserial; None when undefined
+    #[serde(default)]
+    pub choices: Vec<ChoiceSelectionStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChoiceSelectionStats {
+    pub choice_id: String,
+    pub text: String,
+    pub is_correct: bool,
+    pub times_selected: i32,
+    pub selection_rate: f64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct DeckStudyStats {
@@ -220,19 +358,31 @@ pub struct LocalUser {
 #[serde(rename_all = "camelCase")]
 pub struct Course {
     pub id: String,
+    pub content_id: String,
     pub user_id: String,
     pub name: String,
     pub description: Option<String>,
+    pub version: i32,
     pub created_at: String,
     pub updated_at: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub lesson_count: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub completed_lesson_count: Option<i32>,
+    pub completed_lesson_count: Option<i32>,  // Counted by the completion rules
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required_lesson_count: Option<i32>,  // Mandatory lessons plus what each elective group needs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_completed: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_favorite: Option<bool>,
     #[serde(default)]
     pub lessons: Vec<Lesson>,
+    #[serde(default)]
+    pub elective_groups: Vec<ElectiveGroup>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enrollment: Option<CourseEnrollment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overdue_lesson_count: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -257,10 +407,17 @@ pub struct UpdateCourseRequest {
 #[serde(rename_all = "camelCase")]
 pub struct Lesson {
     pub id: String,
+    pub content_id: String,
     pub course_id: String,
     pub title: String,
     pub description: Option<String>,
     pub position: i32,
+    pub requires_previous: bool,  // Locked until the lesson before it is complete
+    #[serde(default)]
+    pub prerequisite_ids: Vec<String>,  // Lessons that must be complete first
+    pub due_day: Option<i32>,  // Calendar pacing: days after the start date
+    pub is_optional: bool,  // Not needed to complete the course
+    pub elective_group_id: Option<String>,
     pub created_at: String,
     pub updated_at: String,
     #[serde(default)]
@@ -269,6 +426,32 @@ pub struct Lesson {
     pub is_completed: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub completed_item_count: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_locked: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unlock_reason: Option<String>,  // What to complete first, when locked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<String>,  // YYYY-MM-DD, from the learner's enrollment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_overdue: Option<bool>,
+}
+
+/// Lessons of which any `required_count` complete the group
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ElectiveGroup {
+    pub id: String,
+    pub content_id: String,
+    pub course_id: String,
+    pub name: String,
+    pub required_count: i32,
+    pub created_at: String,
+    pub updated_at: String,
+    // Populated by query
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_count: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_satisfied: Option<bool>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -276,12 +459,16 @@ pub struct Lesson {
 pub enum LessonItemType {
     Deck,
     Quiz,
+    Page,
+    Notebook,
 }
 
 impl LessonItemType {
     pub fn from_str(s: &str) -> Self {
         match s {
             "quiz" => LessonItemType::Quiz,
+            "page" => LessonItemType::Page,
+            "notebook" => LessonItemType::Notebook,
             _ => LessonItemType::Deck,
         }
     }
@@ -294,6 +481,7 @@ pub enum RequirementType {
     Review,     // For decks: just open/view the deck
     Complete,   // For quizzes: just finish the quiz
     MinScore,   // For quizzes: score at least X%
+    Read,       // For pages and notebooks: read every page
 }
 
 impl RequirementType {
@@ -303,6 +491,7 @@ impl RequirementType {
             "review" => Some(RequirementType::Review),
             "complete" => Some(RequirementType::Complete),
             "min_score" => Some(RequirementType::MinScore),
+            "read" => Some(RequirementType::Read),
             _ => None,
         }
     }
@@ -312,12 +501,14 @@ impl RequirementType {
 #[serde(rename_all = "camelCase")]
 pub struct LessonItem {
     pub id: String,
+    pub content_id: String,
     pub lesson_id: String,
     pub item_type: LessonItemType,
     pub item_id: Option<String>,  // NULL if item not yet imported
     pub item_name: String,
+    pub target_content_id: Option<String>,  // Content ID of what the item links to, kept for relinking
     pub requirement_type: Option<RequirementType>,
-    pub requirement_value: Option<i32>,  // For min_score: the percentage
+    pub requirement_value: Option<i32>,  // min_score: percentage; study: cards in one session; review: times opened; read: seconds per page
     pub position: i32,
     pub created_at: String,
     // Populated by query
@@ -345,6 +536,16 @@ pub struct LessonProgress {
     pub updated_at: String,
 }
 
+/// Whether a lesson item's requirement is met by the learner's quiz attempts
+/// and study sessions
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RequirementCheck {
+    pub is_met: bool,
+    pub score_percentage: Option<i32>,  // For quizzes: the score that was judged
+    pub reason: Option<String>,  // Why not, when unmet
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateLessonRequest {
@@ -377,12 +578,299 @@ pub struct ReorderLessonsRequest {
     pub lesson_ids: Vec<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetLessonPrerequisitesRequest {
+    pub requires_previous: bool,
+    pub prerequisite_ids: Vec<String>,
+}
+
+/// One lesson's unlock rules, for setting several at once
+#[derive(Debug, Clone)]
+pub struct LessonPrerequisites {
+    pub lesson_id: String,
+    pub requires_previous: bool,
+    pub prerequisite_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetLessonCompletionRuleRequest {
+    pub is_optional: bool,
+    pub elective_group_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElectiveGroupRequest {
+    pub name: String,
+    pub required_count: i32,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ReorderLessonItemsRequest {
     pub item_ids: Vec<String>,
 }
 
+// ============================================
+// Relinking Models
+// ============================================
+
+/// How a candidate for a missing item was found, strongest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkMatch {
+    ContentId,
+    ExactName,
+    NormalizedName,  // Same name ignoring case and whitespace
+    Fuzzy,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkCandidate {
+    pub item_id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notebook_name: Option<String>,  // Pages only
+    pub match_type: LinkMatch,
+    pub confidence: f64,  // 0.0 to 1.0
+}
+
+/// A missing item that could not be linked on its own, with what it might be
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlinkedItem {
+    pub lesson_item_id: String,
+    pub lesson_id: String,
+    pub lesson_title: String,
+    pub item_type: LessonItemType,
+    pub item_name: String,
+    pub candidates: Vec<LinkCandidate>,  // Best first
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkItemsResult {
+    pub items_linked: i32,
+    pub unlinked_items: Vec<UnlinkedItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemLink {
+    pub lesson_item_id: String,
+    pub item_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmItemLinksRequest {
+    pub links: Vec<ItemLink>,
+}
+
+// ============================================
+// Course Report Models
+// ============================================
+
+/// A learner's progress through a course, from their lesson progress and the
+/// study sessions, quiz attempts and page reads linked to it
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseReport {
+    pub course_id: String,
+    pub completion_percentage: f64,  // Counted by the completion rules
+    pub completed_lesson_count: i32,
+    pub required_lesson_count: i32,
+    pub timeline: Vec<CompletionPoint>,
+    pub lessons: Vec<LessonReport>,
+    pub average_lesson_time_seconds: Option<f64>,  // Over completed lessons with tracked time
+    pub drop_off: Option<DropOffLesson>,
+    pub estimate: Option<CompletionEstimate>,
+}
+
+/// Completion as it stood at the end of a day a lesson was completed
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionPoint {
+    pub date: String,  // YYYY-MM-DD
+    pub completed_lesson_count: i32,
+    pub completion_percentage: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LessonReport {
+    pub lesson_id: String,
+    pub title: String,
+    pub is_completed: bool,
+    pub completed_at: Option<String>,
+    pub time_spent_seconds: i32,
+    pub items: Vec<LessonItemReport>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LessonItemReport {
+    pub lesson_item_id: String,
+    pub item_type: LessonItemType,
+    pub item_name: String,
+    pub is_completed: bool,
+    pub time_spent_seconds: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scores: Option<ScoreDistribution>,  // Quiz items only
+}
+
+/// Scores of every completed standard attempt at a quiz
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreDistribution {
+    pub attempts: i32,
+    pub average_score: Option<f64>,
+    pub best_score: Option<i32>,
+    pub buckets: Vec<ScoreBucket>,  // 0-9, 10-19, ... 90-100
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreBucket {
+    pub min_score: i32,
+    pub max_score: i32,
+    pub count: i32,
+}
+
+/// The first unfinished lesson the course still needs, where progress stopped
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DropOffLesson {
+    pub lesson_id: String,
+    pub title: String,
+    pub last_activity_at: Option<String>,
+    pub days_inactive: Option<i64>,
+}
+
+/// How long the rest of the course should take at the learner's pace so far
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionEstimate {
+    pub remaining_lessons: i32,
+    pub days_per_lesson: f64,
+    pub estimated_days_remaining: i64,
+    pub estimated_completion_date: String,  // YYYY-MM-DD
+    pub estimated_time_remaining_seconds: Option<i64>,  // From the average lesson time
+}
+
+// ============================================
+// Pacing Models
+// ============================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PacingMode {
+    SelfPaced,  // No due dates
+    Calendar,   // Each lesson is due a set number of days after the start
+    Weekly,     // A set number of lessons is due each week
+}
+
+impl PacingMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "self_paced" => Some(PacingMode::SelfPaced),
+            "calendar" => Some(PacingMode::Calendar),
+            "weekly" => Some(PacingMode::Weekly),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PacingMode::SelfPaced => "self_paced",
+            PacingMode::Calendar => "calendar",
+            PacingMode::Weekly => "weekly",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseEnrollment {
+    pub user_id: String,
+    pub course_id: String,
+    pub start_date: String,  // YYYY-MM-DD
+    pub pacing_mode: PacingMode,
+    pub lessons_per_week: Option<i32>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnrollCourseRequest {
+    pub start_date: Option<String>,  // Defaults to today
+    pub pacing_mode: String,
+    pub lessons_per_week: Option<i32>,
+}
+
+/// What to work on today across every course the user is enrolled in
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodayPlan {
+    pub date: String,
+    pub lessons: Vec<TodayLesson>,
+    pub decks: Vec<TodayDeck>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodayLesson {
+    pub course_id: String,
+    pub course_name: String,
+    pub lesson_id: String,
+    pub lesson_title: String,
+    pub due_date: Option<String>,  // None for the next lesson of a self-paced course
+    pub is_overdue: bool,
+    pub remaining_items: i32,
+}
+
+/// A deck to study for one of today's lessons
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodayDeck {
+    pub deck_id: String,
+    pub deck_name: String,
+    pub card_count: i32,
+    pub course_name: String,
+    pub lesson_id: String,
+    pub lesson_item_id: String,
+    pub due_date: Option<String>,
+}
+
+// ============================================
+// Certificate Models
+// ============================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Certificate {
+    pub id: String,
+    pub user_id: String,
+    pub course_id: Option<String>,  // NULL once the course is deleted
+    pub learner_name: String,
+    pub course_name: String,
+    pub completed_at: String,
+    pub scores: Vec<CertificateScore>,
+    pub verification_hash: String,
+    pub issued_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateScore {
+    pub quiz_name: String,
+    pub score_percentage: Option<i32>,
+}
+
 // ============================================
 // Request Types
 // ============================================
@@ -433,6 +921,9 @@ pub struct CreateQuizRequest {
     pub name: String,
     pub description: Option<String>,
     pub shuffle_questions: Option<bool>,
+    pub pool_size: Option<i32>,
+    pub shuffle_choices: Option<bool>,
+    pub time_limit_seconds: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -441,6 +932,16 @@ pub struct UpdateQuizRequest {
     pub name: String,
     pub description: Option<String>,
     pub shuffle_questions: Option<bool>,
+    pub pool_size: Option<i32>,
+    pub shuffle_choices: Option<bool>,
+    pub time_limit_seconds: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QuizPoolRuleRequest {
+    pub tag_id: String,
+    pub question_count: i32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -453,6 +954,7 @@ pub struct CreateQuestionRequest {
     pub correct_answer: Option<String>,
     pub multiple_answers: Option<bool>,
     pub explanation: Option<String>,
+    pub time_limit_seconds: Option<i32>,
     pub choices: Option<Vec<CreateChoiceRequest>>,
 }
 
@@ -466,6 +968,7 @@ pub struct UpdateQuestionRequest {
     pub correct_answer: Option<String>,
     pub multiple_answers: Option<bool>,
     pub explanation: Option<String>,
+    pub time_limit_seconds: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -479,6 +982,17 @@ pub struct CreateChoiceRequest {
 #[serde(rename_all = "camelCase")]
 pub struct SubmitQuizRequest {
     pub answers: Vec<QuestionAnswer>,
+    #[serde(default)]
+    pub lesson_item_id: Option<String>,  // Set when the quiz is taken in a course
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryQuizRequest {
+    pub quiz_id: String,
+    pub attempt_id: Option<String>,      // Retry the misses of this attempt...
+    pub recent_attempts: Option<i32>,    // ...or of this many latest completed attempts
+    pub adaptive: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -486,6 +1000,7 @@ pub struct SubmitQuizRequest {
 pub struct QuestionAnswer {
     pub question_id: String,
     pub answer: String,
+    pub time_spent_seconds: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -504,6 +1019,7 @@ pub struct CreateUserRequest {
 #[serde(rename_all = "camelCase")]
 pub struct Notebook {
     pub id: String,
+    pub content_id: String,
     pub user_id: String,
     pub name: String,
     pub description: Option<String>,
@@ -521,15 +1037,77 @@ pub struct Notebook {
 #[serde(rename_all = "camelCase")]
 pub struct Page {
     pub id: String,
+    pub content_id: String,
     pub notebook_id: String,
     pub title: String,
     pub content: String,
-    pub position: i32,
+    pub position: i32,  // Among the pages sharing its parent
     pub is_pinned: bool,
+    pub parent_page_id: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// A page with its sub-pages, in the order they are listed
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PageTreeNode {
+    #[serde(flatten)]
+    pub page: Page,
+    pub children: Vec<PageTreeNode>,
+}
+
+/// How much of a page has been read, for lesson items with a read requirement
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PageRead {
+    pub page_id: String,
+    pub seconds_read: i32,
+    pub marked_read_at: Option<String>,
+    pub updated_at: String,
+}
+
+/// A page as it was before an edit replaced it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PageRevision {
+    pub id: String,
+    pub page_id: String,
+    pub title: String,
+    pub content: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffLineKind {
+    Unchanged,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub text: String,
+    pub old_line: Option<i32>,  // 1-based; None for added lines
+    pub new_line: Option<i32>,  // 1-based; None for removed lines
+}
+
+/// Line changes between two versions of a page
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageDiff {
+    pub page_id: String,
+    pub from_revision_id: Option<String>,  // None means the page as it is now
+    pub to_revision_id: Option<String>,
+    pub title_changed: bool,
+    pub lines_added: i32,
+    pub lines_removed: i32,
+    pub lines: Vec<DiffLine>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateNotebookRequest {
@@ -554,6 +1132,7 @@ pub struct CreatePageRequest {
     pub title: String,
     pub content: Option<String>,
     pub position: Option<i32>,
+    pub parent_page_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -568,6 +1147,7 @@ pub struct UpdatePageRequest {
 #[serde(rename_all = "camelCase")]
 pub struct ReorderPagesRequest {
     pub page_ids: Vec<String>,
+    pub parent_page_id: Option<String>,  // Whose sub-pages these are; None for top-level pages
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -579,3 +1159,129 @@ pub struct PageSearchResult {
     pub notebook_name: String,
     pub updated_at: String,
 }
+
+/// A [[Title]] link that no page's title matches
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DanglingLink {
+    pub source_page_id: String,
+    pub source_title: String,
+    pub notebook_id: String,
+    pub notebook_name: String,
+    pub target_title: String,
+    pub link_count: i32,  // Times the page links to the title
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PageGraphNode {
+    pub id: String,
+    pub title: String,
+    pub notebook_id: String,
+    pub notebook_name: String,
+    pub outgoing_count: i32,
+    pub incoming_count: i32,
+}
+
+/// Links from one page to another, counted once per pair
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PageGraphEdge {
+    pub source_page_id: String,
+    pub target_page_id: String,
+    pub link_count: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PageGraph {
+    pub nodes: Vec<PageGraphNode>,
+    pub edges: Vec<PageGraphEdge>,
+    pub dangling_link_count: i32,
+}
+
+// ============================================
+// Trash Models
+// ============================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashItemType {
+    Deck,
+    Card,
+    Quiz,
+    Course,
+    Notebook,
+    Page,
+}
+
+impl TrashItemType {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "deck" => Some(TrashItemType::Deck),
+            "card" => Some(TrashItemType::Card),
+            "quiz" => Some(TrashItemType::Quiz),
+            "course" => Some(TrashItemType::Course),
+            "notebook" => Some(TrashItemType::Notebook),
+            "page" => Some(TrashItemType::Page),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrashItemType::Deck => "deck",
+            TrashItemType::Card => "card",
+            TrashItemType::Quiz => "quiz",
+            TrashItemType::Course => "course",
+            TrashItemType::Notebook => "notebook",
+            TrashItemType::Page => "page",
+        }
+    }
+}
+
+/// Deleted content waiting to be restored or purged
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashItem {
+    pub id: String,
+    pub item_type: TrashItemType,
+    pub item_id: String,
+    pub name: String,
+    pub parent_id: Option<String>,  // Deck of a card, notebook of a page
+    pub deleted_at: String,
+    pub purge_at: String,  // When it is deleted for good
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashRestoreResult {
+    pub item_type: TrashItemType,
+    pub item_id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_name: Option<String>,  // Set when the name was taken and the item renamed
+}
+
+// ============================================
+// Page Card Models
+// ============================================
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateCardsRequest {
+    pub deck_id: String,
+    pub remove_missing: Option<bool>,  // Delete cards whose note is no longer on the page
+}
+
+/// What generating cards from a page changed in the deck
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateCardsResult {
+    pub deck_id: String,
+    pub cards_created: i32,
+    pub cards_updated: i32,
+    pub cards_unchanged: i32,
+    pub cards_removed: i32,
+    pub missing_card_ids: Vec<String>,  // Kept cards whose note is no longer on the page
+}
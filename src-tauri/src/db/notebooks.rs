@@ -1,7 +1,11 @@
+use std::collections::{HashMap, HashSet};
+
 use rusqlite::{params, Connection};
 use uuid::Uuid;
 
-use super::models::{CreateNotebookRequest, CreatePageRequest, Notebook, Page, PageSearchResult, UpdateNotebookRequest, UpdatePageRequest};
+use super::links::{index_page_links, link_new_page, update_links_after_edit};
+use super::models::{CreateNotebookRequest, CreatePageRequest, Notebook, Page, PageRead, PageSearchResult, PageTreeNode, UpdateNotebookRequest, UpdatePageRequest};
+use super::revisions::save_revision_before_edit;
 
 // ============================================
 // Notebook Operations
@@ -78,7 +82,8 @@ pub fn get_all_notebooks(conn: &Connection, user_id: &str) -> Result<Vec<Noteboo
             "SELECT n.id, n.user_id, n.name, n.description, n.icon, n.color,
                     n.created_at, n.updated_at,
                     (SELECT COUNT(*) FROM pages WHERE notebook_id = n.id) as page_count,
-                    (SELECT COUNT(*) FROM notebook_favorites WHERE notebook_id = n.id AND user_id = ?1) as is_fav
+                    (SELECT COUNT(*) FROM notebook_favorites WHERE notebook_id = n.id AND user_id = ?1) as is_fav,
+                    COALESCE(n.content_id, n.id)
              FROM notebooks n WHERE n.user_id = ?1 ORDER BY n.updated_at DESC",
         )
         .map_err(|e| format!("Failed to prepare query: {}", e))?;
@@ -87,6 +92,7 @@ pub fn get_all_notebooks(conn: &Connection, user_id: &str) -> Result<Vec<Noteboo
         .query_map(params![user_id], |row| {
             Ok(Notebook {
                 id: row.get(0)?,
+                content_id: row.get(10)?,
                 user_id: row.get(1)?,
                 name: row.get(2)?,
                 description: row.get(3)?,
@@ -107,12 +113,14 @@ pub fn get_all_notebooks(conn: &Connection, user_id: &str) -> Result<Vec<Noteboo
 
 pub fn get_notebook(conn: &Connection, id: &str) -> Result<Option<Notebook>, String> {
     match conn.query_row(
-        "SELECT id, user_id, name, description, icon, color, created_at, updated_at
+        "SELECT id, user_id, name, description, icon, color, created_at, updated_at,
+                COALESCE(content_id, id)
          FROM notebooks WHERE id = ?1",
         params![id],
         |row| {
             Ok(Notebook {
                 id: row.get(0)?,
+                content_id: row.get(8)?,
                 user_id: row.get(1)?,
                 name: row.get(2)?,
                 description: row.get(3)?,
@@ -164,6 +172,33 @@ pub fn delete_notebook(conn: &Connection, user_id: &str, id: &str) -> Result<(),
     Ok(())
 }
 
+/// Find the notebook an earlier import created from the same source notebook
+pub fn find_notebook_by_content_id(
+    conn: &Connection,
+    user_id: &str,
+    content_id: &str,
+) -> Result<Option<Notebook>, String> {
+    match conn.query_row(
+        "SELECT id FROM notebooks WHERE user_id = ?1 AND COALESCE(content_id, id) = ?2
+         ORDER BY updated_at DESC LIMIT 1",
+        params![user_id, content_id],
+        |row| row.get::<_, String>(0),
+    ) {
+        Ok(id) => get_notebook(conn, &id),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(format!("Database error: {}", e)),
+    }
+}
+
+pub fn set_notebook_content_id(conn: &Connection, id: &str, content_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE notebooks SET content_id = ?1 WHERE id = ?2",
+        params![content_id, id],
+    )
+    .map_err(|e| format!("Failed to set notebook content ID: {}", e))?;
+    Ok(())
+}
+
 // ============================================
 // Page Operations
 // ============================================
@@ -182,29 +217,25 @@ pub fn create_page(
         return Err("Page title cannot exceed 255 characters".to_string());
     }
 
+    let parent_page_id = request.parent_page_id.as_deref();
+    if let Some(parent_id) = parent_page_id {
+        check_page_parent(conn, notebook_id, None, parent_id)?;
+    }
+
     let id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now().to_rfc3339();
     let content = request.content.as_deref().unwrap_or("");
 
-    // Get the next position if not specified
+    // Get the next position among its siblings if not specified
     let position = match request.position {
         Some(pos) => pos,
-        None => {
-            let max_pos: i32 = conn
-                .query_row(
-                    "SELECT COALESCE(MAX(position), -1) FROM pages WHERE notebook_id = ?1",
-                    params![notebook_id],
-                    |row| row.get(0),
-                )
-                .unwrap_or(-1);
-            max_pos + 1
-        }
+        None => next_page_position(conn, notebook_id, parent_page_id),
     };
 
     conn.execute(
-        "INSERT INTO pages (id, notebook_id, title, content, position, is_pinned, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, 0, ?6, ?7)",
-        params![id, notebook_id, title, content, position, now, now],
+        "INSERT INTO pages (id, notebook_id, title, content, position, is_pinned, parent_page_id, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, 0, ?6, ?7, ?8)",
+        params![id, notebook_id, title, content, position, parent_page_id, now, now],
     )
     .map_err(|e| format!("Failed to create page: {}", e))?;
 
@@ -216,6 +247,8 @@ pub fn create_page(
         eprintln!("Warning: Failed to update notebook timestamp: {}", e);
     }
 
+    link_new_page(conn, &id)?;
+
     get_page(conn, &id)?
         .ok_or_else(|| "Failed to retrieve created page".to_string())
 }
@@ -223,7 +256,8 @@ pub fn create_page(
 pub fn get_pages_for_notebook(conn: &Connection, notebook_id: &str) -> Result<Vec<Page>, String> {
     let mut stmt = conn
         .prepare(
-            "SELECT id, notebook_id, title, content, position, is_pinned, created_at, updated_at
+            "SELECT id, notebook_id, title, content, position, is_pinned, created_at, updated_at,
+                    COALESCE(content_id, id), parent_page_id
              FROM pages WHERE notebook_id = ?1
              ORDER BY is_pinned DESC, position ASC",
         )
@@ -233,11 +267,13 @@ pub fn get_pages_for_notebook(conn: &Connection, notebook_id: &str) -> Result<Ve
         .query_map(params![notebook_id], |row| {
             Ok(Page {
                 id: row.get(0)?,
+                content_id: row.get(8)?,
                 notebook_id: row.get(1)?,
                 title: row.get(2)?,
                 content: row.get(3)?,
                 position: row.get(4)?,
                 is_pinned: row.get::<_, i32>(5)? != 0,
+                parent_page_id: row.get(9)?,
                 created_at: row.get(6)?,
                 updated_at: row.get(7)?,
             })
@@ -249,19 +285,53 @@ pub fn get_pages_for_notebook(conn: &Connection, notebook_id: &str) -> Result<Ve
         .map_err(|e| format!("Failed to collect pages: {}", e))
 }
 
+/// A notebook's pages nested under their parents, each level pinned first
+/// and then by position
+pub fn get_page_tree(conn: &Connection, notebook_id: &str) -> Result<Vec<PageTreeNode>, String> {
+    let pages = get_pages_for_notebook(conn, notebook_id)?;
+    let ids: HashSet<String> = pages.iter().map(|p| p.id.clone()).collect();
+
+    let mut children: HashMap<Option<String>, Vec<Page>> = HashMap::new();
+    for page in pages {
+        // A page whose parent is not in the notebook is listed at the top
+        let parent = page.parent_page_id.clone().filter(|id| ids.contains(id));
+        children.entry(parent).or_default().push(page);
+    }
+
+    fn build(parent: Option<String>, children: &mut HashMap<Option<String>, Vec<Page>>) -> Vec<PageTreeNode> {
+        children
+            .remove(&parent)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|page| {
+                let nested = build(Some(page.id.clone()), children);
+                PageTreeNode {
+                    page,
+                    children: nested,
+                }
+            })
+            .collect()
+    }
+
+    Ok(build(None, &mut children))
+}
+
 pub fn get_page(conn: &Connection, id: &str) -> Result<Option<Page>, String> {
     match conn.query_row(
-        "SELECT id, notebook_id, title, content, position, is_pinned, created_at, updated_at
+        "SELECT id, notebook_id, title, content, position, is_pinned, created_at, updated_at,
+                COALESCE(content_id, id), parent_page_id
          FROM pages WHERE id = ?1",
         params![id],
         |row| {
             Ok(Page {
                 id: row.get(0)?,
+                content_id: row.get(8)?,
                 notebook_id: row.get(1)?,
                 title: row.get(2)?,
                 content: row.get(3)?,
                 position: row.get(4)?,
                 is_pinned: row.get::<_, i32>(5)? != 0,
+                parent_page_id: row.get(9)?,
                 created_at: row.get(6)?,
                 updated_at: row.get(7)?,
             })
@@ -279,29 +349,88 @@ pub fn update_page(
     request: &UpdatePageRequest,
 ) -> Result<Page, String> {
     let now = chrono::Utc::now().to_rfc3339();
+    let current = get_page(conn, id)?.ok_or_else(|| format!("Page not found: {}", id))?;
+
+    // Imports save pages inside their own transaction; only open one when nobody else has
+    let owns_transaction = conn.is_autocommit();
+    if owns_transaction {
+        conn.execute("BEGIN TRANSACTION", [])
+            .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    }
+
+    let result = save_page_edit(conn, &current, request, &now);
+
+    if owns_transaction {
+        match result {
+            Ok(()) => {
+                conn.execute("COMMIT", [])
+                    .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            }
+            Err(e) => {
+                if let Err(rollback_err) = conn.execute("ROLLBACK", []) {
+                    eprintln!("Warning: Failed to rollback transaction: {}", rollback_err);
+                }
+                return Err(e);
+            }
+        }
+    } else {
+        result?;
+    }
+
+    // Update parent notebook's updated_at
+    if let Err(e) = conn.execute(
+        "UPDATE notebooks SET updated_at = ?1 WHERE id = ?2",
+        params![now, current.notebook_id],
+    ) {
+        eprintln!("Warning: Failed to update notebook timestamp: {}", e);
+    }
+
+    get_page(conn, id)?
+        .ok_or_else(|| format!("Page not found after update: {}", id))
+}
+
+/// The writes of `update_page`, so a page is never saved with stale links
+fn save_page_edit(
+    conn: &Connection,
+    current: &Page,
+    request: &UpdatePageRequest,
+    now: &str,
+) -> Result<(), String> {
     let is_pinned = request.is_pinned.unwrap_or(false);
+    save_revision_before_edit(conn, current, &request.title, &request.content)?;
 
     conn.execute(
         "UPDATE pages SET title = ?1, content = ?2, is_pinned = ?3, updated_at = ?4
          WHERE id = ?5",
-        params![request.title, request.content, is_pinned as i32, now, id],
+        params![request.title, request.content, is_pinned as i32, now, current.id],
     )
     .map_err(|e| format!("Failed to update page: {}", e))?;
 
-    // Update parent notebook's updated_at
-    if let Ok(Some(page)) = get_page(conn, id) {
-        if let Err(e) = conn.execute(
-            "UPDATE notebooks SET updated_at = ?1 WHERE id = ?2",
-            params![now, page.notebook_id],
-        ) {
-            eprintln!("Warning: Failed to update notebook timestamp: {}", e);
-        }
-    }
+    update_links_after_edit(conn, current)
+}
 
-    get_page(conn, id)?
-        .ok_or_else(|| format!("Page not found after update: {}", id))
+/// Change a page's content on the user's behalf, such as when a linked page
+/// is renamed. The page is saved to its history first, like an edit.
+pub fn rewrite_page_content(conn: &Connection, page: &Page, content: &str) -> Result<(), String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    save_revision_before_edit(conn, page, &page.title, content)?;
+
+    conn.execute(
+        "UPDATE pages SET content = ?1, updated_at = ?2 WHERE id = ?3",
+        params![content, now, page.id],
+    )
+    .map_err(|e| format!("Failed to update page content: {}", e))?;
+
+    if let Err(e) = conn.execute(
+        "UPDATE notebooks SET updated_at = ?1 WHERE id = ?2",
+        params![now, page.notebook_id],
+    ) {
+        eprintln!("Warning: Failed to update notebook timestamp: {}", e);
+    }
+    Ok(())
 }
 
+/// Delete a page along with its sub-pages
 pub fn delete_page(conn: &Connection, id: &str) -> Result<(), String> {
     // Get notebook_id before deleting
     let notebook_id: Option<String> = conn
@@ -329,7 +458,13 @@ pub fn delete_page(conn: &Connection, id: &str) -> Result<(), String> {
     Ok(())
 }
 
-pub fn reorder_pages(conn: &Connection, notebook_id: &str, page_ids: &[String]) -> Result<(), String> {
+/// Set the order of the pages under one parent, or of the top-level pages
+pub fn reorder_pages(
+    conn: &Connection,
+    notebook_id: &str,
+    parent_page_id: Option<&str>,
+    page_ids: &[String],
+) -> Result<(), String> {
     let now = chrono::Utc::now().to_rfc3339();
 
     // Begin transaction for atomic reordering
@@ -339,8 +474,9 @@ pub fn reorder_pages(conn: &Connection, notebook_id: &str, page_ids: &[String])
     let result = (|| -> Result<(), String> {
         for (index, page_id) in page_ids.iter().enumerate() {
             conn.execute(
-                "UPDATE pages SET position = ?1, updated_at = ?2 WHERE id = ?3 AND notebook_id = ?4",
-                params![index as i32, now, page_id, notebook_id],
+                "UPDATE pages SET position = ?1, updated_at = ?2
+                 WHERE id = ?3 AND notebook_id = ?4 AND parent_page_id IS ?5",
+                params![index as i32, now, page_id, notebook_id, parent_page_id],
             )
             .map_err(|e| format!("Failed to reorder page {}: {}", page_id, e))?;
         }
@@ -526,6 +662,36 @@ pub fn get_recent_pages(
 // Page Organization Operations
 // ============================================
 
+/// Move a page's sub-pages up to its own parent, in its place, so that
+/// deleting the page keeps them
+pub fn promote_child_pages(conn: &Connection, page_id: &str) -> Result<(), String> {
+    let page = get_page(conn, page_id)?.ok_or_else(|| format!("Page not found: {}", page_id))?;
+    let children: Vec<Page> = get_pages_for_notebook(conn, &page.notebook_id)?
+        .into_iter()
+        .filter(|p| p.parent_page_id.as_deref() == Some(page_id))
+        .collect();
+    if children.is_empty() {
+        return Ok(());
+    }
+
+    conn.execute(
+        "UPDATE pages SET position = position + ?1
+         WHERE notebook_id = ?2 AND parent_page_id IS ?3 AND position > ?4",
+        params![children.len() as i32, page.notebook_id, page.parent_page_id, page.position],
+    )
+    .map_err(|e| format!("Failed to make room for sub-pages: {}", e))?;
+
+    for (index, child) in children.iter().enumerate() {
+        conn.execute(
+            "UPDATE pages SET parent_page_id = ?1, position = ?2 WHERE id = ?3",
+            params![page.parent_page_id, page.position + 1 + index as i32, child.id],
+        )
+        .map_err(|e| format!("Failed to move sub-page: {}", e))?;
+    }
+
+    Ok(())
+}
+
 /// Duplicate a page within the same notebook
 pub fn duplicate_page(conn: &Connection, page_id: &str) -> Result<Page, String> {
     // Get the original page
@@ -535,23 +701,17 @@ pub fn duplicate_page(conn: &Connection, page_id: &str) -> Result<Page, String>
     let id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now().to_rfc3339();
 
-    // Get the next position in the notebook
-    let max_pos: i32 = conn
-        .query_row(
-            "SELECT COALESCE(MAX(position), -1) FROM pages WHERE notebook_id = ?1",
-            params![original.notebook_id],
-            |row| row.get(0),
-        )
-        .unwrap_or(-1);
-    let position = max_pos + 1;
+    // The copy goes after its siblings; sub-pages are not copied
+    let parent_page_id = original.parent_page_id.as_deref();
+    let position = next_page_position(conn, &original.notebook_id, parent_page_id);
 
     // Create the duplicate with " (copy)" suffix
     let title = format!("{} (copy)", original.title);
 
     conn.execute(
-        "INSERT INTO pages (id, notebook_id, title, content, position, is_pinned, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, 0, ?6, ?7)",
-        params![id, original.notebook_id, title, original.content, position, now, now],
+        "INSERT INTO pages (id, notebook_id, title, content, position, is_pinned, parent_page_id, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, 0, ?6, ?7, ?8)",
+        params![id, original.notebook_id, title, original.content, position, parent_page_id, now, now],
     )
     .map_err(|e| format!("Failed to duplicate page: {}", e))?;
 
@@ -563,15 +723,19 @@ pub fn duplicate_page(conn: &Connection, page_id: &str) -> Result<Page, String>
         eprintln!("Warning: Failed to update notebook timestamp: {}", e);
     }
 
+    link_new_page(conn, &id)?;
+
     get_page(conn, &id)?
         .ok_or_else(|| "Failed to retrieve duplicated page".to_string())
 }
 
-/// Move a page to a different notebook
+/// Move a page, with its sub-pages, under another parent page or to the top
+/// level of a notebook, which may be a different one
 pub fn move_page(
     conn: &Connection,
     page_id: &str,
     target_notebook_id: &str,
+    parent_page_id: Option<&str>,
 ) -> Result<Page, String> {
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -580,23 +744,35 @@ pub fn move_page(
         .ok_or_else(|| format!("Page not found: {}", page_id))?;
 
     let source_notebook_id = page.notebook_id.clone();
+    if let Some(parent_id) = parent_page_id {
+        check_page_parent(conn, target_notebook_id, Some(page_id), parent_id)?;
+    }
 
-    // Get the next position in the target notebook
-    let max_pos: i32 = conn
-        .query_row(
-            "SELECT COALESCE(MAX(position), -1) FROM pages WHERE notebook_id = ?1",
-            params![target_notebook_id],
-            |row| row.get(0),
-        )
-        .unwrap_or(-1);
-    let position = max_pos + 1;
+    // Imports move pages inside their own transaction; only open one when nobody else has
+    let owns_transaction = conn.is_autocommit();
+    if owns_transaction {
+        conn.execute("BEGIN TRANSACTION", [])
+            .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    }
 
-    // Move the page
-    conn.execute(
-        "UPDATE pages SET notebook_id = ?1, position = ?2, updated_at = ?3 WHERE id = ?4",
-        params![target_notebook_id, position, now, page_id],
-    )
-    .map_err(|e| format!("Failed to move page: {}", e))?;
+    let result = move_page_subtree(conn, &page, target_notebook_id, parent_page_id, &now);
+
+    if owns_transaction {
+        match result {
+            Ok(()) => {
+                conn.execute("COMMIT", [])
+                    .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            }
+            Err(e) => {
+                if let Err(rollback_err) = conn.execute("ROLLBACK", []) {
+                    eprintln!("Warning: Failed to rollback transaction: {}", rollback_err);
+                }
+                return Err(e);
+            }
+        }
+    } else {
+        result?;
+    }
 
     // Update both notebooks' updated_at
     if let Err(e) = conn.execute(
@@ -616,42 +792,189 @@ pub fn move_page(
         .ok_or_else(|| "Failed to retrieve moved page".to_string())
 }
 
+/// The writes of `move_page`, which must all happen or none
+fn move_page_subtree(
+    conn: &Connection,
+    page: &Page,
+    target_notebook_id: &str,
+    parent_page_id: Option<&str>,
+    now: &str,
+) -> Result<(), String> {
+    let page_id = page.id.as_str();
+
+    // Get the next position under the new parent
+    let position = next_page_position(conn, target_notebook_id, parent_page_id);
+
+    // Move the page
+    conn.execute(
+        "UPDATE pages SET notebook_id = ?1, parent_page_id = ?2, position = ?3, updated_at = ?4 WHERE id = ?5",
+        params![target_notebook_id, parent_page_id, position, now, page_id],
+    )
+    .map_err(|e| format!("Failed to move page: {}", e))?;
+
+    // Sub-pages follow it into the notebook, and links from all of them
+    // resolve to pages in their own notebook first
+    let subtree = get_page_subtree_ids(conn, page_id)?;
+    for id in &subtree {
+        conn.execute(
+            "UPDATE pages SET notebook_id = ?1 WHERE id = ?2",
+            params![target_notebook_id, id],
+        )
+        .map_err(|e| format!("Failed to move sub-page: {}", e))?;
+    }
+    for id in &subtree {
+        index_page_links(conn, id)?;
+    }
+    Ok(())
+}
+
+/// A page and all the pages nested under it, parents first
+pub fn get_page_subtree_ids(conn: &Connection, page_id: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare(
+            "WITH RECURSIVE subtree(id, depth) AS (
+                 SELECT ?1, 0
+                 UNION ALL
+                 SELECT p.id, s.depth + 1 FROM pages p JOIN subtree s ON p.parent_page_id = s.id
+             )
+             SELECT id FROM subtree ORDER BY depth",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let ids = stmt
+        .query_map(params![page_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to query sub-pages: {}", e))?;
+
+    ids.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect sub-pages: {}", e))
+}
+
+/// Check that a page can go under `parent_page_id`: the parent must be in the
+/// notebook and must not be the page itself or one of its sub-pages
+fn check_page_parent(
+    conn: &Connection,
+    notebook_id: &str,
+    page_id: Option<&str>,
+    parent_page_id: &str,
+) -> Result<(), String> {
+    let parent = get_page(conn, parent_page_id)?
+        .ok_or_else(|| format!("Parent page not found: {}", parent_page_id))?;
+    if parent.notebook_id != notebook_id {
+        return Err("A page's parent must be in the same notebook".to_string());
+    }
+    if let Some(page_id) = page_id {
+        if get_page_subtree_ids(conn, page_id)?.iter().any(|id| id == parent_page_id) {
+            return Err("A page cannot be moved under itself or one of its sub-pages".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// The position after the last page under a parent, or at the top level
+fn next_page_position(conn: &Connection, notebook_id: &str, parent_page_id: Option<&str>) -> i32 {
+    let max_pos: i32 = conn
+        .query_row(
+            "SELECT COALESCE(MAX(position), -1) FROM pages WHERE notebook_id = ?1 AND parent_page_id IS ?2",
+            params![notebook_id, parent_page_id],
+            |row| row.get(0),
+        )
+        .unwrap_or(-1);
+    max_pos + 1
+}
+
+pub fn set_page_content_id(conn: &Connection, id: &str, content_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE pages SET content_id = ?1 WHERE id = ?2",
+        params![content_id, id],
+    )
+    .map_err(|e| format!("Failed to set page content ID: {}", e))?;
+    Ok(())
+}
+
+// ============================================
+// Reading Progress
+// ============================================
+
+pub fn get_page_read(conn: &Connection, page_id: &str) -> Result<Option<PageRead>, String> {
+    match conn.query_row(
+        "SELECT page_id, seconds_read, marked_read_at, updated_at FROM page_reads WHERE page_id = ?1",
+        params![page_id],
+        |row| {
+            Ok(PageRead {
+                page_id: row.get(0)?,
+                seconds_read: row.get(1)?,
+                marked_read_at: row.get(2)?,
+                updated_at: row.get(3)?,
+            })
+        },
+    ) {
+        Ok(read) => Ok(Some(read)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(format!("Failed to get page read: {}", e)),
+    }
+}
+
+/// Add time spent on a page to its running total
+pub fn add_page_reading_time(conn: &Connection, page_id: &str, seconds: i32) -> Result<PageRead, String> {
+    if seconds < 0 {
+        return Err("Reading time cannot be negative".to_string());
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO page_reads (page_id, seconds_read, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(page_id) DO UPDATE SET
+             seconds_read = seconds_read + excluded.seconds_read,
+             updated_at = excluded.updated_at",
+        params![page_id, seconds, now],
+    )
+    .map_err(|e| format!("Failed to record reading time: {}", e))?;
+
+    get_page_read(conn, page_id)?.ok_or_else(|| "Failed to retrieve page read".to_string())
+}
+
+/// Mark a page as read, or clear the mark; time already spent is kept
+pub fn mark_page_read(conn: &Connection, page_id: &str, is_read: bool) -> Result<PageRead, String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let marked_at = is_read.then(|| now.clone());
+    conn.execute(
+        "INSERT INTO page_reads (page_id, marked_read_at, updated_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(page_id) DO UPDATE SET
+             marked_read_at = excluded.marked_read_at,
+             updated_at = excluded.updated_at",
+        params![page_id, marked_at, now],
+    )
+    .map_err(|e| format!("Failed to mark page as read: {}", e))?;
+
+    get_page_read(conn, page_id)?.ok_or_else(|| "Failed to retrieve page read".to_string())
+}
+
 // ============================================
 // Backlinks Operations
 // ============================================
 
-/// Get all pages that link to a given page using [[Page Title]] syntax
-/// Searches for [[exact_title]] pattern in page content
+/// Get all pages that link to a given page using [[Page Title]] syntax,
+/// from the link index kept as pages are saved
 pub fn get_backlinks(
     conn: &Connection,
     page_id: &str,
     user_id: &str,
 ) -> Result<Vec<PageSearchResult>, String> {
-    // First get the target page's title
-    let page = get_page(conn, page_id)?
-        .ok_or_else(|| format!("Page not found: {}", page_id))?;
-
-    // Search for [[Page Title]] or [[Page Title|...]] in content
-    // We need to match both [[Title]] and [[Title|Display Text]]
     let mut stmt = conn
         .prepare(
-            "SELECT p.id, p.notebook_id, p.title, n.name as notebook_name, p.updated_at
-             FROM pages p
+            "SELECT DISTINCT p.id, p.notebook_id, p.title, n.name as notebook_name, p.updated_at
+             FROM page_links l
+             INNER JOIN pages p ON l.source_page_id = p.id
              INNER JOIN notebooks n ON p.notebook_id = n.id
              WHERE n.user_id = ?1
+               AND l.target_page_id = ?2
                AND p.id != ?2
-               AND (p.content LIKE ?3 OR p.content LIKE ?4)
              ORDER BY p.updated_at DESC",
         )
         .map_err(|e| format!("Failed to prepare backlinks query: {}", e))?;
 
-    // Pattern for [[Title]] anywhere in content
-    let pattern1 = format!("%[[{}]]%", page.title);
-    // Pattern for [[Title|...]] (with display text)
-    let pattern2 = format!("%[[{}|%", page.title);
-
     let results = stmt
-        .query_map(params![user_id, page_id, pattern1, pattern2], |row| {
+        .query_map(params![user_id, page_id], |row| {
             Ok(PageSearchResult {
                 id: row.get(0)?,
                 notebook_id: row.get(1)?,
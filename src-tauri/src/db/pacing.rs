@@ -0,0 +1,255 @@
+use std::collections::HashSet;
+
+use chrono::{Duration, Local, NaiveDate};
+use rusqlite::{params, Connection};
+
+use super::courses::{get_all_courses, get_lessons};
+use super::electives::is_mandatory;
+use super::models::{
+    CourseEnrollment, Lesson, LessonItemType, PacingMode, TodayDeck, TodayLesson, TodayPlan,
+};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// ============================================
+// Enrollment Operations
+// ============================================
+
+/// Enroll the user in a course, or change the pace of an existing enrollment.
+/// The start date defaults to today.
+pub fn enroll_in_course(
+    conn: &Connection,
+    user_id: &str,
+    course_id: &str,
+    start_date: Option<&str>,
+    pacing_mode: &str,
+    lessons_per_week: Option<i32>,
+) -> Result<CourseEnrollment, String> {
+    let mode = PacingMode::parse(pacing_mode)
+        .ok_or_else(|| format!("Invalid pacing mode: {}", pacing_mode))?;
+    let lessons_per_week = match mode {
+        PacingMode::Weekly => match lessons_per_week {
+            Some(n) if n >= 1 => Some(n),
+            _ => return Err("Weekly pacing needs at least one lesson per week".to_string()),
+        },
+        _ => None,
+    };
+    let start_date = match start_date {
+        Some(date) => parse_date(date)?,
+        None => today(),
+    };
+
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO course_enrollments (user_id, course_id, start_date, pacing_mode, lessons_per_week,
+                                         created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
+         ON CONFLICT(user_id, course_id) DO UPDATE SET
+             start_date = excluded.start_date,
+             pacing_mode = excluded.pacing_mode,
+             lessons_per_week = excluded.lessons_per_week,
+             updated_at = excluded.updated_at",
+        params![
+            user_id,
+            course_id,
+            start_date.format(DATE_FORMAT).to_string(),
+            mode.as_str(),
+            lessons_per_week,
+            now,
+        ],
+    )
+    .map_err(|e| format!("Failed to enroll in course: {}", e))?;
+
+    get_enrollment(conn, user_id, course_id)?
+        .ok_or_else(|| "Failed to retrieve enrollment".to_string())
+}
+
+pub fn get_enrollment(
+    conn: &Connection,
+    user_id: &str,
+    course_id: &str,
+) -> Result<Option<CourseEnrollment>, String> {
+    match conn.query_row(
+        "SELECT user_id, course_id, start_date, pacing_mode, lessons_per_week, created_at, updated_at
+         FROM course_enrollments WHERE user_id = ?1 AND course_id = ?2",
+        params![user_id, course_id],
+        |row| {
+            let mode: String = row.get(3)?;
+            Ok(CourseEnrollment {
+                user_id: row.get(0)?,
+                course_id: row.get(1)?,
+                start_date: row.get(2)?,
+                pacing_mode: PacingMode::parse(&mode).unwrap_or(PacingMode::SelfPaced),
+                lessons_per_week: row.get(4)?,
+                created_at: row.get(5)?,
+                updated_at: row.get(6)?,
+            })
+        },
+    ) {
+        Ok(enrollment) => Ok(Some(enrollment)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(format!("Failed to get enrollment: {}", e)),
+    }
+}
+
+pub fn unenroll_from_course(conn: &Connection, user_id: &str, course_id: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM course_enrollments WHERE user_id = ?1 AND course_id = ?2",
+        params![user_id, course_id],
+    )
+    .map_err(|e| format!("Failed to unenroll from course: {}", e))?;
+    Ok(())
+}
+
+/// Set how many days after the start date a lesson is due under calendar pacing
+pub fn set_lesson_due_day(conn: &Connection, lesson_id: &str, due_day: Option<i32>) -> Result<(), String> {
+    if due_day.is_some_and(|d| d < 0) {
+        return Err("Due day cannot be negative".to_string());
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let updated = conn
+        .execute(
+            "UPDATE lessons SET due_day = ?1, updated_at = ?2 WHERE id = ?3",
+            params![due_day, now, lesson_id],
+        )
+        .map_err(|e| format!("Failed to set due day: {}", e))?;
+    if updated == 0 {
+        return Err(format!("Lesson not found: {}", lesson_id));
+    }
+    Ok(())
+}
+
+// ============================================
+// Due Dates
+// ============================================
+
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date.trim(), DATE_FORMAT)
+        .map_err(|_| format!("Invalid date (expected YYYY-MM-DD): {}", date))
+}
+
+/// Fill in each lesson's due date and overdue flag from the enrollment.
+/// Weekly pacing counts only mandatory lessons with something to complete,
+/// and only mandatory lessons can be overdue.
+pub fn apply_pacing(lessons: &mut [Lesson], enrollment: &CourseEnrollment, today: NaiveDate) {
+    let Ok(start) = parse_date(&enrollment.start_date) else {
+        return;
+    };
+
+    let mut paced = 0;
+    for lesson in lessons.iter_mut() {
+        let due = match enrollment.pacing_mode {
+            PacingMode::SelfPaced => None,
+            PacingMode::Calendar => lesson.due_day.map(|d| start + Duration::days(d as i64)),
+            PacingMode::Weekly if lesson.items.is_empty() || !is_mandatory(lesson) => None,
+            PacingMode::Weekly => {
+                let per_week = enrollment.lessons_per_week.unwrap_or(1).max(1);
+                let week = paced / per_week + 1;
+                paced += 1;
+                Some(start + Duration::days(week as i64 * 7 - 1))
+            }
+        };
+
+        lesson.due_date = due.map(|d| d.format(DATE_FORMAT).to_string());
+        lesson.is_overdue = Some(
+            due.is_some_and(|d| d < today) && lesson.is_completed != Some(true) && is_mandatory(lesson),
+        );
+    }
+}
+
+// ============================================
+// Today Plan
+// ============================================
+
+/// Everything due today or earlier across the user's enrolled courses, plus
+/// the next mandatory lesson of each self-paced course. Overdue work comes first.
+pub fn get_today_plan(conn: &Connection, user_id: &str) -> Result<TodayPlan, String> {
+    let today = today();
+    let today_str = today.format(DATE_FORMAT).to_string();
+    let mut plan_lessons = Vec::new();
+    let mut plan_decks = Vec::new();
+
+    for course in get_all_courses(conn, user_id)? {
+        let Some(enrollment) = get_enrollment(conn, user_id, &course.id)? else {
+            continue;
+        };
+
+        let open = get_lessons(conn, user_id, &course.id)?.into_iter().filter(|l| {
+            !l.items.is_empty() && l.is_completed != Some(true) && l.is_locked != Some(true)
+        });
+        let due: Vec<Lesson> = match enrollment.pacing_mode {
+            PacingMode::SelfPaced => open.filter(is_mandatory).take(1).collect(),
+            _ => open
+                .filter(|l| l.due_date.as_deref().is_some_and(|d| d <= today_str.as_str()))
+                .collect(),
+        };
+
+        for lesson in due {
+            for item in &lesson.items {
+                if item.item_type != LessonItemType::Deck || item.is_completed == Some(true) {
+                    continue;
+                }
+                let Some(deck_id) = &item.item_id else {
+                    continue;
+                };
+                plan_decks.push(TodayDeck {
+                    deck_id: deck_id.clone(),
+                    deck_name: item.item_name.clone(),
+                    card_count: count_cards(conn, deck_id)?,
+                    course_name: course.name.clone(),
+                    lesson_id: lesson.id.clone(),
+                    lesson_item_id: item.id.clone(),
+                    due_date: lesson.due_date.clone(),
+                });
+            }
+
+            let remaining = lesson.items.iter().filter(|i| i.is_completed != Some(true)).count() as i32;
+            plan_lessons.push(TodayLesson {
+                course_id: course.id.clone(),
+                course_name: course.name.clone(),
+                lesson_id: lesson.id,
+                lesson_title: lesson.title,
+                due_date: lesson.due_date,
+                is_overdue: lesson.is_overdue == Some(true),
+                remaining_items: remaining,
+            });
+        }
+    }
+
+    // Overdue first, then earliest due; undated self-paced lessons last
+    plan_lessons.sort_by(|a, b| {
+        b.is_overdue
+            .cmp(&a.is_overdue)
+            .then_with(|| a.due_date.is_none().cmp(&b.due_date.is_none()))
+            .then_with(|| a.due_date.cmp(&b.due_date))
+    });
+    plan_decks.sort_by(|a, b| {
+        a.due_date
+            .is_none()
+            .cmp(&b.due_date.is_none())
+            .then_with(|| a.due_date.cmp(&b.due_date))
+    });
+    // A deck shared by several due lessons only needs studying once
+    let mut seen = HashSet::new();
+    plan_decks.retain(|d| seen.insert(d.deck_id.clone()));
+
+    Ok(TodayPlan {
+        date: today_str,
+        lessons: plan_lessons,
+        decks: plan_decks,
+    })
+}
+
+fn count_cards(conn: &Connection, deck_id: &str) -> Result<i32, String> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM cards WHERE deck_id = ?1",
+        params![deck_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Failed to count cards: {}", e))
+}
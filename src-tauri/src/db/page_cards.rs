@@ -0,0 +1,487 @@
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection};
+
+use super::decks::{create_card, get_card, update_card};
+use super::models::{
+    Card, CreateCardRequest, GenerateCardsResult, Page, TrashItemType, UpdateCardRequest,
+};
+use super::notebooks::get_page;
+use super::trash::move_to_trash;
+
+/// A card found in a page's notes. The anchor is where the note sits: its
+/// section, its place among the section's notes and, for clozes, its group.
+/// Rewording a note keeps its anchor, so the card made from it is updated.
+struct NoteCard {
+    anchor: String,
+    front: String,
+    back: String,
+}
+
+/// A stretch of a cloze line: text shown on both sides, or an answer hidden
+/// on the front
+enum ClozePart {
+    Text(String),
+    Answer {
+        group: Option<u32>,
+        answer: String,
+        hint: Option<String>,
+    },
+}
+
+// ============================================
+// Generating Cards
+// ============================================
+
+/// Make cards in a deck from the notes on a page: cloze lines (`==answer==`
+/// or `{{c1::answer}}`), `Term :: Definition` lines and `Q:` / `A:` blocks.
+/// Cards made from the page before are updated rather than added again; ones
+/// whose note is gone are moved to the trash with `remove_missing`, or reported.
+pub fn generate_cards_from_page(
+    conn: &Connection,
+    user_id: &str,
+    page_id: &str,
+    deck_id: &str,
+    remove_missing: bool,
+) -> Result<GenerateCardsResult, String> {
+    let page = get_page(conn, page_id)?.ok_or_else(|| format!("Page not found: {}", page_id))?;
+    let owns_page: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM notebooks WHERE id = ?1 AND user_id = ?2)",
+            params![page.notebook_id, user_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to check page: {}", e))?;
+    let owns_deck: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM decks WHERE id = ?1 AND user_id = ?2)",
+            params![deck_id, user_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to check deck: {}", e))?;
+    if !owns_page {
+        return Err("Page not found or access denied".to_string());
+    }
+    if !owns_deck {
+        return Err("Deck not found or access denied".to_string());
+    }
+
+    conn.execute("BEGIN TRANSACTION", [])
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+
+    match save_page_cards(conn, user_id, &page, deck_id, remove_missing) {
+        Ok(result) => {
+            conn.execute("COMMIT", [])
+                .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            Ok(result)
+        }
+        Err(e) => {
+            if let Err(rollback_err) = conn.execute("ROLLBACK", []) {
+                eprintln!("Warning: Failed to rollback transaction: {}", rollback_err);
+            }
+            Err(e)
+        }
+    }
+}
+
+fn save_page_cards(
+    conn: &Connection,
+    user_id: &str,
+    page: &Page,
+    deck_id: &str,
+    remove_missing: bool,
+) -> Result<GenerateCardsResult, String> {
+    let mut result = GenerateCardsResult {
+        deck_id: deck_id.to_string(),
+        cards_created: 0,
+        cards_updated: 0,
+        cards_unchanged: 0,
+        cards_removed: 0,
+        missing_card_ids: Vec::new(),
+    };
+
+    let notes = extract_cards(&page.content);
+    let mut existing = get_generated_cards(conn, deck_id, &page.id)?;
+    let mut matched: Vec<Option<Card>> = Vec::with_capacity(notes.len());
+
+    // A note whose text is unchanged keeps its card even when notes added
+    // above it have moved its anchor; the rest are matched by anchor
+    for note in &notes {
+        let same_text = existing
+            .iter()
+            .position(|(_, card)| card.front == note.front && card.back == note.back);
+        matched.push(same_text.map(|index| existing.remove(index).1));
+    }
+    for (note, card) in notes.iter().zip(matched.iter_mut()) {
+        if card.is_none() {
+            if let Some(index) = existing.iter().position(|(anchor, _)| *anchor == note.anchor) {
+                *card = Some(existing.remove(index).1);
+            }
+        }
+    }
+
+    for (note, card) in notes.into_iter().zip(matched) {
+        let card_id = match card {
+            Some(card) if card.front == note.front && card.back == note.back => {
+                result.cards_unchanged += 1;
+                card.id
+            }
+            Some(card) => {
+                // The page is the source: its text replaces the card's, while
+                // content types and notes set on the card are kept
+                let request = UpdateCardRequest {
+                    front: note.front,
+                    front_type: Some(card.front_type),
+                    front_language: card.front_language,
+                    back: note.back,
+                    back_type: Some(card.back_type),
+                    back_language: card.back_language,
+                    notes: card.notes,
+                };
+                update_card(conn, &card.id, deck_id, &request)?;
+                result.cards_updated += 1;
+                card.id
+            }
+            None => {
+                let request = CreateCardRequest {
+                    front: note.front,
+                    front_type: None,
+                    front_language: None,
+                    back: note.back,
+                    back_type: None,
+                    back_language: None,
+                    notes: None,
+                };
+                result.cards_created += 1;
+                create_card(conn, deck_id, &request)?.id
+            }
+        };
+
+        conn.execute(
+            "UPDATE cards SET source_page_id = ?1, source_anchor = ?2 WHERE id = ?3",
+            params![page.id, note.anchor, card_id],
+        )
+        .map_err(|e| format!("Failed to link card to page: {}", e))?;
+    }
+
+    for (_, card) in existing {
+        if remove_missing {
+            move_to_trash(conn, user_id, TrashItemType::Card, &card.id)?;
+            result.cards_removed += 1;
+        } else {
+            result.missing_card_ids.push(card.id);
+        }
+    }
+
+    Ok(result)
+}
+
+/// Cards already made from the page in the deck, with their anchors
+fn get_generated_cards(conn: &Connection, deck_id: &str, page_id: &str) -> Result<Vec<(String, Card)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT source_anchor, id FROM cards
+             WHERE deck_id = ?1 AND source_page_id = ?2 AND source_anchor IS NOT NULL
+             ORDER BY created_at ASC",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![deck_id, page_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| format!("Failed to query generated cards: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect generated cards: {}", e))?;
+
+    rows.into_iter()
+        .map(|(anchor, id)| Ok((anchor, get_card(conn, &id, deck_id)?)))
+        .collect()
+}
+
+// ============================================
+// Finding Notes
+// ============================================
+
+/// Every card the page's notes make, in page order. Code blocks are skipped.
+fn extract_cards(content: &str) -> Vec<NoteCard> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut cards = Vec::new();
+    let mut in_code_block = false;
+    let mut section = String::new();
+    let mut sections_seen: HashMap<String, usize> = HashMap::new();
+    let mut note_index = 0;
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i].trim();
+        i += 1;
+        if line.starts_with("```") || line.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        // Notes are numbered within the section under each heading; a heading
+        // used twice on the page names two sections
+        if let Some(heading) = heading_text(line) {
+            let key = normalize(heading);
+            let count = sections_seen.entry(key.clone()).or_insert(0);
+            *count += 1;
+            section = if *count > 1 { format!("{}#{}", key, count) } else { key };
+            note_index = 0;
+        }
+
+        let text = strip_markers(line);
+        let found = if let Some(question) = strip_label(text, 'Q') {
+            match question_card(&lines, i, question) {
+                Some((card, next)) => {
+                    i = next;
+                    vec![card]
+                }
+                None => vec![],
+            }
+        } else if let Some(card) = term_card(text) {
+            vec![card]
+        } else {
+            cloze_cards(text)
+        };
+
+        if !found.is_empty() {
+            note_index += 1;
+        }
+        for mut card in found {
+            card.anchor = format!("{}/{}:{}", section, note_index, card.anchor);
+            cards.push(card);
+        }
+    }
+
+    cards
+}
+
+/// The text of a `#` heading line
+fn heading_text(line: &str) -> Option<&str> {
+    let hashes = line.len() - line.trim_start_matches('#').len();
+    if (1..=6).contains(&hashes) && line[hashes..].starts_with(' ') {
+        Some(line[hashes..].trim())
+    } else {
+        None
+    }
+}
+
+/// A `Q:` block: the question, which may run over several lines, then an
+/// `A:` line and the answer up to a blank line, heading or the next question.
+/// Returns the card and the line after it.
+fn question_card(lines: &[&str], from: usize, first_line: &str) -> Option<(NoteCard, usize)> {
+    let mut question = vec![first_line];
+    let mut i = from;
+
+    let first_answer_line = loop {
+        let text = strip_markers(lines.get(i)?.trim());
+        if text.is_empty() || strip_label(text, 'Q').is_some() {
+            return None;
+        }
+        i += 1;
+        match strip_label(text, 'A') {
+            Some(answer) => break answer,
+            None => question.push(text),
+        }
+    };
+
+    let mut answer = vec![first_answer_line];
+    while let Some(line) = lines.get(i).map(|l| l.trim()) {
+        let text = strip_markers(line);
+        if text.is_empty() || line.starts_with('#') || line.starts_with("```") || strip_label(text, 'Q').is_some() {
+            break;
+        }
+        answer.push(text);
+        i += 1;
+    }
+
+    let join = |parts: Vec<&str>| parts.into_iter().filter(|p| !p.is_empty()).collect::<Vec<_>>().join("\n");
+    let (front, back) = (join(question), join(answer));
+    if front.is_empty() || back.is_empty() {
+        return None;
+    }
+
+    Some((
+        NoteCard {
+            anchor: "qa".to_string(),
+            front,
+            back,
+        },
+        i,
+    ))
+}
+
+/// A `Term :: Definition` line. The `::` needs space on both sides, so code
+/// like `std::fs` in a sentence is left alone.
+fn term_card(text: &str) -> Option<NoteCard> {
+    if text.contains("{{") {
+        return None;
+    }
+    let (term, definition) = text.split_once("::")?;
+    if !term.ends_with(char::is_whitespace) || !definition.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let (term, definition) = (term.trim(), definition.trim());
+    if term.is_empty() || definition.is_empty() {
+        return None;
+    }
+
+    Some(NoteCard {
+        anchor: "term".to_string(),
+        front: term.to_string(),
+        back: definition.to_string(),
+    })
+}
+
+/// Cards for a line with clozes: one per numbered group, with the rest of the
+/// line's answers shown, and one hiding all its `==highlights==` together
+fn cloze_cards(text: &str) -> Vec<NoteCard> {
+    let parts = parse_cloze(text);
+    let mut groups: Vec<Option<u32>> = Vec::new();
+    for part in &parts {
+        if let ClozePart::Answer { group, .. } = part {
+            if !groups.contains(group) {
+                groups.push(*group);
+            }
+        }
+    }
+
+    let back = render_cloze(&parts, None);
+    groups
+        .into_iter()
+        .map(|group| NoteCard {
+            anchor: match group {
+                Some(n) => format!("cloze:c{}", n),
+                None => "cloze".to_string(),
+            },
+            front: render_cloze(&parts, Some(group)),
+            back: back.clone(),
+        })
+        .collect()
+}
+
+fn parse_cloze(text: &str) -> Vec<ClozePart> {
+    let mut parts = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some((answer, after)) = numbered_cloze(rest).or_else(|| highlight_cloze(rest)) {
+            if !plain.is_empty() {
+                parts.push(ClozePart::Text(std::mem::take(&mut plain)));
+            }
+            parts.push(answer);
+            rest = after;
+        } else {
+            plain.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    if !plain.is_empty() {
+        parts.push(ClozePart::Text(plain));
+    }
+
+    parts
+}
+
+/// `{{c1::answer}}` or `{{c1::answer::hint}}` at the start of `s`
+fn numbered_cloze(s: &str) -> Option<(ClozePart, &str)> {
+    let after_c = s.strip_prefix("{{c")?;
+    let digits = after_c.len() - after_c.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let group = after_c[..digits].parse().ok()?;
+    let inner_and_rest = after_c[digits..].strip_prefix("::")?;
+    let end = inner_and_rest.find("}}")?;
+
+    let inner = &inner_and_rest[..end];
+    let (answer, hint) = match inner.split_once("::") {
+        Some((answer, hint)) => (answer.trim(), Some(hint.trim().to_string()).filter(|h| !h.is_empty())),
+        None => (inner.trim(), None),
+    };
+    if answer.is_empty() {
+        return None;
+    }
+
+    Some((
+        ClozePart::Answer {
+            group: Some(group),
+            answer: answer.to_string(),
+            hint,
+        },
+        &inner_and_rest[end + 2..],
+    ))
+}
+
+/// `==answer==` at the start of `s`, not padded with spaces inside, so
+/// `a == b` is not taken for one
+fn highlight_cloze(s: &str) -> Option<(ClozePart, &str)> {
+    let inner_and_rest = s.strip_prefix("==")?;
+    let end = inner_and_rest.find("==")?;
+    let answer = &inner_and_rest[..end];
+    if answer.is_empty() || answer.starts_with(char::is_whitespace) || answer.ends_with(char::is_whitespace) {
+        return None;
+    }
+
+    Some((
+        ClozePart::Answer {
+            group: None,
+            answer: answer.to_string(),
+            hint: None,
+        },
+        &inner_and_rest[end + 2..],
+    ))
+}
+
+/// The line with the answers of `hidden` replaced by blanks, or with every
+/// answer shown when `hidden` is None
+fn render_cloze(parts: &[ClozePart], hidden: Option<Option<u32>>) -> String {
+    parts
+        .iter()
+        .map(|part| match part {
+            ClozePart::Text(text) => text.clone(),
+            ClozePart::Answer { group, hint, .. } if hidden == Some(*group) => {
+                format!("[{}]", hint.as_deref().unwrap_or("..."))
+            }
+            ClozePart::Answer { answer, .. } => answer.clone(),
+        })
+        .collect()
+}
+
+/// The line without its heading, quote or list marker
+fn strip_markers(line: &str) -> &str {
+    let mut text = line.trim_start_matches('>').trim_start();
+
+    let hashes = text.len() - text.trim_start_matches('#').len();
+    if (1..=6).contains(&hashes) && text[hashes..].starts_with(' ') {
+        text = text[hashes..].trim_start();
+    }
+
+    if let Some(rest) = text.strip_prefix(['-', '*', '+']).filter(|r| r.starts_with(' ')) {
+        text = rest.trim_start();
+    } else {
+        let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if let Some(rest) = text[digits..].strip_prefix(['.', ')']).filter(|r| digits > 0 && r.starts_with(' ')) {
+            text = rest.trim_start();
+        }
+    }
+
+    text.trim_end()
+}
+
+/// The text after a `Q:` or `A:` label, either case
+fn strip_label(text: &str, label: char) -> Option<&str> {
+    let mut chars = text.chars();
+    let first = chars.next()?;
+    if first.to_ascii_uppercase() != label {
+        return None;
+    }
+    chars.as_str().strip_prefix(':').map(str::trim)
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
@@ -1,9 +1,12 @@
+use std::collections::{HashMap, HashSet};
+
 use rusqlite::{params, Connection};
 use uuid::Uuid;
 
 use super::models::{
     Choice, CreateChoiceRequest, CreateQuestionRequest, CreateQuizRequest, Question, QuestionTag,
-    QuestionType, Quiz, QuizTag, UpdateQuestionRequest, UpdateQuizRequest,
+    QuestionType, Quiz, QuizPoolRule, QuizPoolRuleRequest, QuizTag, UpdateQuestionRequest,
+    UpdateQuizRequest,
 };
 
 // ============================================
@@ -19,15 +22,30 @@ pub fn create_quiz(conn: &Connection, user_id: &str, request: &CreateQuizRequest
     if name.len() > 255 {
         return Err("Quiz name cannot exceed 255 characters".to_string());
     }
+    validate_pool_size(request.pool_size)?;
+    validate_time_limit(request.time_limit_seconds)?;
 
     let id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now().to_rfc3339();
     let shuffle = request.shuffle_questions.unwrap_or(false);
+    let shuffle_choices = request.shuffle_choices.unwrap_or(false);
 
     conn.execute(
-        "INSERT INTO quizzes (id, user_id, name, description, shuffle_questions, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-        params![id, user_id, name, request.description, shuffle as i32, now, now],
+        "INSERT INTO quizzes (id, user_id, name, description, shuffle_questions, pool_size,
+         shuffle_choices, time_limit_seconds, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            id,
+            user_id,
+            name,
+            request.description,
+            shuffle as i32,
+            request.pool_size,
+            shuffle_choices as i32,
+            request.time_limit_seconds,
+            now,
+            now
+        ],
     )
     .map_err(|e| format!("Failed to create quiz: {}", e))?;
 
@@ -37,7 +55,8 @@ pub fn create_quiz(conn: &Connection, user_id: &str, request: &CreateQuizRequest
 pub fn get_quiz(conn: &Connection, quiz_id: &str) -> Result<Quiz, String> {
     let mut stmt = conn
         .prepare(
-            "SELECT id, name, description, shuffle_questions, created_at, updated_at
+            "SELECT id, name, description, shuffle_questions, pool_size, shuffle_choices,
+             time_limit_seconds, created_at, updated_at, COALESCE(content_id, id)
              FROM quizzes WHERE id = ?1",
         )
         .map_err(|e| format!("Failed to prepare query: {}", e))?;
@@ -46,12 +65,17 @@ pub fn get_quiz(conn: &Connection, quiz_id: &str) -> Result<Quiz, String> {
         .query_row(params![quiz_id], |row| {
             Ok(Quiz {
                 id: row.get(0)?,
+                content_id: row.get(9)?,
                 name: row.get(1)?,
                 description: row.get(2)?,
                 shuffle_questions: row.get::<_, i32>(3)? != 0,
-                created_at: row.get(4)?,
-                updated_at: row.get(5)?,
+                pool_size: row.get(4)?,
+                shuffle_choices: row.get::<_, i32>(5)? != 0,
+                time_limit_seconds: row.get(6)?,
+                created_at: row.get(7)?,
+                updated_at: row.get(8)?,
                 questions: vec![],
+                pool_rules: vec![],
                 question_count: None,
                 is_favorite: None,
             })
@@ -59,10 +83,12 @@ pub fn get_quiz(conn: &Connection, quiz_id: &str) -> Result<Quiz, String> {
         .map_err(|e| format!("Quiz not found: {}", e))?;
 
     let questions = get_questions_for_quiz(conn, quiz_id)?;
+    let pool_rules = get_pool_rules_for_quiz(conn, quiz_id)?;
     let count = questions.len() as i32;
 
     Ok(Quiz {
         questions,
+        pool_rules,
         question_count: Some(count),
         ..quiz
     })
@@ -71,10 +97,11 @@ pub fn get_quiz(conn: &Connection, quiz_id: &str) -> Result<Quiz, String> {
 pub fn get_all_quizzes(conn: &Connection, user_id: &str) -> Result<Vec<Quiz>, String> {
     let mut stmt = conn
         .prepare(
-            "SELECT q.id, q.name, q.description, q.shuffle_questions,
-                    q.created_at, q.updated_at,
+            "SELECT q.id, q.name, q.description, q.shuffle_questions, q.pool_size,
+                    q.shuffle_choices, q.time_limit_seconds, q.created_at, q.updated_at,
                     (SELECT COUNT(*) FROM questions WHERE quiz_id = q.id) as question_count,
-                    CASE WHEN qf.quiz_id IS NOT NULL THEN 1 ELSE 0 END as is_favorite
+                    CASE WHEN qf.quiz_id IS NOT NULL THEN 1 ELSE 0 END as is_favorite,
+                    COALESCE(q.content_id, q.id)
              FROM quizzes q
              LEFT JOIN quiz_favorites qf ON q.id = qf.quiz_id AND qf.user_id = ?1
              WHERE q.user_id = ?1
@@ -86,14 +113,19 @@ pub fn get_all_quizzes(conn: &Connection, user_id: &str) -> Result<Vec<Quiz>, St
         .query_map(params![user_id], |row| {
             Ok(Quiz {
                 id: row.get(0)?,
+                content_id: row.get(11)?,
                 name: row.get(1)?,
                 description: row.get(2)?,
                 shuffle_questions: row.get::<_, i32>(3)? != 0,
-                created_at: row.get(4)?,
-                updated_at: row.get(5)?,
+                pool_size: row.get(4)?,
+                shuffle_choices: row.get::<_, i32>(5)? != 0,
+                time_limit_seconds: row.get(6)?,
+                created_at: row.get(7)?,
+                updated_at: row.get(8)?,
                 questions: vec![],
-                question_count: Some(row.get(6)?),
-                is_favorite: Some(row.get::<_, i32>(7)? != 0),
+                pool_rules: vec![],
+                question_count: Some(row.get(9)?),
+                is_favorite: Some(row.get::<_, i32>(10)? != 0),
             })
         })
         .map_err(|e| format!("Failed to query quizzes: {}", e))?
@@ -108,13 +140,37 @@ pub fn update_quiz(
     quiz_id: &str,
     request: &UpdateQuizRequest,
 ) -> Result<Quiz, String> {
+    validate_pool_size(request.pool_size)?;
+    validate_time_limit(request.time_limit_seconds)?;
+
+    // The saved pool rules must still fit a changed pool size
+    let rules: Vec<QuizPoolRuleRequest> = get_pool_rules_for_quiz(conn, quiz_id)?
+        .into_iter()
+        .map(|rule| QuizPoolRuleRequest {
+            tag_id: rule.tag_id,
+            question_count: rule.question_count,
+        })
+        .collect();
+    validate_pool_rules(conn, quiz_id, &rules, request.pool_size)?;
+
     let now = chrono::Utc::now().to_rfc3339();
     let shuffle = request.shuffle_questions.unwrap_or(false);
+    let shuffle_choices = request.shuffle_choices.unwrap_or(false);
 
     conn.execute(
-        "UPDATE quizzes SET name = ?1, description = ?2, shuffle_questions = ?3, updated_at = ?4
-         WHERE id = ?5",
-        params![request.name, request.description, shuffle as i32, now, quiz_id],
+        "UPDATE quizzes SET name = ?1, description = ?2, shuffle_questions = ?3, pool_size = ?4,
+         shuffle_choices = ?5, time_limit_seconds = ?6, updated_at = ?7
+         WHERE id = ?8",
+        params![
+            request.name,
+            request.description,
+            shuffle as i32,
+            request.pool_size,
+            shuffle_choices as i32,
+            request.time_limit_seconds,
+            now,
+            quiz_id
+        ],
     )
     .map_err(|e| format!("Failed to update quiz: {}", e))?;
 
@@ -135,6 +191,33 @@ pub fn delete_quiz(conn: &Connection, user_id: &str, quiz_id: &str) -> Result<()
     Ok(())
 }
 
+/// Find the quiz an earlier import created from the same source quiz
+pub fn find_quiz_by_content_id(
+    conn: &Connection,
+    user_id: &str,
+    content_id: &str,
+) -> Result<Option<Quiz>, String> {
+    match conn.query_row(
+        "SELECT id FROM quizzes WHERE user_id = ?1 AND COALESCE(content_id, id) = ?2
+         ORDER BY updated_at DESC LIMIT 1",
+        params![user_id, content_id],
+        |row| row.get::<_, String>(0),
+    ) {
+        Ok(id) => get_quiz(conn, &id).map(Some),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(format!("Database error: {}", e)),
+    }
+}
+
+pub fn set_quiz_content_id(conn: &Connection, quiz_id: &str, content_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE quizzes SET content_id = ?1 WHERE id = ?2",
+        params![content_id, quiz_id],
+    )
+    .map_err(|e| format!("Failed to set quiz content ID: {}", e))?;
+    Ok(())
+}
+
 // ============================================
 // Question Operations
 // ============================================
@@ -144,6 +227,8 @@ pub fn create_question(
     quiz_id: &str,
     request: &CreateQuestionRequest,
 ) -> Result<Question, String> {
+    validate_time_limit(request.time_limit_seconds)?;
+
     let id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -161,9 +246,9 @@ pub fn create_question(
 
     conn.execute(
         "INSERT INTO questions (id, quiz_id, question_type, content, content_type,
-         content_language, correct_answer, multiple_answers, explanation, position,
-         created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+         content_language, correct_answer, multiple_answers, explanation, time_limit_seconds,
+         position, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
         params![
             id,
             quiz_id,
@@ -174,6 +259,7 @@ pub fn create_question(
             request.correct_answer,
             multiple_answers as i32,
             request.explanation,
+            request.time_limit_seconds,
             position,
             now,
             now
@@ -195,7 +281,8 @@ pub fn get_question(conn: &Connection, question_id: &str) -> Result<Question, St
     let mut stmt = conn
         .prepare(
             "SELECT id, quiz_id, question_type, content, content_type, content_language,
-             correct_answer, multiple_answers, explanation, position, created_at, updated_at
+             correct_answer, multiple_answers, explanation, time_limit_seconds, position,
+             created_at, updated_at, COALESCE(content_id, id)
              FROM questions WHERE id = ?1",
         )
         .map_err(|e| format!("Failed to prepare query: {}", e))?;
@@ -204,6 +291,7 @@ pub fn get_question(conn: &Connection, question_id: &str) -> Result<Question, St
         .query_row(params![question_id], |row| {
             Ok(Question {
                 id: row.get(0)?,
+                content_id: row.get(13)?,
                 quiz_id: row.get(1)?,
                 question_type: QuestionType::from_str(&row.get::<_, String>(2)?),
                 content: row.get(3)?,
@@ -212,9 +300,10 @@ pub fn get_question(conn: &Connection, question_id: &str) -> Result<Question, St
                 correct_answer: row.get(6)?,
                 multiple_answers: row.get::<_, i32>(7)? != 0,
                 explanation: row.get(8)?,
-                position: row.get(9)?,
-                created_at: row.get(10)?,
-                updated_at: row.get(11)?,
+                time_limit_seconds: row.get(9)?,
+                position: row.get(10)?,
+                created_at: row.get(11)?,
+                updated_at: row.get(12)?,
                 choices: vec![],
                 tags: vec![],
             })
@@ -235,7 +324,8 @@ pub fn get_questions_for_quiz(conn: &Connection, quiz_id: &str) -> Result<Vec<Qu
     let mut stmt = conn
         .prepare(
             "SELECT id, quiz_id, question_type, content, content_type, content_language,
-             correct_answer, multiple_answers, explanation, position, created_at, updated_at
+             correct_answer, multiple_answers, explanation, time_limit_seconds, position,
+             created_at, updated_at, COALESCE(content_id, id)
              FROM questions WHERE quiz_id = ?1 ORDER BY position",
         )
         .map_err(|e| format!("Failed to prepare query: {}", e))?;
@@ -244,6 +334,7 @@ pub fn get_questions_for_quiz(conn: &Connection, quiz_id: &str) -> Result<Vec<Qu
         .query_map(params![quiz_id], |row| {
             Ok(Question {
                 id: row.get(0)?,
+                content_id: row.get(13)?,
                 quiz_id: row.get(1)?,
                 question_type: QuestionType::from_str(&row.get::<_, String>(2)?),
                 content: row.get(3)?,
@@ -252,9 +343,10 @@ pub fn get_questions_for_quiz(conn: &Connection, quiz_id: &str) -> Result<Vec<Qu
                 correct_answer: row.get(6)?,
                 multiple_answers: row.get::<_, i32>(7)? != 0,
                 explanation: row.get(8)?,
-                position: row.get(9)?,
-                created_at: row.get(10)?,
-                updated_at: row.get(11)?,
+                time_limit_seconds: row.get(9)?,
+                position: row.get(10)?,
+                created_at: row.get(11)?,
+                updated_at: row.get(12)?,
                 choices: vec![],
                 tags: vec![],
             })
@@ -279,6 +371,8 @@ pub fn update_question(
     question_id: &str,
     request: &UpdateQuestionRequest,
 ) -> Result<Question, String> {
+    validate_time_limit(request.time_limit_seconds)?;
+
     let now = chrono::Utc::now().to_rfc3339();
     let content_type = request.content_type.as_deref().unwrap_or("TEXT");
     let multiple_answers = request.multiple_answers.unwrap_or(false);
@@ -286,7 +380,7 @@ pub fn update_question(
     conn.execute(
         "UPDATE questions SET question_type = ?1, content = ?2, content_type = ?3,
          content_language = ?4, correct_answer = ?5, multiple_answers = ?6,
-         explanation = ?7, updated_at = ?8 WHERE id = ?9",
+         explanation = ?7, time_limit_seconds = ?8, updated_at = ?9 WHERE id = ?10",
         params![
             request.question_type,
             request.content,
@@ -295,6 +389,7 @@ pub fn update_question(
             request.correct_answer,
             multiple_answers as i32,
             request.explanation,
+            request.time_limit_seconds,
             now,
             question_id
         ],
@@ -304,6 +399,19 @@ pub fn update_question(
     get_question(conn, question_id)
 }
 
+pub fn set_question_content_id(
+    conn: &Connection,
+    question_id: &str,
+    content_id: &str,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE questions SET content_id = ?1 WHERE id = ?2",
+        params![content_id, question_id],
+    )
+    .map_err(|e| format!("Failed to set question content ID: {}", e))?;
+    Ok(())
+}
+
 pub fn delete_question(conn: &Connection, question_id: &str) -> Result<(), String> {
     conn.execute("DELETE FROM questions WHERE id = ?1", params![question_id])
         .map_err(|e| format!("Failed to delete question: {}", e))?;
@@ -319,16 +427,7 @@ pub fn reorder_questions(
     conn.execute("BEGIN TRANSACTION", [])
         .map_err(|e| format!("Failed to begin transaction: {}", e))?;
 
-    let result = (|| -> Result<(), String> {
-        for (idx, qid) in question_ids.iter().enumerate() {
-            conn.execute(
-                "UPDATE questions SET position = ?1 WHERE id = ?2 AND quiz_id = ?3",
-                params![idx as i32, qid, quiz_id],
-            )
-            .map_err(|e| format!("Failed to reorder question: {}", e))?;
-        }
-        Ok(())
-    })();
+    let result = set_question_order(conn, quiz_id, question_ids);
 
     match result {
         Ok(()) => {
@@ -345,6 +444,22 @@ pub fn reorder_questions(
     }
 }
 
+/// Renumber questions in the given order, for callers already in a transaction
+pub fn set_question_order(
+    conn: &Connection,
+    quiz_id: &str,
+    question_ids: &[String],
+) -> Result<(), String> {
+    for (idx, qid) in question_ids.iter().enumerate() {
+        conn.execute(
+            "UPDATE questions SET position = ?1 WHERE id = ?2 AND quiz_id = ?3",
+            params![idx as i32, qid, quiz_id],
+        )
+        .map_err(|e| format!("Failed to reorder question: {}", e))?;
+    }
+    Ok(())
+}
+
 // ============================================
 // Choice Operations
 // ============================================
@@ -532,6 +647,197 @@ pub fn get_quiz_tag_by_name(
     }
 }
 
+fn validate_time_limit(time_limit_seconds: Option<i32>) -> Result<(), String> {
+    if let Some(seconds) = time_limit_seconds {
+        if seconds < 1 {
+            return Err("Time limit must be at least 1 second".to_string());
+        }
+    }
+    Ok(())
+}
+
+// ============================================
+// Question Pool Operations
+// ============================================
+
+fn validate_pool_size(pool_size: Option<i32>) -> Result<(), String> {
+    if let Some(size) = pool_size {
+        if size < 1 {
+            return Err("Pool size must be at least 1".to_string());
+        }
+    }
+    Ok(())
+}
+
+pub fn get_pool_rules_for_quiz(conn: &Connection, quiz_id: &str) -> Result<Vec<QuizPoolRule>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT r.id, r.quiz_id, r.tag_id, t.name, r.question_count, r.position
+             FROM quiz_pool_rules r
+             INNER JOIN quiz_tags t ON t.id = r.tag_id
+             WHERE r.quiz_id = ?1 ORDER BY r.position",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let rules = stmt
+        .query_map(params![quiz_id], |row| {
+            Ok(QuizPoolRule {
+                id: row.get(0)?,
+                quiz_id: row.get(1)?,
+                tag_id: row.get(2)?,
+                tag_name: row.get(3)?,
+                question_count: row.get(4)?,
+                position: row.get(5)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query pool rules: {}", e))?;
+
+    rules
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect pool rules: {}", e))
+}
+
+/// Replace the stratified sampling rules for a quiz
+pub fn set_pool_rules_for_quiz(
+    conn: &Connection,
+    quiz_id: &str,
+    rules: &[QuizPoolRuleRequest],
+) -> Result<Vec<QuizPoolRule>, String> {
+    let pool_size: Option<i32> = conn
+        .query_row(
+            "SELECT pool_size FROM quizzes WHERE id = ?1",
+            params![quiz_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Quiz not found: {}", e))?;
+    validate_pool_rules(conn, quiz_id, rules, pool_size)?;
+
+    // Imports call this inside their own transaction; only open one when nobody else has
+    let owns_transaction = conn.is_autocommit();
+    if owns_transaction {
+        conn.execute("BEGIN TRANSACTION", [])
+            .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    }
+
+    let result = (|| -> Result<(), String> {
+        conn.execute(
+            "DELETE FROM quiz_pool_rules WHERE quiz_id = ?1",
+            params![quiz_id],
+        )
+        .map_err(|e| format!("Failed to clear pool rules: {}", e))?;
+
+        for (idx, rule) in rules.iter().enumerate() {
+            let id = Uuid::new_v4().to_string();
+            conn.execute(
+                "INSERT INTO quiz_pool_rules (id, quiz_id, tag_id, question_count, position)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![id, quiz_id, rule.tag_id, rule.question_count, idx as i32],
+            )
+            .map_err(|e| format!("Failed to save pool rule: {}", e))?;
+        }
+        Ok(())
+    })();
+
+    if owns_transaction {
+        match result {
+            Ok(()) => {
+                conn.execute("COMMIT", [])
+                    .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            }
+            Err(e) => {
+                if let Err(rollback_err) = conn.execute("ROLLBACK", []) {
+                    eprintln!("Warning: Failed to rollback transaction: {}", rollback_err);
+                }
+                return Err(e);
+            }
+        }
+    } else {
+        result?;
+    }
+
+    get_pool_rules_for_quiz(conn, quiz_id)
+}
+
+/// Check that an attempt can always draw what the rules ask for. Rules draw in
+/// order without reusing questions, so a rule can only count on the questions
+/// with its tag that earlier rules cannot take, and together the rules may
+/// not draw more than the quiz's pool size.
+fn validate_pool_rules(
+    conn: &Connection,
+    quiz_id: &str,
+    rules: &[QuizPoolRuleRequest],
+    pool_size: Option<i32>,
+) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.id, t.name, qt.question_id FROM quiz_tags t
+             LEFT JOIN question_tags qt ON qt.tag_id = t.id
+             WHERE t.quiz_id = ?1",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let rows = stmt
+        .query_map(params![quiz_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })
+        .map_err(|e| format!("Failed to query tagged questions: {}", e))?;
+
+    let mut tags: HashMap<String, (String, HashSet<String>)> = HashMap::new();
+    for row in rows {
+        let (tag_id, name, question_id) =
+            row.map_err(|e| format!("Failed to read tagged question: {}", e))?;
+        let (_, questions) = tags.entry(tag_id).or_insert_with(|| (name, HashSet::new()));
+        questions.extend(question_id);
+    }
+
+    for (index, rule) in rules.iter().enumerate() {
+        if rule.question_count < 1 {
+            return Err("Each pool rule must draw at least 1 question".to_string());
+        }
+        let (name, tagged) = tags
+            .get(&rule.tag_id)
+            .ok_or_else(|| format!("Tag not found in this quiz: {}", rule.tag_id))?;
+        if (tagged.len() as i32) < rule.question_count {
+            return Err(format!(
+                "Pool rule asks for {} questions but only {} carry that tag",
+                rule.question_count,
+                tagged.len()
+            ));
+        }
+
+        // Earlier rules may draw the questions this tag shares with theirs
+        let taken: i32 = rules[..index]
+            .iter()
+            .map(|earlier| {
+                let shared = tags[&earlier.tag_id].1.intersection(tagged).count() as i32;
+                shared.min(earlier.question_count)
+            })
+            .sum();
+        let left = tagged.len() as i32 - taken;
+        if left < rule.question_count {
+            return Err(format!(
+                "Pool rule for \"{}\" asks for {} questions, but earlier rules with overlapping tags may leave only {}",
+                name,
+                rule.question_count,
+                left.max(0)
+            ));
+        }
+    }
+
+    let drawn: i32 = rules.iter().map(|r| r.question_count).sum();
+    if let Some(pool_size) = pool_size.filter(|&size| drawn > size) {
+        return Err(format!(
+            "Pool rules draw {} questions but the quiz asks only {} per attempt",
+            drawn, pool_size
+        ));
+    }
+
+    Ok(())
+}
+
 // ============================================
 // Quiz Favorites Operations
 // ============================================
@@ -0,0 +1,318 @@
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use rusqlite::{params, Connection};
+
+use super::courses::get_lessons;
+use super::electives::{course_completion, get_elective_groups};
+use super::models::{
+    CompletionEstimate, CompletionPoint, CourseReport, DropOffLesson, ElectiveGroup, Lesson,
+    LessonItem, LessonItemReport, LessonItemType, LessonReport, ScoreBucket, ScoreDistribution,
+};
+use super::pacing::today;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Progress on one lesson item, with the time spent in its linked attempt or session
+struct ItemProgress {
+    completed_at: Option<String>,
+    created_at: String,
+    updated_at: String,
+    linked_seconds: i32,
+}
+
+// ============================================
+// Course Report
+// ============================================
+
+/// Build the learner's report for a course: completion over time, time spent
+/// per lesson, quiz score distributions, where progress stopped and how long
+/// the rest of the course should take at the pace so far
+pub fn get_course_report(conn: &Connection, user_id: &str, course_id: &str) -> Result<CourseReport, String> {
+    let lessons = get_lessons(conn, user_id, course_id)?;
+    let mut groups = get_elective_groups(conn, course_id)?;
+    let progress = get_item_progress(conn, user_id, course_id)?;
+    let completion = course_completion(&lessons, &mut groups);
+
+    let mut lesson_reports = Vec::new();
+    let mut completion_dates = HashMap::new();
+    for lesson in &lessons {
+        let mut items = Vec::new();
+        for item in &lesson.items {
+            items.push(item_report(conn, item, progress.get(&item.id))?);
+        }
+
+        // A lesson is complete once its last item is
+        let completed_at = if lesson.is_completed == Some(true) {
+            lesson
+                .items
+                .iter()
+                .filter_map(|i| progress.get(&i.id).and_then(|p| p.completed_at.clone()))
+                .max()
+        } else {
+            None
+        };
+        if let Some(date) = completed_at.as_deref().and_then(local_date) {
+            completion_dates.insert(lesson.id.as_str(), date);
+        }
+
+        lesson_reports.push(LessonReport {
+            lesson_id: lesson.id.clone(),
+            title: lesson.title.clone(),
+            is_completed: lesson.is_completed == Some(true),
+            completed_at,
+            time_spent_seconds: items.iter().map(|i| i.time_spent_seconds).sum(),
+            items,
+        });
+    }
+
+    let timed: Vec<i32> = lesson_reports
+        .iter()
+        .filter(|l| l.is_completed && l.time_spent_seconds > 0)
+        .map(|l| l.time_spent_seconds)
+        .collect();
+    let average_lesson_time_seconds =
+        (!timed.is_empty()).then(|| timed.iter().sum::<i32>() as f64 / timed.len() as f64);
+
+    let last_activity_at = progress.values().map(|p| p.updated_at.clone()).max();
+    let drop_off = if completion.is_complete() {
+        None
+    } else {
+        drop_off_lesson(&lessons, &groups, last_activity_at)
+    };
+
+    let estimate = estimate_completion(
+        &progress,
+        &completion_dates,
+        completion.required - completion.completed,
+        completion.completed,
+        average_lesson_time_seconds,
+    );
+
+    Ok(CourseReport {
+        course_id: course_id.to_string(),
+        completion_percentage: percentage(completion.completed, completion.required),
+        completed_lesson_count: completion.completed,
+        required_lesson_count: completion.required,
+        timeline: completion_timeline(&lessons, &groups, &completion_dates),
+        lessons: lesson_reports,
+        average_lesson_time_seconds,
+        drop_off,
+        estimate,
+    })
+}
+
+fn get_item_progress(
+    conn: &Connection,
+    user_id: &str,
+    course_id: &str,
+) -> Result<HashMap<String, ItemProgress>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT lp.lesson_item_id, lp.completed_at, lp.created_at, lp.updated_at,
+                    COALESCE(qa.duration_seconds, 0) + COALESCE(ss.duration_seconds, 0)
+             FROM lesson_progress lp
+             LEFT JOIN quiz_attempts qa ON qa.id = lp.attempt_id
+             LEFT JOIN study_sessions ss ON ss.id = lp.session_id
+             WHERE lp.user_id = ?1 AND lp.course_id = ?2",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let progress = stmt
+        .query_map(params![user_id, course_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                ItemProgress {
+                    completed_at: row.get(1)?,
+                    created_at: row.get(2)?,
+                    updated_at: row.get(3)?,
+                    linked_seconds: row.get(4)?,
+                },
+            ))
+        })
+        .map_err(|e| format!("Failed to query lesson progress: {}", e))?;
+
+    progress
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| format!("Failed to collect lesson progress: {}", e))
+}
+
+fn item_report(
+    conn: &Connection,
+    item: &LessonItem,
+    progress: Option<&ItemProgress>,
+) -> Result<LessonItemReport, String> {
+    // Pages are timed while read rather than through a linked session
+    let read_seconds = match (&item.item_type, &item.item_id) {
+        (LessonItemType::Page, Some(page_id)) => conn
+            .query_row(
+                "SELECT COALESCE(SUM(seconds_read), 0) FROM page_reads WHERE page_id = ?1",
+                params![page_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to get reading time: {}", e))?,
+        (LessonItemType::Notebook, Some(notebook_id)) => conn
+            .query_row(
+                "SELECT COALESCE(SUM(r.seconds_read), 0)
+                 FROM page_reads r JOIN pages p ON p.id = r.page_id
+                 WHERE p.notebook_id = ?1",
+                params![notebook_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to get reading time: {}", e))?,
+        _ => 0,
+    };
+
+    let scores = match (&item.item_type, &item.item_id) {
+        (LessonItemType::Quiz, Some(quiz_id)) => Some(score_distribution(conn, quiz_id)?),
+        _ => None,
+    };
+
+    Ok(LessonItemReport {
+        lesson_item_id: item.id.clone(),
+        item_type: item.item_type.clone(),
+        item_name: item.item_name.clone(),
+        is_completed: item.is_completed == Some(true),
+        time_spent_seconds: progress.map_or(0, |p| p.linked_seconds) + read_seconds,
+        scores,
+    })
+}
+
+/// Scores of the quiz's graded standard attempts, bucketed by tens with 100
+/// counted in the top bucket
+fn score_distribution(conn: &Connection, quiz_id: &str) -> Result<ScoreDistribution, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT score_percentage FROM quiz_attempts
+             WHERE quiz_id = ?1 AND completed_at IS NOT NULL AND abandoned_at IS NULL
+               AND mode = 'standard'",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let scores = stmt
+        .query_map(params![quiz_id], |row| row.get::<_, i32>(0))
+        .map_err(|e| format!("Failed to query quiz scores: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect quiz scores: {}", e))?;
+
+    let mut buckets: Vec<ScoreBucket> = (0..10)
+        .map(|i| ScoreBucket {
+            min_score: i * 10,
+            max_score: if i == 9 { 100 } else { i * 10 + 9 },
+            count: 0,
+        })
+        .collect();
+    for &score in &scores {
+        buckets[(score.clamp(0, 100) / 10).min(9) as usize].count += 1;
+    }
+
+    Ok(ScoreDistribution {
+        attempts: scores.len() as i32,
+        average_score: (!scores.is_empty())
+            .then(|| scores.iter().sum::<i32>() as f64 / scores.len() as f64),
+        best_score: scores.iter().max().copied(),
+        buckets,
+    })
+}
+
+/// Completion at the end of each day a lesson was completed, judged by the
+/// course's current rules against the lessons finished by then
+fn completion_timeline(
+    lessons: &[Lesson],
+    groups: &[ElectiveGroup],
+    completion_dates: &HashMap<&str, NaiveDate>,
+) -> Vec<CompletionPoint> {
+    let dates: BTreeSet<NaiveDate> = completion_dates.values().copied().collect();
+
+    dates
+        .into_iter()
+        .map(|date| {
+            let mut as_of = lessons.to_vec();
+            for lesson in &mut as_of {
+                lesson.is_completed =
+                    Some(completion_dates.get(lesson.id.as_str()).is_some_and(|d| *d <= date));
+            }
+            let completion = course_completion(&as_of, &mut groups.to_vec());
+            CompletionPoint {
+                date: date.format(DATE_FORMAT).to_string(),
+                completed_lesson_count: completion.completed,
+                completion_percentage: percentage(completion.completed, completion.required),
+            }
+        })
+        .collect()
+}
+
+/// The first unfinished lesson that still counts towards completion: a
+/// mandatory lesson, or one from an elective group that needs more
+fn drop_off_lesson(
+    lessons: &[Lesson],
+    groups: &[ElectiveGroup],
+    last_activity_at: Option<String>,
+) -> Option<DropOffLesson> {
+    let lesson = lessons.iter().find(|l| {
+        if l.items.is_empty() || l.is_completed == Some(true) || l.is_optional {
+            return false;
+        }
+        match &l.elective_group_id {
+            Some(group_id) => groups
+                .iter()
+                .any(|g| &g.id == group_id && g.is_satisfied != Some(true)),
+            None => true,
+        }
+    })?;
+
+    let days_inactive = last_activity_at
+        .as_deref()
+        .and_then(local_date)
+        .map(|d| (today() - d).num_days());
+    Some(DropOffLesson {
+        lesson_id: lesson.id.clone(),
+        title: lesson.title.clone(),
+        last_activity_at,
+        days_inactive,
+    })
+}
+
+/// Project the remaining lessons at the learner's pace so far: days from their
+/// first progress in the course to their latest completion, per lesson completed
+fn estimate_completion(
+    progress: &HashMap<String, ItemProgress>,
+    completion_dates: &HashMap<&str, NaiveDate>,
+    remaining_lessons: i32,
+    completed_lessons: i32,
+    average_lesson_time_seconds: Option<f64>,
+) -> Option<CompletionEstimate> {
+    if remaining_lessons <= 0 || completed_lessons <= 0 {
+        return None;
+    }
+    let started = progress.values().filter_map(|p| local_date(&p.created_at)).min()?;
+    let latest = completion_dates.values().max()?;
+
+    let days_taken = ((*latest - started).num_days() + 1).max(1);
+    let days_per_lesson = days_taken as f64 / completed_lessons as f64;
+    let estimated_days_remaining = (days_per_lesson * remaining_lessons as f64).ceil() as i64;
+
+    Some(CompletionEstimate {
+        remaining_lessons,
+        days_per_lesson,
+        estimated_days_remaining,
+        estimated_completion_date: (today() + Duration::days(estimated_days_remaining))
+            .format(DATE_FORMAT)
+            .to_string(),
+        estimated_time_remaining_seconds: average_lesson_time_seconds
+            .map(|avg| (avg * remaining_lessons as f64).round() as i64),
+    })
+}
+
+fn local_date(timestamp: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|t| t.with_timezone(&Local).date_naive())
+}
+
+fn percentage(completed: i32, required: i32) -> f64 {
+    if required == 0 {
+        0.0
+    } else {
+        completed as f64 * 100.0 / required as f64
+    }
+}
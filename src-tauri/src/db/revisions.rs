@@ -0,0 +1,339 @@
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection};
+use uuid::Uuid;
+
+use super::models::{DiffLine, DiffLineKind, Page, PageDiff, PageRevision, UpdatePageRequest};
+use super::notebooks::{get_page, update_page};
+
+/// Edits this soon after the last revision belong to the same editing session
+/// and are not saved separately, so autosave keeps one revision per session
+const REVISION_INTERVAL_MINUTES: i64 = 10;
+/// Revisions kept per page, newest first
+const MAX_REVISIONS_PER_PAGE: usize = 50;
+/// Revisions older than this are deleted, except a page's newest
+const REVISION_RETENTION_DAYS: i64 = 90;
+/// Line changes a diff looks for before showing the versions as replaced
+const MAX_DIFF_EDITS: isize = 1000;
+
+// ============================================
+// Saving Revisions
+// ============================================
+
+/// Save the page as it is before it changes to `title` and `content`. Within
+/// an editing session only the first change is saved, unless the edit removes
+/// most of the page, which is always saved so it can be undone.
+pub fn save_revision_before_edit(
+    conn: &Connection,
+    page: &Page,
+    title: &str,
+    content: &str,
+) -> Result<(), String> {
+    if page.title == title && page.content == content {
+        return Ok(());
+    }
+
+    let latest = get_page_revisions(conn, &page.id)?.into_iter().next();
+    let in_session = latest.as_ref().is_some_and(|r| {
+        DateTime::parse_from_rfc3339(&r.created_at)
+            .is_ok_and(|t| Utc::now() - t.with_timezone(&Utc) < Duration::minutes(REVISION_INTERVAL_MINUTES))
+    });
+    let destructive = content.trim().len() * 2 < page.content.trim().len();
+    if in_session && !destructive {
+        return Ok(());
+    }
+
+    save_revision(conn, page)
+}
+
+/// Save the page as it is now, unless its newest revision already matches
+fn save_revision(conn: &Connection, page: &Page) -> Result<(), String> {
+    let latest = get_page_revisions(conn, &page.id)?.into_iter().next();
+    if latest.is_some_and(|r| r.title == page.title && r.content == page.content) {
+        return Ok(());
+    }
+
+    conn.execute(
+        "INSERT INTO page_revisions (id, page_id, title, content, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            Uuid::new_v4().to_string(),
+            page.id,
+            page.title,
+            page.content,
+            Utc::now().to_rfc3339()
+        ],
+    )
+    .map_err(|e| format!("Failed to save page revision: {}", e))?;
+
+    prune_page_revisions(conn, &page.id)
+}
+
+/// Keep the newest revisions of a page and drop those past the retention period
+fn prune_page_revisions(conn: &Connection, page_id: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM page_revisions WHERE page_id = ?1 AND id NOT IN (
+             SELECT id FROM page_revisions WHERE page_id = ?1 ORDER BY created_at DESC LIMIT ?2
+         )",
+        params![page_id, MAX_REVISIONS_PER_PAGE as i64],
+    )
+    .map_err(|e| format!("Failed to prune page revisions: {}", e))?;
+
+    let cutoff = (Utc::now() - Duration::days(REVISION_RETENTION_DAYS)).to_rfc3339();
+    conn.execute(
+        "DELETE FROM page_revisions WHERE page_id = ?1 AND created_at < ?2 AND id != (
+             SELECT id FROM page_revisions WHERE page_id = ?1 ORDER BY created_at DESC LIMIT 1
+         )",
+        params![page_id, cutoff],
+    )
+    .map_err(|e| format!("Failed to prune page revisions: {}", e))?;
+
+    Ok(())
+}
+
+// ============================================
+// Browsing and Restoring
+// ============================================
+
+/// A page's revisions, newest first
+pub fn get_page_revisions(conn: &Connection, page_id: &str) -> Result<Vec<PageRevision>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, page_id, title, content, created_at FROM page_revisions
+             WHERE page_id = ?1 ORDER BY created_at DESC",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let revisions = stmt
+        .query_map(params![page_id], |row| {
+            Ok(PageRevision {
+                id: row.get(0)?,
+                page_id: row.get(1)?,
+                title: row.get(2)?,
+                content: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query page revisions: {}", e))?;
+
+    revisions
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect page revisions: {}", e))
+}
+
+pub fn get_page_revision(conn: &Connection, id: &str) -> Result<Option<PageRevision>, String> {
+    match conn.query_row(
+        "SELECT id, page_id, title, content, created_at FROM page_revisions WHERE id = ?1",
+        params![id],
+        |row| {
+            Ok(PageRevision {
+                id: row.get(0)?,
+                page_id: row.get(1)?,
+                title: row.get(2)?,
+                content: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        },
+    ) {
+        Ok(revision) => Ok(Some(revision)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(format!("Failed to get page revision: {}", e)),
+    }
+}
+
+/// Put a revision back as the page's content. The page as it was is saved
+/// first, so a restore can itself be undone.
+pub fn restore_page_revision(conn: &Connection, revision_id: &str) -> Result<Page, String> {
+    let revision = get_page_revision(conn, revision_id)?
+        .ok_or_else(|| format!("Page revision not found: {}", revision_id))?;
+    let page = get_page(conn, &revision.page_id)?
+        .ok_or_else(|| format!("Page not found: {}", revision.page_id))?;
+
+    save_revision(conn, &page)?;
+    update_page(
+        conn,
+        &page.id,
+        &UpdatePageRequest {
+            title: revision.title,
+            content: revision.content,
+            is_pinned: Some(page.is_pinned),
+        },
+    )
+}
+
+// ============================================
+// Diffs
+// ============================================
+
+/// Compare two versions of a page line by line. Either side may be omitted
+/// to mean the page as it is now.
+pub fn diff_page_revisions(
+    conn: &Connection,
+    page_id: &str,
+    from_revision_id: Option<&str>,
+    to_revision_id: Option<&str>,
+) -> Result<PageDiff, String> {
+    let (from_title, from_content) = page_version(conn, page_id, from_revision_id)?;
+    let (to_title, to_content) = page_version(conn, page_id, to_revision_id)?;
+
+    let lines = diff_lines(&from_content, &to_content);
+    Ok(PageDiff {
+        page_id: page_id.to_string(),
+        from_revision_id: from_revision_id.map(|s| s.to_string()),
+        to_revision_id: to_revision_id.map(|s| s.to_string()),
+        title_changed: from_title != to_title,
+        lines_added: lines.iter().filter(|l| l.kind == DiffLineKind::Added).count() as i32,
+        lines_removed: lines.iter().filter(|l| l.kind == DiffLineKind::Removed).count() as i32,
+        lines,
+    })
+}
+
+/// The title and content of a revision of the page, or of the page itself
+fn page_version(conn: &Connection, page_id: &str, revision_id: Option<&str>) -> Result<(String, String), String> {
+    match revision_id {
+        Some(id) => {
+            let revision = get_page_revision(conn, id)?
+                .filter(|r| r.page_id == page_id)
+                .ok_or_else(|| format!("Page revision not found: {}", id))?;
+            Ok((revision.title, revision.content))
+        }
+        None => {
+            let page = get_page(conn, page_id)?.ok_or_else(|| format!("Page not found: {}", page_id))?;
+            Ok((page.title, page.content))
+        }
+    }
+}
+
+/// Line diff after setting aside the lines both versions start and end with.
+/// The lines between are compared with Myers' algorithm, which is fast when
+/// the versions are close; past `MAX_DIFF_EDITS` changes they are shown as
+/// removed and added wholesale.
+fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut lines = Vec::new();
+    let unchanged = |text: &str, old_index: usize, new_index: usize| DiffLine {
+        kind: DiffLineKind::Unchanged,
+        text: text.to_string(),
+        old_line: Some(old_index as i32 + 1),
+        new_line: Some(new_index as i32 + 1),
+    };
+
+    for (i, text) in old[..prefix].iter().enumerate() {
+        lines.push(unchanged(text, i, i));
+    }
+
+    let edits = shortest_edit(old_mid, new_mid).unwrap_or_else(|| {
+        let removed = (0..old_mid.len()).map(|i| (Some(i), None));
+        let added = (0..new_mid.len()).map(|j| (None, Some(j)));
+        removed.chain(added).collect()
+    });
+    for edit in edits {
+        lines.push(match edit {
+            (Some(i), Some(j)) => unchanged(old_mid[i], prefix + i, prefix + j),
+            (Some(i), None) => DiffLine {
+                kind: DiffLineKind::Removed,
+                text: old_mid[i].to_string(),
+                old_line: Some((prefix + i) as i32 + 1),
+                new_line: None,
+            },
+            (None, Some(j)) => DiffLine {
+                kind: DiffLineKind::Added,
+                text: new_mid[j].to_string(),
+                old_line: None,
+                new_line: Some((prefix + j) as i32 + 1),
+            },
+            (None, None) => unreachable!(),
+        });
+    }
+
+    let old_tail = old.len() - suffix;
+    let new_tail = new.len() - suffix;
+    for k in 0..suffix {
+        lines.push(unchanged(old[old_tail + k], old_tail + k, new_tail + k));
+    }
+
+    lines
+}
+
+/// The fewest removals and additions turning `old` into `new`, as pairs of
+/// line indexes: both for a kept line, only the old for a removed one and only
+/// the new for an added one. None when more than `MAX_DIFF_EDITS` are needed.
+fn shortest_edit(old: &[&str], new: &[&str]) -> Option<Vec<(Option<usize>, Option<usize>)>> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max_d = (n + m).min(MAX_DIFF_EDITS);
+    let offset = max_d + 1;
+    // furthest[k]: the furthest old index reached on diagonal k = x - y
+    let mut furthest = vec![0isize; 2 * offset as usize + 1];
+    // The diagonals reached after each number of edits, -d..=d, to walk back
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    for d in 0..=max_d {
+        for k in (-d..=d).step_by(2) {
+            let (left, right) = (furthest[(offset + k - 1) as usize], furthest[(offset + k + 1) as usize]);
+            let mut x = if k == -d || (k != d && left < right) { right } else { left + 1 };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            furthest[(offset + k) as usize] = x;
+
+            if x >= n && y >= m {
+                trace.push(furthest[(offset - d) as usize..=(offset + d) as usize].to_vec());
+                return Some(walk_back(&trace, n, m));
+            }
+        }
+        trace.push(furthest[(offset - d) as usize..=(offset + d) as usize].to_vec());
+    }
+    None
+}
+
+/// Follow the edits found by `shortest_edit` from the end back to the start
+fn walk_back(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<(Option<usize>, Option<usize>)> {
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+
+    for d in (1..trace.len() as isize).rev() {
+        let previous = &trace[d as usize - 1];
+        let reached = |k: isize| previous[(k + d - 1) as usize];
+        let k = x - y;
+        let previous_k = if k == -d || (k != d && reached(k - 1) < reached(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_x = reached(previous_k);
+        let previous_y = previous_x - previous_k;
+
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            edits.push((Some(x as usize), Some(y as usize)));
+        }
+        if previous_k == k + 1 {
+            edits.push((None, Some(previous_y as usize)));
+        } else {
+            edits.push((Some(previous_x as usize), None));
+        }
+        x = previous_x;
+        y = previous_y;
+    }
+    while x > 0 && y > 0 {
+        x -= 1;
+        y -= 1;
+        edits.push((Some(x as usize), Some(y as usize)));
+    }
+
+    edits.reverse();
+    edits
+}
@@ -1,4 +1,4 @@
-use rusqlite::Connection;
+use rusqlite::{params, Connection};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::Mutex;
@@ -27,10 +27,205 @@ pub fn init_database(app: &AppHandle) -> Result<Connection, String> {
     conn.execute("PRAGMA foreign_keys = ON", [])
         .map_err(|e| format!("Failed to enable foreign keys: {}", e))?;
 
-    // Initialize schema
+    init_schema(&conn)?;
+
+    Ok(conn)
+}
+
+/// Create any missing tables and bring older databases up to date
+pub fn init_schema(conn: &Connection) -> Result<(), String> {
     let schema = include_str!("../../migrations/schema.sql");
     conn.execute_batch(schema)
         .map_err(|e| format!("Failed to initialize database schema: {}", e))?;
 
-    Ok(conn)
+    run_migrations(conn)
+}
+
+/// Add columns introduced after a table was first created.
+/// `CREATE TABLE IF NOT EXISTS` leaves existing tables untouched, so databases
+/// created by older versions only pick up new columns here.
+fn run_migrations(conn: &Connection) -> Result<(), String> {
+    // Question pools
+    add_column_if_missing(conn, "quizzes", "pool_size", "INTEGER")?;
+    add_column_if_missing(conn, "quizzes", "shuffle_choices", "INTEGER NOT NULL DEFAULT 0")?;
+
+    // Timed quizzes
+    add_column_if_missing(conn, "quizzes", "time_limit_seconds", "INTEGER")?;
+    add_column_if_missing(conn, "questions", "time_limit_seconds", "INTEGER")?;
+    add_column_if_missing(conn, "quiz_attempts", "deadline", "TEXT")?;
+    add_column_if_missing(conn, "quiz_attempts", "is_late", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "attempt_questions", "shown_at", "TEXT")?;
+
+    // Resumable attempts
+    add_column_if_missing(conn, "quiz_attempts", "abandoned_at", "TEXT")?;
+
+    // Item analysis
+    add_column_if_missing(conn, "attempt_answers", "time_spent_seconds", "INTEGER")?;
+    add_column_if_missing(conn, "question_results", "time_spent_seconds", "INTEGER")?;
+
+    // Retry and adaptive attempts
+    add_column_if_missing(conn, "quiz_attempts", "mode", "TEXT NOT NULL DEFAULT 'standard'")?;
+
+    // Stable content IDs
+    add_column_if_missing(conn, "courses", "content_id", "TEXT")?;
+    add_column_if_missing(conn, "decks", "content_id", "TEXT")?;
+    add_column_if_missing(conn, "cards", "content_id", "TEXT")?;
+    add_column_if_missing(conn, "quizzes", "content_id", "TEXT")?;
+    add_column_if_missing(conn, "questions", "content_id", "TEXT")?;
+
+    // Course updates
+    add_column_if_missing(conn, "courses", "version", "INTEGER NOT NULL DEFAULT 1")?;
+    add_column_if_missing(conn, "lessons", "content_id", "TEXT")?;
+    add_column_if_missing(conn, "lesson_items", "content_id", "TEXT")?;
+
+    // Lesson prerequisites
+    add_column_if_missing(conn, "lessons", "requires_previous", "INTEGER NOT NULL DEFAULT 0")?;
+
+    // Course pacing
+    add_column_if_missing(conn, "lessons", "due_day", "INTEGER")?;
+
+    // Optional and elective lessons
+    add_column_if_missing(conn, "lessons", "is_optional", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(
+        conn,
+        "lessons",
+        "elective_group_id",
+        "TEXT REFERENCES elective_groups(id) ON DELETE SET NULL",
+    )?;
+
+    // Pages in courses
+    add_column_if_missing(conn, "notebooks", "content_id", "TEXT")?;
+    add_column_if_missing(conn, "pages", "content_id", "TEXT")?;
+
+    // Relinking missing items
+    add_column_if_missing(conn, "lesson_items", "target_content_id", "TEXT")?;
+    allow_missing_lesson_items(conn)?;
+
+    // Sub-pages, before anything below reads pages
+    add_column_if_missing(
+        conn,
+        "pages",
+        "parent_page_id",
+        "TEXT REFERENCES pages(id) ON DELETE CASCADE",
+    )?;
+    // Created here rather than in the schema, which runs before the column exists
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_pages_parent_page_id ON pages(parent_page_id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create index on pages: {}", e))?;
+
+    // Indexed page links
+    index_existing_page_links(conn)?;
+
+    // Cards from pages
+    add_column_if_missing(conn, "cards", "source_page_id", "TEXT")?;
+    add_column_if_missing(conn, "cards", "source_anchor", "TEXT")?;
+
+    Ok(())
+}
+
+/// Older schemas declared `lesson_items.item_id` NOT NULL, so an item whose
+/// deck or quiz was not imported could not be saved. SQLite cannot drop a
+/// constraint, so the table is rebuilt; foreign keys are off meanwhile so
+/// dropping the old table does not cascade to lesson progress.
+fn allow_missing_lesson_items(conn: &Connection) -> Result<(), String> {
+    let not_null: bool = conn
+        .query_row(
+            "SELECT \"notnull\" FROM pragma_table_info('lesson_items') WHERE name = 'item_id'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to inspect table lesson_items: {}", e))?;
+    if !not_null {
+        return Ok(());
+    }
+
+    let foreign_keys: bool = conn
+        .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to read foreign key setting: {}", e))?;
+    conn.execute("PRAGMA foreign_keys = OFF", [])
+        .map_err(|e| format!("Failed to disable foreign keys: {}", e))?;
+
+    let rebuilt = conn.execute_batch(
+        "BEGIN TRANSACTION;
+         CREATE TABLE lesson_items_rebuilt (
+             id TEXT PRIMARY KEY,
+             lesson_id TEXT NOT NULL,
+             item_type TEXT NOT NULL,
+             item_id TEXT,
+             item_name TEXT NOT NULL,
+             requirement_type TEXT,
+             requirement_value INTEGER,
+             position INTEGER NOT NULL DEFAULT 0,
+             content_id TEXT,
+             target_content_id TEXT,
+             created_at TEXT NOT NULL,
+             FOREIGN KEY (lesson_id) REFERENCES lessons(id) ON DELETE CASCADE
+         );
+         INSERT INTO lesson_items_rebuilt (id, lesson_id, item_type, item_id, item_name, requirement_type,
+                                           requirement_value, position, content_id, target_content_id, created_at)
+             SELECT id, lesson_id, item_type, item_id, item_name, requirement_type,
+                    requirement_value, position, content_id, target_content_id, created_at
+             FROM lesson_items;
+         DROP TABLE lesson_items;
+         ALTER TABLE lesson_items_rebuilt RENAME TO lesson_items;
+         CREATE INDEX IF NOT EXISTS idx_lesson_items_lesson_id ON lesson_items(lesson_id);
+         CREATE INDEX IF NOT EXISTS idx_lesson_items_position ON lesson_items(lesson_id, position);
+         COMMIT;",
+    );
+    if rebuilt.is_err() {
+        if let Err(e) = conn.execute("ROLLBACK", []) {
+            eprintln!("Warning: Failed to rollback transaction: {}", e);
+        }
+    }
+
+    if foreign_keys {
+        conn.execute("PRAGMA foreign_keys = ON", [])
+            .map_err(|e| format!("Failed to enable foreign keys: {}", e))?;
+    }
+    rebuilt.map_err(|e| format!("Failed to rebuild table lesson_items: {}", e))
+}
+
+/// Pages saved before links were indexed have content with links but no rows
+/// in `page_links`; index them all once
+fn index_existing_page_links(conn: &Connection) -> Result<(), String> {
+    let unindexed: bool = conn
+        .query_row(
+            "SELECT NOT EXISTS(SELECT 1 FROM page_links)
+                AND EXISTS(SELECT 1 FROM pages WHERE content LIKE '%[[%]]%')",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to inspect page links: {}", e))?;
+    if !unindexed {
+        return Ok(());
+    }
+
+    super::links::index_all_page_links(conn)
+}
+
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), String> {
+    let exists: bool = conn
+        .query_row(
+            "SELECT EXISTS(SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2)",
+            params![table, column],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to inspect table {}: {}", table, e))?;
+
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )
+        .map_err(|e| format!("Failed to add column {}.{}: {}", table, column, e))?;
+    }
+
+    Ok(())
 }
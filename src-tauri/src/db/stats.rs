@@ -1,31 +1,80 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::seq::SliceRandom;
 use rusqlite::{params, Connection};
 use uuid::Uuid;
 
-use super::models::{DeckStudyStats, QuestionAnswer, QuestionResult, QuizAttempt, QuizStats, StudySession};
+use super::models::{
+    AdaptiveProgress, AttemptCleanupResult, AttemptMode, AttemptQuestion, ChoiceSelectionStats,
+    DeckStudyStats, Question, QuestionAnswer, QuestionItemStats, QuestionResult, Quiz, QuizAttempt,
+    QuizItemAnalysis, QuizStats, RetryQuizRequest, SavedAnswer, StudySession,
+};
+use super::quizzes::{get_questions_for_quiz, get_quiz};
+
+/// Slack allowed past a deadline for the round trip from the app
+const DEADLINE_GRACE_SECONDS: i64 = 5;
 
 // ============================================
 // Quiz Attempt Operations
 // ============================================
 
 pub fn start_quiz_attempt(conn: &Connection, quiz_id: &str) -> Result<QuizAttempt, String> {
+    // Draw this attempt's questions up front so grading and review see the same set
+    let quiz = get_quiz(conn, quiz_id)?;
+    let questions = draw_attempt_questions(&quiz)?;
+    let deadline = attempt_time_budget(&quiz, &questions).map(|seconds| {
+        (chrono::Utc::now() + chrono::Duration::seconds(seconds)).to_rfc3339()
+    });
+
+    insert_attempt(conn, quiz_id, questions, deadline, AttemptMode::Standard)
+}
+
+fn insert_attempt(
+    conn: &Connection,
+    quiz_id: &str,
+    questions: Vec<AttemptQuestion>,
+    deadline: Option<String>,
+    mode: AttemptMode,
+) -> Result<QuizAttempt, String> {
     let id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now().to_rfc3339();
+    let total_questions = questions.len() as i32;
 
-    // Get question count
-    let total_questions: i32 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM questions WHERE quiz_id = ?1",
-            params![quiz_id],
-            |row| row.get(0),
+    conn.execute("BEGIN TRANSACTION", [])
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+
+    let result = (|| -> Result<(), String> {
+        conn.execute(
+            "INSERT INTO quiz_attempts (id, quiz_id, started_at, total_questions, correct_answers,
+             score_percentage, deadline, mode)
+             VALUES (?1, ?2, ?3, ?4, 0, 0, ?5, ?6)",
+            params![id, quiz_id, now, total_questions, deadline, mode.as_str()],
         )
-        .unwrap_or(0);
+        .map_err(|e| format!("Failed to start quiz attempt: {}", e))?;
 
-    conn.execute(
-        "INSERT INTO quiz_attempts (id, quiz_id, started_at, total_questions, correct_answers, score_percentage)
-         VALUES (?1, ?2, ?3, ?4, 0, 0)",
-        params![id, quiz_id, now, total_questions],
-    )
-    .map_err(|e| format!("Failed to start quiz attempt: {}", e))?;
+        for question in &questions {
+            conn.execute(
+                "INSERT INTO attempt_questions (attempt_id, question_id, position, choice_order)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![id, question.question_id, question.position, question.choice_order.join(",")],
+            )
+            .map_err(|e| format!("Failed to save attempt question: {}", e))?;
+        }
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => {
+            conn.execute("COMMIT", [])
+                .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+        }
+        Err(e) => {
+            if let Err(rollback_err) = conn.execute("ROLLBACK", []) {
+                eprintln!("Warning: Failed to rollback transaction: {}", rollback_err);
+            }
+            return Err(e);
+        }
+    }
 
     Ok(QuizAttempt {
         id,
@@ -36,32 +85,260 @@ pub fn start_quiz_attempt(conn: &Connection, quiz_id: &str) -> Result<QuizAttemp
         total_questions,
         correct_answers: 0,
         score_percentage: 0,
+        deadline,
+        is_late: false,
+        abandoned_at: None,
+        mode,
+        questions,
+        saved_answers: vec![],
         question_results: vec![],
     })
 }
 
+/// Pick the questions for a new attempt.
+///
+/// Pool rules are drawn first, without reusing a question across rules; the
+/// rest of `pool_size` is then filled from whatever is left. A quiz with
+/// neither setting asks every question.
+fn draw_attempt_questions(quiz: &Quiz) -> Result<Vec<AttemptQuestion>, String> {
+    let mut rng = rand::thread_rng();
+    let mut remaining: Vec<&Question> = quiz.questions.iter().collect();
+    let mut drawn: Vec<&Question> = Vec::new();
+
+    for rule in &quiz.pool_rules {
+        let (mut tagged, rest): (Vec<&Question>, Vec<&Question>) = remaining
+            .into_iter()
+            .partition(|q| q.tags.iter().any(|t| t.id == rule.tag_id));
+
+        let wanted = rule.question_count as usize;
+        if tagged.len() < wanted {
+            return Err(format!(
+                "Not enough questions tagged \"{}\": need {}, found {}",
+                rule.tag_name,
+                wanted,
+                tagged.len()
+            ));
+        }
+
+        tagged.shuffle(&mut rng);
+        let unused = tagged.split_off(wanted);
+        drawn.extend(tagged);
+        remaining = rest;
+        remaining.extend(unused);
+    }
+
+    match quiz.pool_size {
+        Some(size) => {
+            let missing = (size as usize).saturating_sub(drawn.len());
+            remaining.shuffle(&mut rng);
+            drawn.extend(remaining.into_iter().take(missing));
+        }
+        None if quiz.pool_rules.is_empty() => drawn = remaining,
+        None => {}
+    }
+
+    Ok(order_attempt_questions(quiz, drawn))
+}
+
+/// Lay out chosen questions the way the quiz presents them, fixing the order
+/// of their choices for the attempt.
+fn order_attempt_questions(quiz: &Quiz, mut chosen: Vec<&Question>) -> Vec<AttemptQuestion> {
+    let mut rng = rand::thread_rng();
+    if quiz.shuffle_questions {
+        chosen.shuffle(&mut rng);
+    } else {
+        chosen.sort_by_key(|q| q.position);
+    }
+
+    chosen
+        .into_iter()
+        .enumerate()
+        .map(|(idx, question)| {
+            let mut choice_order: Vec<String> =
+                question.choices.iter().map(|c| c.id.clone()).collect();
+            if quiz.shuffle_choices {
+                choice_order.shuffle(&mut rng);
+            }
+            AttemptQuestion {
+                question_id: question.id.clone(),
+                position: idx as i32,
+                choice_order,
+            }
+        })
+        .collect()
+}
+
+/// Seconds allowed for an attempt: the quiz-wide limit when set, otherwise the
+/// sum of per-question limits as long as every drawn question has one.
+fn attempt_time_budget(quiz: &Quiz, drawn: &[AttemptQuestion]) -> Option<i64> {
+    if let Some(limit) = quiz.time_limit_seconds {
+        return Some(limit as i64);
+    }
+    if drawn.is_empty() {
+        return None;
+    }
+
+    drawn.iter().try_fold(0i64, |total, entry| {
+        quiz.questions
+            .iter()
+            .find(|q| q.id == entry.question_id)
+            .and_then(|q| q.time_limit_seconds)
+            .map(|limit| total + limit as i64)
+    })
+}
+
+pub fn get_attempt_question_order(
+    conn: &Connection,
+    attempt_id: &str,
+) -> Result<Vec<AttemptQuestion>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT question_id, position, choice_order
+             FROM attempt_questions WHERE attempt_id = ?1 ORDER BY position",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let questions = stmt
+        .query_map(params![attempt_id], |row| {
+            let choice_order: Option<String> = row.get(2)?;
+            Ok(AttemptQuestion {
+                question_id: row.get(0)?,
+                position: row.get(1)?,
+                choice_order: choice_order
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.split(',').map(|id| id.to_string()).collect())
+                    .unwrap_or_default(),
+            })
+        })
+        .map_err(|e| format!("Failed to query attempt questions: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect attempt questions: {}", e))?;
+
+    Ok(questions)
+}
+
+/// The questions of an attempt in the order they were asked, with choices in
+/// the order they were shown. Attempts started before question pools existed
+/// have no drawn set and fall back to the whole quiz.
+pub fn get_attempt_questions(conn: &Connection, attempt_id: &str) -> Result<Vec<Question>, String> {
+    let quiz_id: String = conn
+        .query_row(
+            "SELECT quiz_id FROM quiz_attempts WHERE id = ?1",
+            params![attempt_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Attempt not found: {}", e))?;
+
+    let order = get_attempt_question_order(conn, attempt_id)?;
+    let quiz_questions = get_questions_for_quiz(conn, &quiz_id)?;
+    if order.is_empty() {
+        return Ok(quiz_questions);
+    }
+
+    let mut by_id: HashMap<String, Question> = quiz_questions
+        .into_iter()
+        .map(|q| (q.id.clone(), q))
+        .collect();
+
+    let mut questions = Vec::with_capacity(order.len());
+    for entry in order {
+        // Questions deleted since the attempt started are skipped
+        let Some(mut question) = by_id.remove(&entry.question_id) else {
+            continue;
+        };
+        if !entry.choice_order.is_empty() {
+            question.choices.sort_by_key(|c| {
+                entry
+                    .choice_order
+                    .iter()
+                    .position(|id| *id == c.id)
+                    .unwrap_or(usize::MAX)
+            });
+        }
+        questions.push(question);
+    }
+
+    Ok(questions)
+}
+
 pub fn submit_quiz_attempt(
     conn: &Connection,
     attempt_id: &str,
     answers: &[QuestionAnswer],
 ) -> Result<QuizAttempt, String> {
     let now = chrono::Utc::now().to_rfc3339();
+    finish_attempt(conn, attempt_id, answers, &now)?;
+    get_quiz_attempt(conn, attempt_id)
+}
 
+/// Grade an open attempt as of `finished_at`.
+///
+/// Answers saved with `save_attempt_answer` are graded together with the ones
+/// submitted now; a submitted answer replaces a saved one for the same question.
+/// Any answer given after the deadline, or after its question's own limit ran
+/// out, is kept for review but earns no credit.
+fn finish_attempt(
+    conn: &Connection,
+    attempt_id: &str,
+    answers: &[QuestionAnswer],
+    finished_at: &str,
+) -> Result<(), String> {
     // Get attempt info
-    let (_quiz_id, started_at): (String, String) = conn
+    let (started_at, deadline, completed_at, abandoned_at, mode): (
+        String,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        String,
+    ) = conn
         .query_row(
-            "SELECT quiz_id, started_at FROM quiz_attempts WHERE id = ?1",
+            "SELECT started_at, deadline, completed_at, abandoned_at, mode
+             FROM quiz_attempts WHERE id = ?1",
             params![attempt_id],
-            |row| Ok((row.get(0)?, row.get(1)?)),
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
         )
         .map_err(|e| format!("Attempt not found: {}", e))?;
 
+    if completed_at.is_some() {
+        return Err("Attempt has already been submitted".to_string());
+    }
+    if abandoned_at.is_some() {
+        return Err("Attempt was abandoned".to_string());
+    }
+    if AttemptMode::parse(&mode) == AttemptMode::Adaptive {
+        return Err("Adaptive attempts are answered one question at a time".to_string());
+    }
+
     // Calculate duration
     let start = chrono::DateTime::parse_from_rfc3339(&started_at)
         .map_err(|e| format!("Invalid start time: {}", e))?;
-    let end = chrono::DateTime::parse_from_rfc3339(&now)
+    let end = chrono::DateTime::parse_from_rfc3339(finished_at)
         .map_err(|e| format!("Invalid end time: {}", e))?;
     let duration = (end - start).num_seconds() as i32;
+    let is_late = is_past_deadline(deadline.as_deref(), finished_at)?;
+
+    // Only questions drawn for this attempt may be answered
+    check_answers_belong_to_attempt(conn, attempt_id, answers.iter().map(|a| &a.question_id))?;
+
+    let mut graded: Vec<SavedAnswer> = get_saved_answers(conn, attempt_id)?;
+    for answer in answers {
+        match graded.iter_mut().find(|s| s.question_id == answer.question_id) {
+            Some(saved) => {
+                // Re-sending an unchanged answer keeps the time it was first saved
+                if saved.answer != answer.answer {
+                    saved.answer = answer.answer.clone();
+                    saved.answered_at = finished_at.to_string();
+                }
+                saved.time_spent_seconds = answer.time_spent_seconds.or(saved.time_spent_seconds);
+            }
+            None => graded.push(SavedAnswer {
+                question_id: answer.question_id.clone(),
+                answer: answer.answer.clone(),
+                answered_at: finished_at.to_string(),
+                time_spent_seconds: answer.time_spent_seconds,
+            }),
+        }
+    }
 
     // Begin transaction for atomic quiz submission
     conn.execute("BEGIN TRANSACTION", [])
@@ -70,8 +347,11 @@ pub fn submit_quiz_attempt(
     let result = (|| -> Result<(), String> {
         // Grade each answer
         let mut correct_count = 0;
-        for answer in answers {
-            let is_correct = grade_answer(conn, &answer.question_id, &answer.answer)?;
+        for answer in &graded {
+            let question_due = question_due_at(conn, attempt_id, &answer.question_id)?;
+            let on_time = !is_past_deadline(deadline.as_deref(), &answer.answered_at)?
+                && !is_past_deadline(question_due.as_deref(), &answer.answered_at)?;
+            let is_correct = on_time && grade_answer(conn, &answer.question_id, &answer.answer)?;
             if is_correct {
                 correct_count += 1;
             }
@@ -79,13 +359,28 @@ pub fn submit_quiz_attempt(
             // Save question result
             let result_id = Uuid::new_v4().to_string();
             conn.execute(
-                "INSERT INTO question_results (id, attempt_id, question_id, user_answer, is_correct)
-                 VALUES (?1, ?2, ?3, ?4, ?5)",
-                params![result_id, attempt_id, answer.question_id, answer.answer, is_correct as i32],
+                "INSERT INTO question_results (id, attempt_id, question_id, user_answer, is_correct,
+                 time_spent_seconds)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    result_id,
+                    attempt_id,
+                    answer.question_id,
+                    answer.answer,
+                    is_correct as i32,
+                    answer.time_spent_seconds
+                ],
             )
             .map_err(|e| format!("Failed to save question result: {}", e))?;
         }
 
+        // Provisional answers are superseded by the results
+        conn.execute(
+            "DELETE FROM attempt_answers WHERE attempt_id = ?1",
+            params![attempt_id],
+        )
+        .map_err(|e| format!("Failed to clear saved answers: {}", e))?;
+
         // Calculate score
         let total: i32 = conn
             .query_row(
@@ -104,8 +399,8 @@ pub fn submit_quiz_attempt(
         // Update attempt
         conn.execute(
             "UPDATE quiz_attempts SET completed_at = ?1, duration_seconds = ?2,
-             correct_answers = ?3, score_percentage = ?4 WHERE id = ?5",
-            params![now, duration, correct_count, score_percentage, attempt_id],
+             correct_answers = ?3, score_percentage = ?4, is_late = ?5 WHERE id = ?6",
+            params![finished_at, duration, correct_count, score_percentage, is_late as i32, attempt_id],
         )
         .map_err(|e| format!("Failed to complete attempt: {}", e))?;
 
@@ -117,16 +412,71 @@ pub fn submit_quiz_attempt(
         Ok(()) => {
             conn.execute("COMMIT", [])
                 .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            Ok(())
         }
         Err(e) => {
             if let Err(rollback_err) = conn.execute("ROLLBACK", []) {
                 eprintln!("Warning: Failed to rollback transaction: {}", rollback_err);
             }
-            return Err(e);
+            Err(e)
         }
     }
+}
 
-    get_quiz_attempt(conn, attempt_id)
+fn is_past_deadline(deadline: Option<&str>, at: &str) -> Result<bool, String> {
+    let Some(deadline) = deadline else {
+        return Ok(false);
+    };
+    let deadline = chrono::DateTime::parse_from_rfc3339(deadline)
+        .map_err(|e| format!("Invalid deadline: {}", e))?;
+    let at = chrono::DateTime::parse_from_rfc3339(at)
+        .map_err(|e| format!("Invalid answer time: {}", e))?;
+    Ok((at - deadline).num_seconds() > DEADLINE_GRACE_SECONDS)
+}
+
+/// When the answer to a timed question is due: its limit after the app first
+/// showed it. None when it has no limit or was never marked as shown.
+fn question_due_at(conn: &Connection, attempt_id: &str, question_id: &str) -> Result<Option<String>, String> {
+    let (shown_at, limit): (Option<String>, Option<i64>) = match conn.query_row(
+        "SELECT aq.shown_at, q.time_limit_seconds
+         FROM attempt_questions aq
+         INNER JOIN questions q ON q.id = aq.question_id
+         WHERE aq.attempt_id = ?1 AND aq.question_id = ?2",
+        params![attempt_id, question_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ) {
+        Ok(timing) => timing,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+        Err(e) => return Err(format!("Failed to get question timing: {}", e)),
+    };
+
+    let (Some(shown_at), Some(limit)) = (shown_at, limit) else {
+        return Ok(None);
+    };
+    let shown_at = chrono::DateTime::parse_from_rfc3339(&shown_at)
+        .map_err(|e| format!("Invalid shown time: {}", e))?;
+    Ok(Some((shown_at + chrono::Duration::seconds(limit)).to_rfc3339()))
+}
+
+fn check_answers_belong_to_attempt<'a>(
+    conn: &Connection,
+    attempt_id: &str,
+    question_ids: impl IntoIterator<Item = &'a String>,
+) -> Result<(), String> {
+    let drawn: HashSet<String> = get_attempt_question_order(conn, attempt_id)?
+        .into_iter()
+        .map(|q| q.question_id)
+        .collect();
+    if drawn.is_empty() {
+        return Ok(());
+    }
+
+    for question_id in question_ids {
+        if !drawn.contains(question_id) {
+            return Err(format!("Question {} is not part of this attempt", question_id));
+        }
+    }
+    Ok(())
 }
 
 fn grade_answer(conn: &Connection, question_id: &str, user_answer: &str) -> Result<bool, String> {
@@ -173,7 +523,8 @@ pub fn get_quiz_attempt(conn: &Connection, attempt_id: &str) -> Result<QuizAttem
     let mut stmt = conn
         .prepare(
             "SELECT id, quiz_id, started_at, completed_at, duration_seconds,
-             total_questions, correct_answers, score_percentage
+             total_questions, correct_answers, score_percentage, deadline, is_late,
+             abandoned_at, mode
              FROM quiz_attempts WHERE id = ?1",
         )
         .map_err(|e| format!("Failed to prepare query: {}", e))?;
@@ -189,14 +540,24 @@ pub fn get_quiz_attempt(conn: &Connection, attempt_id: &str) -> Result<QuizAttem
                 total_questions: row.get(5)?,
                 correct_answers: row.get(6)?,
                 score_percentage: row.get(7)?,
+                deadline: row.get(8)?,
+                is_late: row.get::<_, i32>(9)? != 0,
+                abandoned_at: row.get(10)?,
+                mode: AttemptMode::parse(&row.get::<_, String>(11)?),
+                questions: vec![],
+                saved_answers: vec![],
                 question_results: vec![],
             })
         })
         .map_err(|e| format!("Attempt not found: {}", e))?;
 
+    let questions = get_attempt_question_order(conn, attempt_id)?;
+    let saved_answers = get_saved_answers(conn, attempt_id)?;
     let results = get_question_results_for_attempt(conn, attempt_id)?;
 
     Ok(QuizAttempt {
+        questions,
+        saved_answers,
         question_results: results,
         ..attempt
     })
@@ -208,7 +569,7 @@ pub fn get_question_results_for_attempt(
 ) -> Result<Vec<QuestionResult>, String> {
     let mut stmt = conn
         .prepare(
-            "SELECT id, attempt_id, question_id, user_answer, is_correct
+            "SELECT id, attempt_id, question_id, user_answer, is_correct, time_spent_seconds
              FROM question_results WHERE attempt_id = ?1",
         )
         .map_err(|e| format!("Failed to prepare query: {}", e))?;
@@ -221,6 +582,7 @@ pub fn get_question_results_for_attempt(
                 question_id: row.get(2)?,
                 user_answer: row.get(3)?,
                 is_correct: row.get::<_, i32>(4)? != 0,
+                time_spent_seconds: row.get(5)?,
             })
         })
         .map_err(|e| format!("Failed to query results: {}", e))?
@@ -234,7 +596,8 @@ pub fn get_quiz_attempts(conn: &Connection, quiz_id: &str) -> Result<Vec<QuizAtt
     let mut stmt = conn
         .prepare(
             "SELECT id, quiz_id, started_at, completed_at, duration_seconds,
-             total_questions, correct_answers, score_percentage
+             total_questions, correct_answers, score_percentage, deadline, is_late,
+             abandoned_at, mode
              FROM quiz_attempts WHERE quiz_id = ?1 ORDER BY started_at DESC",
         )
         .map_err(|e| format!("Failed to prepare query: {}", e))?;
@@ -250,6 +613,12 @@ pub fn get_quiz_attempts(conn: &Connection, quiz_id: &str) -> Result<Vec<QuizAtt
                 total_questions: row.get(5)?,
                 correct_answers: row.get(6)?,
                 score_percentage: row.get(7)?,
+                deadline: row.get(8)?,
+                is_late: row.get::<_, i32>(9)? != 0,
+                abandoned_at: row.get(10)?,
+                mode: AttemptMode::parse(&row.get::<_, String>(11)?),
+                questions: vec![],
+                saved_answers: vec![],
                 question_results: vec![],
             })
         })
@@ -281,7 +650,7 @@ pub fn get_quiz_stats(conn: &Connection, quiz_id: &str) -> Result<QuizStats, Str
                 AVG(duration_seconds),
                 MAX(completed_at)
              FROM quiz_attempts
-             WHERE quiz_id = ?1 AND completed_at IS NOT NULL",
+             WHERE quiz_id = ?1 AND completed_at IS NOT NULL AND mode = 'standard'",
             params![quiz_id],
             |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
         )
@@ -291,7 +660,7 @@ pub fn get_quiz_stats(conn: &Connection, quiz_id: &str) -> Result<QuizStats, Str
     let mut stmt = conn
         .prepare(
             "SELECT score_percentage FROM quiz_attempts
-             WHERE quiz_id = ?1 AND completed_at IS NOT NULL
+             WHERE quiz_id = ?1 AND completed_at IS NOT NULL AND mode = 'standard'
              ORDER BY completed_at DESC LIMIT 5",
         )
         .map_err(|e| format!("Failed to prepare query: {}", e))?;
@@ -313,6 +682,681 @@ pub fn get_quiz_stats(conn: &Connection, quiz_id: &str) -> Result<QuizStats, Str
     })
 }
 
+/// Item analysis for a quiz, computed from the results of completed standard attempts.
+///
+/// Only answered questions count; a question left blank in an attempt does not
+/// contribute to that question's statistics. Discrimination is the point-biserial
+/// correlation between getting the question right and the attempt's score on
+/// the other questions, so the question does not count towards its own index.
+pub fn get_quiz_item_analysis(conn: &Connection, quiz_id: &str) -> Result<QuizItemAnalysis, String> {
+    let questions = get_questions_for_quiz(conn, quiz_id)?;
+
+    let attempts_analyzed: i32 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM quiz_attempts
+             WHERE quiz_id = ?1 AND completed_at IS NOT NULL AND mode = 'standard'",
+            params![quiz_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to count attempts: {}", e))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT r.question_id, r.user_answer, r.is_correct, r.time_spent_seconds,
+                    a.correct_answers, a.total_questions
+             FROM question_results r
+             INNER JOIN quiz_attempts a ON a.id = r.attempt_id
+             WHERE a.quiz_id = ?1 AND a.completed_at IS NOT NULL AND a.mode = 'standard'",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    struct ResultRow {
+        user_answer: Option<String>,
+        is_correct: bool,
+        time_spent_seconds: Option<i32>,
+        attempt_correct: i32,
+        attempt_total: i32,
+    }
+
+    let mut rows_by_question: HashMap<String, Vec<ResultRow>> = HashMap::new();
+    let rows = stmt
+        .query_map(params![quiz_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                ResultRow {
+                    user_answer: row.get(1)?,
+                    is_correct: row.get::<_, i32>(2)? != 0,
+                    time_spent_seconds: row.get(3)?,
+                    attempt_correct: row.get(4)?,
+                    attempt_total: row.get(5)?,
+                },
+            ))
+        })
+        .map_err(|e| format!("Failed to query results: {}", e))?;
+    for row in rows {
+        let (question_id, result) = row.map_err(|e| format!("Failed to read result: {}", e))?;
+        rows_by_question.entry(question_id).or_default().push(result);
+    }
+
+    let mut item_stats = Vec::with_capacity(questions.len());
+    for question in questions {
+        let results = rows_by_question.remove(&question.id).unwrap_or_default();
+        let times_answered = results.len() as i32;
+        let times_correct = results.iter().filter(|r| r.is_correct).count() as i32;
+        let percent_correct = if times_answered > 0 {
+            times_correct as f64 / times_answered as f64 * 100.0
+        } else {
+            0.0
+        };
+
+        let times: Vec<i32> = results.iter().filter_map(|r| r.time_spent_seconds).collect();
+        let average_time_seconds = if times.is_empty() {
+            None
+        } else {
+            Some(times.iter().map(|&t| t as f64).sum::<f64>() / times.len() as f64)
+        };
+
+        // Share of the attempt's other questions answered correctly
+        let scored: Vec<(bool, f64)> = results
+            .iter()
+            .filter(|r| r.attempt_total > 1)
+            .map(|r| {
+                let rest_correct = r.attempt_correct - r.is_correct as i32;
+                (r.is_correct, rest_correct as f64 / (r.attempt_total - 1) as f64)
+            })
+            .collect();
+
+        // Multiple-choice answers are comma-separated choice IDs
+        let choices = question
+            .choices
+            .iter()
+            .map(|choice| {
+                let times_selected = results
+                    .iter()
+                    .filter(|r| {
+                        r.user_answer
+                            .as_deref()
+                            .is_some_and(|a| a.split(',').any(|id| id.trim() == choice.id))
+                    })
+                    .count() as i32;
+                ChoiceSelectionStats {
+                    choice_id: choice.id.clone(),
+                    text: choice.text.clone(),
+                    is_correct: choice.is_correct,
+                    times_selected,
+                    selection_rate: if times_answered > 0 {
+                        times_selected as f64 / times_answered as f64
+                    } else {
+                        0.0
+                    },
+                }
+            })
+            .collect();
+
+        item_stats.push(QuestionItemStats {
+            question_id: question.id,
+            content: question.content,
+            times_answered,
+            times_correct,
+            percent_correct,
+            average_time_seconds,
+            discrimination_index: point_biserial(&scored),
+            choices,
+        });
+    }
+
+    let mut missed: Vec<&QuestionItemStats> = item_stats
+        .iter()
+        .filter(|q| q.times_answered > q.times_correct)
+        .collect();
+    missed.sort_by(|a, b| {
+        (b.times_answered - b.times_correct)
+            .cmp(&(a.times_answered - a.times_correct))
+            .then(a.percent_correct.total_cmp(&b.percent_correct))
+    });
+    let most_missed = missed.into_iter().map(|q| q.question_id.clone()).collect();
+
+    Ok(QuizItemAnalysis {
+        quiz_id: quiz_id.to_string(),
+        attempts_analyzed,
+        questions: item_stats,
+        most_missed,
+    })
+}
+
+/// Point-biserial correlation between a right/wrong item and a score.
+/// Undefined when everyone (or no one) got the item right or all scores match.
+fn point_biserial(scored: &[(bool, f64)]) -> Option<f64> {
+    let n = scored.len() as f64;
+    let right: Vec<f64> = scored.iter().filter(|(c, _)| *c).map(|(_, s)| *s).collect();
+    let wrong: Vec<f64> = scored.iter().filter(|(c, _)| !*c).map(|(_, s)| *s).collect();
+    if right.is_empty() || wrong.is_empty() {
+        return None;
+    }
+
+    let mean = scored.iter().map(|(_, s)| s).sum::<f64>() / n;
+    let std_dev = (scored.iter().map(|(_, s)| (s - mean).powi(2)).sum::<f64>() / n).sqrt();
+    if std_dev == 0.0 {
+        return None;
+    }
+
+    let mean_right = right.iter().sum::<f64>() / right.len() as f64;
+    let mean_wrong = wrong.iter().sum::<f64>() / wrong.len() as f64;
+    let p = right.len() as f64 / n;
+
+    Some((mean_right - mean_wrong) / std_dev * (p * (1.0 - p)).sqrt())
+}
+
+// ============================================
+// In-Progress Attempt Operations
+// ============================================
+
+/// Attempts left open longer than this are closed by `cleanup_stale_attempts`
+pub const STALE_ATTEMPT_HOURS: i64 = 24;
+
+/// Upsert a provisional answer for an open attempt
+pub fn save_attempt_answer(
+    conn: &Connection,
+    attempt_id: &str,
+    answer: &QuestionAnswer,
+) -> Result<SavedAnswer, String> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let (deadline, completed_at, abandoned_at, mode): (
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        String,
+    ) = conn
+        .query_row(
+            "SELECT deadline, completed_at, abandoned_at, mode FROM quiz_attempts WHERE id = ?1",
+            params![attempt_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|e| format!("Attempt not found: {}", e))?;
+
+    if completed_at.is_some() {
+        return Err("Attempt has already been submitted".to_string());
+    }
+    if abandoned_at.is_some() {
+        return Err("Attempt was abandoned".to_string());
+    }
+    if AttemptMode::parse(&mode) == AttemptMode::Adaptive {
+        return Err("Adaptive attempts are answered one question at a time".to_string());
+    }
+    if is_past_deadline(deadline.as_deref(), &now)? {
+        return Err("Time is up for this attempt".to_string());
+    }
+
+    check_answers_belong_to_attempt(conn, attempt_id, [&answer.question_id])?;
+    if is_past_deadline(question_due_at(conn, attempt_id, &answer.question_id)?.as_deref(), &now)? {
+        return Err("Time is up for this question".to_string());
+    }
+
+    conn.execute(
+        "INSERT INTO attempt_answers (attempt_id, question_id, answer, answered_at,
+         time_spent_seconds)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(attempt_id, question_id)
+         DO UPDATE SET answer = excluded.answer, answered_at = excluded.answered_at,
+         time_spent_seconds = excluded.time_spent_seconds",
+        params![attempt_id, answer.question_id, answer.answer, now, answer.time_spent_seconds],
+    )
+    .map_err(|e| format!("Failed to save answer: {}", e))?;
+
+    Ok(SavedAnswer {
+        question_id: answer.question_id.clone(),
+        answer: answer.answer.clone(),
+        answered_at: now,
+        time_spent_seconds: answer.time_spent_seconds,
+    })
+}
+
+/// Record that the app is showing a question of an open attempt, starting the
+/// question's own time limit. Showing it again keeps the first time. Answers
+/// to a timed question should be saved as they are given, since one sent only
+/// with the submission counts as given then.
+pub fn mark_question_shown(conn: &Connection, attempt_id: &str, question_id: &str) -> Result<String, String> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let (deadline, completed_at, abandoned_at): (Option<String>, Option<String>, Option<String>) = conn
+        .query_row(
+            "SELECT deadline, completed_at, abandoned_at FROM quiz_attempts WHERE id = ?1",
+            params![attempt_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| format!("Attempt not found: {}", e))?;
+
+    if completed_at.is_some() {
+        return Err("Attempt has already been submitted".to_string());
+    }
+    if abandoned_at.is_some() {
+        return Err("Attempt was abandoned".to_string());
+    }
+    if is_past_deadline(deadline.as_deref(), &now)? {
+        return Err("Time is up for this attempt".to_string());
+    }
+
+    check_answers_belong_to_attempt(conn, attempt_id, [&question_id.to_string()])?;
+
+    conn.execute(
+        "UPDATE attempt_questions SET shown_at = COALESCE(shown_at, ?1)
+         WHERE attempt_id = ?2 AND question_id = ?3",
+        params![now, attempt_id, question_id],
+    )
+    .map_err(|e| format!("Failed to mark question as shown: {}", e))?;
+
+    conn.query_row(
+        "SELECT shown_at FROM attempt_questions WHERE attempt_id = ?1 AND question_id = ?2",
+        params![attempt_id, question_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Failed to get shown time: {}", e))
+}
+
+pub fn get_saved_answers(conn: &Connection, attempt_id: &str) -> Result<Vec<SavedAnswer>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT question_id, answer, answered_at, time_spent_seconds
+             FROM attempt_answers WHERE attempt_id = ?1 ORDER BY answered_at",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let answers = stmt
+        .query_map(params![attempt_id], |row| {
+            Ok(SavedAnswer {
+                question_id: row.get(0)?,
+                answer: row.get(1)?,
+                answered_at: row.get(2)?,
+                time_spent_seconds: row.get(3)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query saved answers: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect saved answers: {}", e))?;
+
+    Ok(answers)
+}
+
+/// Open attempts on the user's quizzes, newest first
+pub fn get_in_progress_attempts(conn: &Connection, user_id: &str) -> Result<Vec<QuizAttempt>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT a.id FROM quiz_attempts a
+             INNER JOIN quizzes q ON q.id = a.quiz_id
+             WHERE q.user_id = ?1 AND a.completed_at IS NULL AND a.abandoned_at IS NULL
+             ORDER BY a.started_at DESC",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let attempt_ids: Vec<String> = stmt
+        .query_map(params![user_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to query attempts: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect attempts: {}", e))?;
+
+    attempt_ids
+        .iter()
+        .map(|id| get_quiz_attempt(conn, id))
+        .collect()
+}
+
+/// Pick an open attempt back up. An attempt whose deadline passed while the
+/// app was closed is graded with the answers saved before it ran out.
+pub fn resume_quiz_attempt(conn: &Connection, attempt_id: &str) -> Result<QuizAttempt, String> {
+    let attempt = get_quiz_attempt(conn, attempt_id)?;
+
+    if attempt.completed_at.is_some() {
+        return Err("Attempt has already been submitted".to_string());
+    }
+    if attempt.abandoned_at.is_some() {
+        return Err("Attempt was abandoned".to_string());
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    if is_past_deadline(attempt.deadline.as_deref(), &now)? {
+        finish_attempt(conn, attempt_id, &[], &now)?;
+        return get_quiz_attempt(conn, attempt_id);
+    }
+
+    Ok(attempt)
+}
+
+/// Close attempts that were left open: past their deadline, or started more
+/// than `max_age_hours` ago. Attempts with saved answers are graded as of the
+/// last answer; empty ones are marked abandoned.
+pub fn cleanup_stale_attempts(
+    conn: &Connection,
+    max_age_hours: i64,
+) -> Result<AttemptCleanupResult, String> {
+    let now = chrono::Utc::now();
+    let now_str = now.to_rfc3339();
+    let cutoff = now - chrono::Duration::hours(max_age_hours);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT a.id, a.started_at, a.deadline, MAX(aa.answered_at)
+             FROM quiz_attempts a
+             LEFT JOIN attempt_answers aa ON aa.attempt_id = a.id
+             WHERE a.completed_at IS NULL AND a.abandoned_at IS NULL
+             GROUP BY a.id",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let open_attempts: Vec<(String, String, Option<String>, Option<String>)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+        .map_err(|e| format!("Failed to query attempts: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect attempts: {}", e))?;
+
+    let mut result = AttemptCleanupResult {
+        graded_attempts: 0,
+        abandoned_attempts: 0,
+    };
+
+    for (attempt_id, started_at, deadline, last_answered_at) in open_attempts {
+        let started = chrono::DateTime::parse_from_rfc3339(&started_at)
+            .map_err(|e| format!("Invalid start time: {}", e))?;
+        let is_stale = started < cutoff || is_past_deadline(deadline.as_deref(), &now_str)?;
+        if !is_stale {
+            continue;
+        }
+
+        match last_answered_at {
+            Some(finished_at) => {
+                finish_attempt(conn, &attempt_id, &[], &finished_at)?;
+                result.graded_attempts += 1;
+            }
+            None => {
+                conn.execute(
+                    "UPDATE quiz_attempts SET abandoned_at = ?1 WHERE id = ?2",
+                    params![now_str, attempt_id],
+                )
+                .map_err(|e| format!("Failed to abandon attempt: {}", e))?;
+                result.abandoned_attempts += 1;
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+// ============================================
+// Retry & Adaptive Attempt Operations
+// ============================================
+
+/// Correct answers an adaptive attempt needs before it stops re-queuing a question
+pub const ADAPTIVE_CORRECT_TARGET: i32 = 2;
+
+/// Start a follow-up attempt made only of questions answered incorrectly,
+/// either in one attempt or across the most recent completed attempts.
+pub fn start_retry_attempt(conn: &Connection, request: &RetryQuizRequest) -> Result<QuizAttempt, String> {
+    let recent_attempts = request.recent_attempts.unwrap_or(1);
+    if recent_attempts < 1 {
+        return Err("Must retry at least 1 attempt".to_string());
+    }
+
+    let quiz = get_quiz(conn, &request.quiz_id)?;
+    let missed = get_missed_question_ids(
+        conn,
+        &request.quiz_id,
+        request.attempt_id.as_deref(),
+        recent_attempts,
+    )?;
+
+    let chosen: Vec<&Question> = quiz
+        .questions
+        .iter()
+        .filter(|q| missed.contains(&q.id))
+        .collect();
+    if chosen.is_empty() {
+        return Err("No missed questions to retry".to_string());
+    }
+
+    let mode = if request.adaptive.unwrap_or(false) {
+        AttemptMode::Adaptive
+    } else {
+        AttemptMode::Retry
+    };
+
+    // Follow-up practice is untimed
+    let questions = order_attempt_questions(&quiz, chosen);
+    insert_attempt(conn, &quiz.id, questions, None, mode)
+}
+
+fn get_missed_question_ids(
+    conn: &Connection,
+    quiz_id: &str,
+    attempt_id: Option<&str>,
+    recent_attempts: i32,
+) -> Result<HashSet<String>, String> {
+    let ids = match attempt_id {
+        Some(attempt_id) => {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT DISTINCT r.question_id FROM question_results r
+                     INNER JOIN quiz_attempts a ON a.id = r.attempt_id
+                     WHERE r.attempt_id = ?1 AND a.quiz_id = ?2 AND r.is_correct = 0",
+                )
+                .map_err(|e| format!("Failed to prepare query: {}", e))?;
+            let ids = stmt
+                .query_map(params![attempt_id, quiz_id], |row| row.get(0))
+                .map_err(|e| format!("Failed to query missed questions: {}", e))?
+                .collect::<Result<HashSet<String>, _>>();
+            ids
+        }
+        None => {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT DISTINCT question_id FROM question_results
+                     WHERE is_correct = 0 AND attempt_id IN (
+                         SELECT id FROM quiz_attempts
+                         WHERE quiz_id = ?1 AND completed_at IS NOT NULL
+                         ORDER BY completed_at DESC LIMIT ?2
+                     )",
+                )
+                .map_err(|e| format!("Failed to prepare query: {}", e))?;
+            let ids = stmt
+                .query_map(params![quiz_id, recent_attempts], |row| row.get(0))
+                .map_err(|e| format!("Failed to query missed questions: {}", e))?
+                .collect::<Result<HashSet<String>, _>>();
+            ids
+        }
+    };
+
+    ids.map_err(|e| format!("Failed to collect missed questions: {}", e))
+}
+
+/// Grade one answer of an adaptive attempt. A question that has not yet been
+/// answered correctly `ADAPTIVE_CORRECT_TARGET` times goes to the back of the
+/// queue; the attempt completes once every question reaches the target.
+/// The score counts questions answered correctly on the first try.
+pub fn answer_adaptive_question(
+    conn: &Connection,
+    attempt_id: &str,
+    answer: &QuestionAnswer,
+) -> Result<AdaptiveProgress, String> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let (started_at, completed_at, abandoned_at, mode): (
+        String,
+        Option<String>,
+        Option<String>,
+        String,
+    ) = conn
+        .query_row(
+            "SELECT started_at, completed_at, abandoned_at, mode FROM quiz_attempts WHERE id = ?1",
+            params![attempt_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .map_err(|e| format!("Attempt not found: {}", e))?;
+
+    if AttemptMode::parse(&mode) != AttemptMode::Adaptive {
+        return Err("Attempt is not adaptive".to_string());
+    }
+    if completed_at.is_some() {
+        return Err("Attempt has already been completed".to_string());
+    }
+    if abandoned_at.is_some() {
+        return Err("Attempt was abandoned".to_string());
+    }
+
+    let correct_counts = get_adaptive_correct_counts(conn, attempt_id)?;
+    let Some(&(previous_answers, previous_correct)) = correct_counts.get(&answer.question_id) else {
+        return Err(format!("Question {} is not part of this attempt", answer.question_id));
+    };
+    if previous_correct >= ADAPTIVE_CORRECT_TARGET {
+        return Err("Question has already been mastered".to_string());
+    }
+
+    let is_correct = grade_answer(conn, &answer.question_id, &answer.answer)?;
+
+    conn.execute("BEGIN TRANSACTION", [])
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+
+    let result = (|| -> Result<(), String> {
+        let result_id = Uuid::new_v4().to_string();
+        conn.execute(
+            "INSERT INTO question_results (id, attempt_id, question_id, user_answer, is_correct,
+             time_spent_seconds)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                result_id,
+                attempt_id,
+                answer.question_id,
+                answer.answer,
+                is_correct as i32,
+                answer.time_spent_seconds
+            ],
+        )
+        .map_err(|e| format!("Failed to save question result: {}", e))?;
+
+        if is_correct && previous_answers == 0 {
+            conn.execute(
+                "UPDATE quiz_attempts SET correct_answers = correct_answers + 1 WHERE id = ?1",
+                params![attempt_id],
+            )
+            .map_err(|e| format!("Failed to update attempt: {}", e))?;
+        }
+
+        let mastered = is_correct && previous_correct + 1 >= ADAPTIVE_CORRECT_TARGET;
+        if !mastered {
+            conn.execute(
+                "UPDATE attempt_questions SET position = (
+                     SELECT MAX(position) + 1 FROM attempt_questions WHERE attempt_id = ?1
+                 ) WHERE attempt_id = ?1 AND question_id = ?2",
+                params![attempt_id, answer.question_id],
+            )
+            .map_err(|e| format!("Failed to re-queue question: {}", e))?;
+        }
+
+        let remaining = correct_counts
+            .iter()
+            .filter(|(id, (_, correct))| {
+                *id != &answer.question_id && *correct < ADAPTIVE_CORRECT_TARGET
+            })
+            .count();
+        if mastered && remaining == 0 {
+            let start = chrono::DateTime::parse_from_rfc3339(&started_at)
+                .map_err(|e| format!("Invalid start time: {}", e))?;
+            let end = chrono::DateTime::parse_from_rfc3339(&now)
+                .map_err(|e| format!("Invalid end time: {}", e))?;
+            let duration = (end - start).num_seconds() as i32;
+
+            conn.execute(
+                "UPDATE quiz_attempts SET completed_at = ?1, duration_seconds = ?2,
+                 score_percentage = CASE WHEN total_questions > 0
+                     THEN ROUND(correct_answers * 100.0 / total_questions) ELSE 0 END
+                 WHERE id = ?3",
+                params![now, duration, attempt_id],
+            )
+            .map_err(|e| format!("Failed to complete attempt: {}", e))?;
+        }
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => {
+            conn.execute("COMMIT", [])
+                .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+        }
+        Err(e) => {
+            if let Err(rollback_err) = conn.execute("ROLLBACK", []) {
+                eprintln!("Warning: Failed to rollback transaction: {}", rollback_err);
+            }
+            return Err(e);
+        }
+    }
+
+    let progress = get_adaptive_progress(conn, attempt_id)?;
+    Ok(AdaptiveProgress {
+        last_answer_correct: Some(is_correct),
+        ..progress
+    })
+}
+
+pub fn get_adaptive_progress(conn: &Connection, attempt_id: &str) -> Result<AdaptiveProgress, String> {
+    let completed_at: Option<String> = conn
+        .query_row(
+            "SELECT completed_at FROM quiz_attempts WHERE id = ?1",
+            params![attempt_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Attempt not found: {}", e))?;
+
+    let correct_counts = get_adaptive_correct_counts(conn, attempt_id)?;
+    let mastered = correct_counts
+        .values()
+        .filter(|(_, correct)| *correct >= ADAPTIVE_CORRECT_TARGET)
+        .count() as i32;
+
+    // The queue is the attempt's question order; re-queued questions sit at the back
+    let next_question_id = get_attempt_question_order(conn, attempt_id)?
+        .into_iter()
+        .map(|q| q.question_id)
+        .find(|id| {
+            correct_counts
+                .get(id)
+                .is_some_and(|(_, correct)| *correct < ADAPTIVE_CORRECT_TARGET)
+        });
+
+    Ok(AdaptiveProgress {
+        attempt_id: attempt_id.to_string(),
+        last_answer_correct: None,
+        next_question_id,
+        mastered_questions: mastered,
+        total_questions: correct_counts.len() as i32,
+        completed: completed_at.is_some(),
+    })
+}
+
+/// (answers given, correct answers) for each question of an attempt
+fn get_adaptive_correct_counts(
+    conn: &Connection,
+    attempt_id: &str,
+) -> Result<HashMap<String, (i32, i32)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT aq.question_id, COUNT(r.id), COALESCE(SUM(r.is_correct), 0)
+             FROM attempt_questions aq
+             LEFT JOIN question_results r
+                 ON r.attempt_id = aq.attempt_id AND r.question_id = aq.question_id
+             WHERE aq.attempt_id = ?1
+             GROUP BY aq.question_id",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let counts = stmt
+        .query_map(params![attempt_id], |row| {
+            Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?)))
+        })
+        .map_err(|e| format!("Failed to query answer counts: {}", e))?
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| format!("Failed to collect answer counts: {}", e))?;
+
+    Ok(counts)
+}
+
 // ============================================
 // Study Session Operations
 // ============================================
@@ -368,7 +1412,10 @@ pub fn end_study_session(
     )
     .map_err(|e| format!("Failed to end study session: {}", e))?;
 
-    // Get updated session
+    get_study_session(conn, session_id)
+}
+
+pub fn get_study_session(conn: &Connection, session_id: &str) -> Result<StudySession, String> {
     let mut stmt = conn
         .prepare(
             "SELECT id, deck_id, started_at, ended_at, duration_seconds, cards_studied
@@ -0,0 +1,600 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, ErrorCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as JsonValue};
+use uuid::Uuid;
+
+use super::courses::delete_course;
+use super::decks::{delete_card, delete_deck};
+use super::links::link_new_page;
+use super::models::{TrashItem, TrashItemType, TrashRestoreResult};
+use super::notebooks::{delete_notebook, delete_page, get_page, promote_child_pages};
+use super::quizzes::delete_quiz;
+
+pub const DEFAULT_TRASH_RETENTION_DAYS: i64 = 30;
+const TRASH_RETENTION_KEY: &str = "trash_retention_days";
+const MAX_CARD_NAME_CHARS: usize = 80;
+
+/// Every table a delete removes rows from, parent tables first, each with the
+/// condition selecting the rows beneath the deleted item (`?1`)
+const DECK_TABLES: &[(&str, &str)] = &[
+    ("decks", "id = ?1"),
+    ("tags", "deck_id = ?1"),
+    ("cards", "deck_id = ?1"),
+    ("card_tags", "card_id IN (SELECT id FROM cards WHERE deck_id = ?1)"),
+    ("study_sessions", "deck_id = ?1"),
+    ("deck_favorites", "deck_id = ?1"),
+];
+const CARD_TABLES: &[(&str, &str)] = &[("cards", "id = ?1"), ("card_tags", "card_id = ?1")];
+const QUIZ_TABLES: &[(&str, &str)] = &[
+    ("quizzes", "id = ?1"),
+    ("quiz_tags", "quiz_id = ?1"),
+    ("questions", "quiz_id = ?1"),
+    ("choices", "question_id IN (SELECT id FROM questions WHERE quiz_id = ?1)"),
+    ("question_tags", "question_id IN (SELECT id FROM questions WHERE quiz_id = ?1)"),
+    ("quiz_pool_rules", "quiz_id = ?1"),
+    ("quiz_attempts", "quiz_id = ?1"),
+    ("attempt_questions", "attempt_id IN (SELECT id FROM quiz_attempts WHERE quiz_id = ?1)"),
+    ("attempt_answers", "attempt_id IN (SELECT id FROM quiz_attempts WHERE quiz_id = ?1)"),
+    ("question_results", "attempt_id IN (SELECT id FROM quiz_attempts WHERE quiz_id = ?1)"),
+    ("quiz_favorites", "quiz_id = ?1"),
+];
+const COURSE_TABLES: &[(&str, &str)] = &[
+    ("courses", "id = ?1"),
+    ("elective_groups", "course_id = ?1"),
+    ("lessons", "course_id = ?1"),
+    ("lesson_prerequisites", "lesson_id IN (SELECT id FROM lessons WHERE course_id = ?1)"),
+    ("lesson_items", "lesson_id IN (SELECT id FROM lessons WHERE course_id = ?1)"),
+    ("lesson_progress", "course_id = ?1"),
+    ("course_enrollments", "course_id = ?1"),
+    ("course_favorites", "course_id = ?1"),
+    ("certificates", "course_id = ?1"),
+];
+const NOTEBOOK_TABLES: &[(&str, &str)] = &[
+    ("notebooks", "id = ?1"),
+    ("pages", "notebook_id = ?1"),
+    ("page_reads", "page_id IN (SELECT id FROM pages WHERE notebook_id = ?1)"),
+    ("page_revisions", "page_id IN (SELECT id FROM pages WHERE notebook_id = ?1)"),
+    ("notebook_favorites", "notebook_id = ?1"),
+];
+/// The page `?1` and every page nested under it
+macro_rules! page_subtree {
+    () => {
+        "(WITH RECURSIVE subtree(id) AS (
+             SELECT ?1 UNION ALL SELECT p.id FROM pages p JOIN subtree s ON p.parent_page_id = s.id
+         ) SELECT id FROM subtree)"
+    };
+}
+const PAGE_TABLES: &[(&str, &str)] = &[
+    ("pages", concat!("id IN ", page_subtree!())),
+    ("page_reads", concat!("page_id IN ", page_subtree!())),
+    ("page_revisions", concat!("page_id IN ", page_subtree!())),
+];
+
+/// Tables whose rows outlive the delete with their link cleared; restoring
+/// puts the link back rather than adding the row again
+const KEPT_ON_DELETE: &[&str] = &["certificates"];
+
+fn subtree_tables(item_type: TrashItemType) -> &'static [(&'static str, &'static str)] {
+    match item_type {
+        TrashItemType::Deck => DECK_TABLES,
+        TrashItemType::Card => CARD_TABLES,
+        TrashItemType::Quiz => QUIZ_TABLES,
+        TrashItemType::Course => COURSE_TABLES,
+        TrashItemType::Notebook => NOTEBOOK_TABLES,
+        TrashItemType::Page => PAGE_TABLES,
+    }
+}
+
+/// The rows of one table, column by column
+#[derive(Serialize, Deserialize)]
+struct SnapshotTable {
+    table: String,
+    rows: Vec<Map<String, JsonValue>>,
+}
+
+/// What a trashed item is called and where it lived
+struct TrashedRoot {
+    name: String,
+    parent_id: Option<String>,
+}
+
+// ============================================
+// Moving to Trash
+// ============================================
+
+/// Delete an item, keeping it and everything deleted with it in the trash
+pub fn move_to_trash(
+    conn: &Connection,
+    user_id: &str,
+    item_type: TrashItemType,
+    item_id: &str,
+) -> Result<TrashItem, String> {
+    in_transaction(conn, || save_to_trash(conn, user_id, item_type, item_id))
+}
+
+/// Delete a page, with its sub-pages unless `promote_children` first moves
+/// them up to take its place
+pub fn move_page_to_trash(
+    conn: &Connection,
+    user_id: &str,
+    page_id: &str,
+    promote_children: bool,
+) -> Result<TrashItem, String> {
+    in_transaction(conn, || {
+        if promote_children {
+            get_owned_root(conn, user_id, TrashItemType::Page, page_id)?;
+            promote_child_pages(conn, page_id)?;
+        }
+        save_to_trash(conn, user_id, TrashItemType::Page, page_id)
+    })
+}
+
+/// Run `work` in a transaction, or in the caller's when one is already open
+fn in_transaction<T>(conn: &Connection, work: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    if !conn.is_autocommit() {
+        return work();
+    }
+
+    conn.execute("BEGIN TRANSACTION", [])
+        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+
+    match work() {
+        Ok(value) => {
+            conn.execute("COMMIT", [])
+                .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+            Ok(value)
+        }
+        Err(e) => {
+            if let Err(rollback_err) = conn.execute("ROLLBACK", []) {
+                eprintln!("Warning: Failed to rollback transaction: {}", rollback_err);
+            }
+            Err(e)
+        }
+    }
+}
+
+fn save_to_trash(
+    conn: &Connection,
+    user_id: &str,
+    item_type: TrashItemType,
+    item_id: &str,
+) -> Result<TrashItem, String> {
+    let root = get_owned_root(conn, user_id, item_type, item_id)?;
+
+    let mut snapshot = Vec::new();
+    for (table, condition) in subtree_tables(item_type) {
+        let mut captured = capture_rows(conn, table, condition, item_id)?;
+        if captured.table == "pages" {
+            order_parent_pages_first(&mut captured.rows);
+        }
+        snapshot.push(captured);
+    }
+    let snapshot = serde_json::to_string(&snapshot)
+        .map_err(|e| format!("Failed to serialize deleted content: {}", e))?;
+
+    match item_type {
+        TrashItemType::Deck => delete_deck(conn, user_id, item_id)?,
+        TrashItemType::Card => delete_card(conn, item_id, root.parent_id.as_deref().unwrap_or_default())?,
+        TrashItemType::Quiz => delete_quiz(conn, user_id, item_id)?,
+        TrashItemType::Course => delete_course(conn, user_id, item_id)?,
+        TrashItemType::Notebook => delete_notebook(conn, user_id, item_id)?,
+        TrashItemType::Page => delete_page(conn, item_id)?,
+    }
+
+    let id = Uuid::new_v4().to_string();
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO trash (id, user_id, item_type, item_id, name, parent_id, snapshot, deleted_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![id, user_id, item_type.as_str(), item_id, root.name, root.parent_id, snapshot, now],
+    )
+    .map_err(|e| format!("Failed to move to trash: {}", e))?;
+
+    get_trash_item(conn, user_id, &id)?.ok_or_else(|| "Failed to retrieve trash item".to_string())
+}
+
+/// The item's name and parent, if it belongs to the user
+fn get_owned_root(
+    conn: &Connection,
+    user_id: &str,
+    item_type: TrashItemType,
+    item_id: &str,
+) -> Result<TrashedRoot, String> {
+    let sql = match item_type {
+        TrashItemType::Deck => "SELECT name, NULL FROM decks WHERE id = ?1 AND user_id = ?2",
+        TrashItemType::Card => {
+            "SELECT c.front, c.deck_id FROM cards c JOIN decks d ON d.id = c.deck_id
+             WHERE c.id = ?1 AND d.user_id = ?2"
+        }
+        TrashItemType::Quiz => "SELECT name, NULL FROM quizzes WHERE id = ?1 AND user_id = ?2",
+        TrashItemType::Course => "SELECT name, NULL FROM courses WHERE id = ?1 AND user_id = ?2",
+        TrashItemType::Notebook => "SELECT name, NULL FROM notebooks WHERE id = ?1 AND user_id = ?2",
+        TrashItemType::Page => {
+            "SELECT p.title, p.notebook_id FROM pages p JOIN notebooks n ON n.id = p.notebook_id
+             WHERE p.id = ?1 AND n.user_id = ?2"
+        }
+    };
+
+    match conn.query_row(sql, params![item_id, user_id], |row| {
+        Ok(TrashedRoot {
+            name: row.get(0)?,
+            parent_id: row.get(1)?,
+        })
+    }) {
+        Ok(mut root) => {
+            if item_type == TrashItemType::Card {
+                let front = root.name.trim();
+                root.name = match front.char_indices().nth(MAX_CARD_NAME_CHARS) {
+                    Some((end, _)) => format!("{}…", &front[..end]),
+                    None => front.to_string(),
+                };
+            }
+            Ok(root)
+        }
+        Err(rusqlite::Error::QueryReturnedNoRows) => {
+            Err(format!("{} not found or access denied", capitalize(item_type.as_str())))
+        }
+        Err(e) => Err(format!("Database error: {}", e)),
+    }
+}
+
+fn capture_rows(conn: &Connection, table: &str, condition: &str, item_id: &str) -> Result<SnapshotTable, String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT * FROM {} WHERE {}", table, condition))
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+
+    let rows = stmt
+        .query_map(params![item_id], |row| {
+            let mut values = Map::new();
+            for (i, column) in columns.iter().enumerate() {
+                values.insert(column.clone(), to_json(row.get::<_, Value>(i)?));
+            }
+            Ok(values)
+        })
+        .map_err(|e| format!("Failed to read {}: {}", table, e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read {}: {}", table, e))?;
+
+    Ok(SnapshotTable {
+        table: table.to_string(),
+        rows,
+    })
+}
+
+/// Sort page rows so each comes after its parent and can be inserted in order
+fn order_parent_pages_first(rows: &mut Vec<Map<String, JsonValue>>) {
+    let parent_of = |row: &Map<String, JsonValue>| row.get("parent_page_id").and_then(|v| v.as_str()).map(String::from);
+    let id_of = |row: &Map<String, JsonValue>| row.get("id").and_then(|v| v.as_str()).map(String::from);
+
+    let mut pending: HashSet<String> = rows.iter().filter_map(id_of).collect();
+    let mut remaining = std::mem::take(rows);
+    while !remaining.is_empty() {
+        let (ready, waiting): (Vec<_>, Vec<_>) = remaining
+            .into_iter()
+            .partition(|row| parent_of(row).map_or(true, |parent| !pending.contains(&parent)));
+        if ready.is_empty() {
+            // A cycle cannot be saved by the page functions; keep the rows as they are
+            rows.extend(waiting);
+            return;
+        }
+        for row in &ready {
+            if let Some(id) = id_of(row) {
+                pending.remove(&id);
+            }
+        }
+        rows.extend(ready);
+        remaining = waiting;
+    }
+}
+
+// ============================================
+// Browsing the Trash
+// ============================================
+
+/// The user's trash, most recently deleted first
+pub fn get_trash(conn: &Connection, user_id: &str) -> Result<Vec<TrashItem>, String> {
+    let retention_days = get_trash_retention_days(conn)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, item_type, item_id, name, parent_id, deleted_at FROM trash
+             WHERE user_id = ?1 ORDER BY deleted_at DESC",
+        )
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let items = stmt
+        .query_map(params![user_id], |row| read_trash_item(row, retention_days))
+        .map_err(|e| format!("Failed to query trash: {}", e))?;
+
+    items
+        .filter_map(|item| item.transpose())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect trash: {}", e))
+}
+
+fn get_trash_item(conn: &Connection, user_id: &str, id: &str) -> Result<Option<TrashItem>, String> {
+    let retention_days = get_trash_retention_days(conn)?;
+    match conn.query_row(
+        "SELECT id, item_type, item_id, name, parent_id, deleted_at FROM trash
+         WHERE id = ?1 AND user_id = ?2",
+        params![id, user_id],
+        |row| read_trash_item(row, retention_days),
+    ) {
+        Ok(item) => Ok(item),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(format!("Failed to get trash item: {}", e)),
+    }
+}
+
+/// A trash row, or None if it holds a type this version does not know
+fn read_trash_item(row: &rusqlite::Row, retention_days: i64) -> rusqlite::Result<Option<TrashItem>> {
+    let item_type: String = row.get(1)?;
+    let Some(item_type) = TrashItemType::parse(&item_type) else {
+        return Ok(None);
+    };
+    let deleted_at: String = row.get(5)?;
+    let purge_at = DateTime::parse_from_rfc3339(&deleted_at)
+        .map(|t| (t.with_timezone(&Utc) + Duration::days(retention_days)).to_rfc3339())
+        .unwrap_or_default();
+
+    Ok(Some(TrashItem {
+        id: row.get(0)?,
+        item_type,
+        item_id: row.get(2)?,
+        name: row.get(3)?,
+        parent_id: row.get(4)?,
+        deleted_at,
+        purge_at,
+    }))
+}
+
+// ============================================
+// Restoring
+// ============================================
+
+/// Put a trashed item back with everything deleted along with it. A card or
+/// page needs its deck or notebook back first; a course or notebook whose
+/// name has since been taken is restored under a numbered name.
+pub fn restore_from_trash(conn: &Connection, user_id: &str, trash_id: &str) -> Result<TrashRestoreResult, String> {
+    in_transaction(conn, || restore_snapshot(conn, user_id, trash_id))
+}
+
+fn restore_snapshot(conn: &Connection, user_id: &str, trash_id: &str) -> Result<TrashRestoreResult, String> {
+    let item = get_trash_item(conn, user_id, trash_id)?
+        .ok_or_else(|| format!("Trash item not found: {}", trash_id))?;
+    let snapshot: String = conn
+        .query_row("SELECT snapshot FROM trash WHERE id = ?1", params![trash_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to read trash item: {}", e))?;
+    let mut snapshot: Vec<SnapshotTable> = serde_json::from_str(&snapshot)
+        .map_err(|e| format!("Failed to read deleted content: {}", e))?;
+
+    if let Some(parent_id) = item.parent_id.as_deref() {
+        check_parent_exists(conn, user_id, item.item_type, parent_id)?;
+    }
+
+    // A sub-page whose parent page has gone since comes back at the top level
+    if item.item_type == TrashItemType::Page {
+        if let Some(root) = snapshot.first_mut().and_then(|t| t.rows.first_mut()) {
+            if let Some(parent_page_id) = root.get("parent_page_id").and_then(|v| v.as_str()) {
+                if get_page(conn, parent_page_id)?.is_none() {
+                    root.insert("parent_page_id".to_string(), JsonValue::Null);
+                }
+            }
+        }
+    }
+
+    // Courses and notebooks have unique names
+    let mut name = item.name.clone();
+    let unique_table = match item.item_type {
+        TrashItemType::Course => Some("courses"),
+        TrashItemType::Notebook => Some("notebooks"),
+        _ => None,
+    };
+    if let Some(table) = unique_table {
+        name = get_unique_name(conn, table, user_id, &item.name)?;
+        if let Some(root) = snapshot.first_mut().and_then(|t| t.rows.first_mut()) {
+            root.insert("name".to_string(), JsonValue::String(name.clone()));
+        }
+    }
+
+    for (index, table) in snapshot.iter().enumerate() {
+        for row in &table.rows {
+            let is_root = index == 0;
+            insert_row(conn, &table.table, row, is_root)?;
+        }
+    }
+
+    // Links are indexed from page content rather than kept in the trash
+    for table in snapshot.iter().filter(|t| t.table == "pages") {
+        for row in &table.rows {
+            if let Some(JsonValue::String(page_id)) = row.get("id") {
+                link_new_page(conn, page_id)?;
+            }
+        }
+    }
+
+    conn.execute("DELETE FROM trash WHERE id = ?1", params![trash_id])
+        .map_err(|e| format!("Failed to remove trash item: {}", e))?;
+
+    Ok(TrashRestoreResult {
+        item_type: item.item_type,
+        item_id: item.item_id,
+        original_name: (name != item.name).then_some(item.name),
+        name,
+    })
+}
+
+fn check_parent_exists(
+    conn: &Connection,
+    user_id: &str,
+    item_type: TrashItemType,
+    parent_id: &str,
+) -> Result<(), String> {
+    let (parent_type, sql) = match item_type {
+        TrashItemType::Card => ("deck", "SELECT EXISTS(SELECT 1 FROM decks WHERE id = ?1 AND user_id = ?2)"),
+        TrashItemType::Page => ("notebook", "SELECT EXISTS(SELECT 1 FROM notebooks WHERE id = ?1 AND user_id = ?2)"),
+        _ => return Ok(()),
+    };
+    let exists: bool = conn
+        .query_row(sql, params![parent_id, user_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to check {}: {}", parent_type, e))?;
+    if exists {
+        return Ok(());
+    }
+
+    let trashed_parent: Option<String> = conn
+        .query_row(
+            "SELECT name FROM trash WHERE user_id = ?1 AND item_type = ?2 AND item_id = ?3",
+            params![user_id, parent_type, parent_id],
+            |row| row.get(0),
+        )
+        .ok();
+    Err(match trashed_parent {
+        Some(name) => format!("Restore the {} \"{}\" first", parent_type, name),
+        None => format!("The {}'s {} no longer exists", item_type.as_str(), parent_type),
+    })
+}
+
+/// Insert a saved row. Rows below the restored item that point at content
+/// deleted since, like a card's tag, are left out; the item itself must go back.
+fn insert_row(conn: &Connection, table: &str, row: &Map<String, JsonValue>, is_root: bool) -> Result<(), String> {
+    let columns: Vec<&str> = row.keys().map(|k| k.as_str()).collect();
+    let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+    let verb = if KEPT_ON_DELETE.contains(&table) {
+        "INSERT OR REPLACE"
+    } else {
+        "INSERT"
+    };
+    let sql = format!(
+        "{} INTO {} ({}) VALUES ({})",
+        verb,
+        table,
+        columns.join(", "),
+        placeholders.join(", ")
+    );
+
+    match conn.execute(&sql, params_from_iter(row.values().map(from_json))) {
+        Ok(_) => Ok(()),
+        Err(rusqlite::Error::SqliteFailure(e, _))
+            if !is_root && e.code == ErrorCode::ConstraintViolation =>
+        {
+            Ok(())
+        }
+        Err(e) => Err(format!("Failed to restore {}: {}", table, e)),
+    }
+}
+
+/// The name, or the name with the lowest free number after it
+fn get_unique_name(conn: &Connection, table: &str, user_id: &str, base_name: &str) -> Result<String, String> {
+    let sql = format!("SELECT EXISTS(SELECT 1 FROM {} WHERE user_id = ?1 AND name = ?2)", table);
+    let taken = |name: &str| -> Result<bool, String> {
+        conn.query_row(&sql, params![user_id, name], |row| row.get(0))
+            .map_err(|e| format!("Failed to check name: {}", e))
+    };
+
+    if !taken(base_name)? {
+        return Ok(base_name.to_string());
+    }
+    for i in 2..=100 {
+        let candidate = format!("{} {}", base_name, i);
+        if !taken(&candidate)? {
+            return Ok(candidate);
+        }
+    }
+    Ok(format!("{} {}", base_name, Utc::now().timestamp()))
+}
+
+// ============================================
+// Purging
+// ============================================
+
+/// Delete a trashed item for good
+pub fn delete_from_trash(conn: &Connection, user_id: &str, trash_id: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM trash WHERE id = ?1 AND user_id = ?2",
+        params![trash_id, user_id],
+    )
+    .map_err(|e| format!("Failed to delete trash item: {}", e))?;
+    Ok(())
+}
+
+pub fn empty_trash(conn: &Connection, user_id: &str) -> Result<usize, String> {
+    conn.execute("DELETE FROM trash WHERE user_id = ?1", params![user_id])
+        .map_err(|e| format!("Failed to empty trash: {}", e))
+}
+
+/// Delete everything trashed longer ago than the retention period
+pub fn purge_expired_trash(conn: &Connection) -> Result<usize, String> {
+    let cutoff = Utc::now() - Duration::days(get_trash_retention_days(conn)?);
+    conn.execute(
+        "DELETE FROM trash WHERE deleted_at < ?1",
+        params![cutoff.to_rfc3339()],
+    )
+    .map_err(|e| format!("Failed to purge trash: {}", e))
+}
+
+pub fn get_trash_retention_days(conn: &Connection) -> Result<i64, String> {
+    match conn.query_row(
+        "SELECT value FROM app_state WHERE key = ?1",
+        params![TRASH_RETENTION_KEY],
+        |row| row.get::<_, String>(0),
+    ) {
+        Ok(value) => Ok(value.parse().unwrap_or(DEFAULT_TRASH_RETENTION_DAYS)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(DEFAULT_TRASH_RETENTION_DAYS),
+        Err(e) => Err(format!("Failed to get trash retention: {}", e)),
+    }
+}
+
+/// Keep trashed items for `days` days, purging anything already older
+pub fn set_trash_retention_days(conn: &Connection, days: i64) -> Result<usize, String> {
+    if !(1..=3650).contains(&days) {
+        return Err("Trash retention must be between 1 and 3650 days".to_string());
+    }
+    conn.execute(
+        "INSERT OR REPLACE INTO app_state (key, value) VALUES (?1, ?2)",
+        params![TRASH_RETENTION_KEY, days.to_string()],
+    )
+    .map_err(|e| format!("Failed to set trash retention: {}", e))?;
+
+    purge_expired_trash(conn)
+}
+
+// ============================================
+// Value Conversion
+// ============================================
+
+fn to_json(value: Value) -> JsonValue {
+    match value {
+        Value::Null => JsonValue::Null,
+        Value::Integer(i) => JsonValue::from(i),
+        Value::Real(f) => JsonValue::from(f),
+        Value::Text(s) => JsonValue::String(s),
+        Value::Blob(bytes) => JsonValue::from(bytes),
+    }
+}
+
+fn from_json(value: &JsonValue) -> Value {
+    match value {
+        JsonValue::Null => Value::Null,
+        JsonValue::Bool(b) => Value::Integer(*b as i64),
+        JsonValue::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        JsonValue::String(s) => Value::Text(s.clone()),
+        JsonValue::Array(items) => Value::Blob(items.iter().filter_map(|b| b.as_u64()).map(|b| b as u8).collect()),
+        JsonValue::Object(_) => Value::Null,
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -8,6 +8,14 @@ use db::DbState;
 
 fn init_db(app: &AppHandle) -> Result<(), String> {
     let conn = db::init_database(app)?;
+    // Close quiz attempts left open when the app last quit
+    if let Err(e) = db::cleanup_stale_attempts(&conn, db::STALE_ATTEMPT_HOURS) {
+        eprintln!("Warning: Failed to clean up stale quiz attempts: {}", e);
+    }
+    // Delete trashed items kept past the retention period
+    if let Err(e) = db::purge_expired_trash(&conn) {
+        eprintln!("Warning: Failed to purge expired trash: {}", e);
+    }
     app.manage(DbState(Mutex::new(conn)));
     Ok(())
 }
@@ -45,6 +53,7 @@ pub fn run() {
             commands::decks::create_card,
             commands::decks::update_card,
             commands::decks::delete_card,
+            commands::decks::generate_cards_from_page,
             // Tag commands
             commands::decks::get_tags_for_deck,
             commands::decks::get_tags_for_card,
@@ -61,6 +70,7 @@ pub fn run() {
             commands::courses::create_course,
             commands::courses::update_course,
             commands::courses::delete_course,
+            commands::courses::raise_course_version,
             commands::courses::toggle_course_favorite,
             // Lesson commands
             commands::courses::get_lessons,
@@ -69,6 +79,13 @@ pub fn run() {
             commands::courses::update_lesson,
             commands::courses::delete_lesson,
             commands::courses::reorder_lessons,
+            commands::courses::set_lesson_prerequisites,
+            commands::courses::set_lesson_completion_rule,
+            // Elective group commands
+            commands::courses::get_elective_groups,
+            commands::courses::create_elective_group,
+            commands::courses::update_elective_group,
+            commands::courses::delete_elective_group,
             // Lesson item commands
             commands::courses::get_lesson_items,
             commands::courses::add_lesson_item,
@@ -77,9 +94,22 @@ pub fn run() {
             commands::courses::update_lesson_item_reference,
             // Lesson progress commands
             commands::courses::record_lesson_progress,
+            commands::courses::check_lesson_item_requirement,
             commands::courses::clear_lesson_item_progress,
             commands::courses::get_lesson_progress,
+            commands::courses::get_course_report,
             commands::courses::link_course_items,
+            commands::courses::confirm_course_item_links,
+            // Enrollment commands
+            commands::courses::enroll_in_course,
+            commands::courses::get_course_enrollment,
+            commands::courses::unenroll_from_course,
+            commands::courses::set_lesson_due_day,
+            commands::courses::get_today_plan,
+            // Certificate commands
+            commands::courses::issue_course_certificate,
+            commands::courses::get_certificates,
+            commands::courses::verify_certificate,
             // Quiz tag commands
             commands::quizzes::get_tags_for_quiz,
             commands::quizzes::get_tags_for_question,
@@ -101,12 +131,25 @@ pub fn run() {
             commands::quizzes::delete_question,
             commands::quizzes::reorder_questions,
             commands::quizzes::update_question_choices,
+            // Question pool commands
+            commands::quizzes::get_quiz_pool_rules,
+            commands::quizzes::set_quiz_pool_rules,
             // Quiz attempt commands
             commands::quizzes::start_quiz_attempt,
+            commands::quizzes::get_attempt_questions,
             commands::quizzes::submit_quiz_attempt,
             commands::quizzes::get_quiz_attempt,
             commands::quizzes::get_quiz_attempts,
+            commands::quizzes::save_attempt_answer,
+            commands::quizzes::mark_question_shown,
+            commands::quizzes::get_in_progress_attempts,
+            commands::quizzes::resume_quiz_attempt,
+            commands::quizzes::cleanup_stale_attempts,
+            commands::quizzes::start_retry_attempt,
+            commands::quizzes::answer_adaptive_question,
+            commands::quizzes::get_adaptive_progress,
             commands::quizzes::get_quiz_stats,
+            commands::quizzes::get_quiz_item_analysis,
             // Quiz favorite commands
             commands::quizzes::toggle_quiz_favorite,
             // Study session commands
@@ -120,6 +163,23 @@ pub fn run() {
             commands::transfer::export_quiz_to_json,
             commands::transfer::import_course_from_file,
             commands::transfer::export_course_to_json,
+            commands::transfer::apply_course_update,
+            commands::transfer::import_quiz_from_gift,
+            commands::transfer::export_quiz_to_gift,
+            commands::transfer::import_quiz_from_moodle_xml,
+            commands::transfer::export_quiz_to_moodle_xml,
+            commands::transfer::import_quiz_from_qti,
+            commands::transfer::export_quiz_to_qti,
+            commands::transfer::import_deck_from_markdown,
+            commands::transfer::export_deck_to_markdown,
+            commands::transfer::import_quiz_from_markdown,
+            commands::transfer::export_quiz_to_markdown,
+            commands::transfer::preview_delimited_cards,
+            commands::transfer::import_deck_from_delimited,
+            // Print commands
+            commands::print::export_deck_to_pdf,
+            commands::print::export_quiz_to_pdf,
+            commands::print::export_certificate_to_pdf,
             // TTS commands
             commands::tts::is_piper_installed,
             commands::tts::get_piper_voices,
@@ -143,6 +203,14 @@ pub fn run() {
             commands::notebooks::delete_page,
             commands::notebooks::reorder_pages,
             commands::notebooks::toggle_page_pin,
+            // Revision commands
+            commands::notebooks::get_page_revisions,
+            commands::notebooks::diff_page_revisions,
+            commands::notebooks::restore_page_revision,
+            // Reading commands
+            commands::notebooks::record_page_reading,
+            commands::notebooks::mark_page_read,
+            commands::notebooks::get_page_read,
             // Search commands
             commands::notebooks::search_pages,
             commands::notebooks::get_recent_pages,
@@ -152,6 +220,15 @@ pub fn run() {
             // Backlinks commands
             commands::notebooks::get_backlinks,
             commands::notebooks::get_all_page_titles,
+            commands::notebooks::get_dangling_links,
+            commands::notebooks::get_page_graph,
+            // Trash commands
+            commands::trash::get_trash,
+            commands::trash::restore_from_trash,
+            commands::trash::delete_from_trash,
+            commands::trash::empty_trash,
+            commands::trash::get_trash_retention_days,
+            commands::trash::set_trash_retention_days,
             // Image commands
             commands::images::save_image,
             commands::images::get_image_path,
@@ -54,31 +54,48 @@ export function NotebookSidebar({
 }: NotebookSidebarProps) {
   const [searchQuery, setSearchQuery] = useState("");
 
-  // Sort pages: pinned first, then by position
+  // Sort pages: pinned first, then by position, with sub-pages under their parent
   const sortedPages = useMemo(() => {
-    return [...pages].sort((a, b) => {
-      if (a.isPinned && !b.isPinned) return -1;
-      if (!a.isPinned && b.isPinned) return 1;
-      return a.position - b.position;
-    });
+    const ids = new Set(pages.map((page) => page.id));
+    const children = new Map<string | null, Page[]>();
+    for (const page of pages) {
+      const parentId = page.parentPageId && ids.has(page.parentPageId) ? page.parentPageId : null;
+      children.set(parentId, [...(children.get(parentId) ?? []), page]);
+    }
+
+    const result: { page: Page; depth: number }[] = [];
+    const visit = (parentId: string | null, depth: number) => {
+      const siblings = [...(children.get(parentId) ?? [])].sort((a, b) => {
+        if (a.isPinned && !b.isPinned) return -1;
+        if (!a.isPinned && b.isPinned) return 1;
+        return a.position - b.position;
+      });
+      for (const page of siblings) {
+        result.push({ page, depth });
+        visit(page.id, depth + 1);
+      }
+    };
+    visit(null, 0);
+    return result;
   }, [pages]);
 
   // Filter pages by search query and split into pinned/regular in single pass
   const { pinnedPages, regularPages } = useMemo(() => {
     const query = searchQuery.trim().toLowerCase();
     const pinned: Page[] = [];
-    const regular: Page[] = [];
+    const regular: { page: Page; depth: number }[] = [];
 
-    for (const page of sortedPages) {
+    for (const entry of sortedPages) {
+      const { page } = entry;
       // Apply search filter
       if (query && !page.title.toLowerCase().includes(query)) {
         continue;
       }
-      // Split into pinned/regular
+      // Split into pinned/regular; matches while filtering are listed flat
       if (page.isPinned) {
         pinned.push(page);
       } else {
-        regular.push(page);
+        regular.push(query ? { page, depth: 0 } : entry);
       }
     }
 
@@ -174,10 +191,11 @@ export function NotebookSidebar({
               {pinnedPages.length > 0 && (
                 <div className="text-xs text-[#939293] px-2 mb-1">Pages</div>
               )}
-              {regularPages.map((page) => (
+              {regularPages.map(({ page, depth }) => (
                 <PageListItem
                   key={page.id}
                   page={page}
+                  depth={depth}
                   isSelected={selectedPageId === page.id}
                   onSelect={() => onSelectPage(page)}
                   onDelete={() => onDeletePage(page.id)}
@@ -3,6 +3,8 @@ import type { Page } from "@/types";
 
 interface PageListItemProps {
   page: Page;
+  /** How deeply the page is nested under other pages */
+  depth?: number;
   isSelected: boolean;
   onSelect: () => void;
   onDelete: () => void;
@@ -13,6 +15,7 @@ interface PageListItemProps {
 
 export function PageListItem({
   page,
+  depth = 0,
   isSelected,
   onSelect,
   onDelete,
@@ -30,6 +33,7 @@ export function PageListItem({
           ? "bg-[#ffd866]/20 text-[#ffd866]"
           : "text-[#fcfcfa] hover:bg-[#5b595c]/30"
       }`}
+      style={depth > 0 ? { paddingLeft: `${0.5 + depth}rem` } : undefined}
       onClick={onSelect}
     >
       <svg className="w-4 h-4 flex-shrink-0" fill="none" viewBox="0 0 24 24" stroke="currentColor">
@@ -36,10 +36,12 @@ import type {
   ReorderLessonsRequest,
   ReorderLessonItemsRequest,
   LinkItemsResult,
+  ItemLink,
   CourseImportResult,
   // Notebook types
   Notebook,
   Page,
+  PageTreeNode,
   PageSearchResult,
   CreateNotebookRequest,
   UpdateNotebookRequest,
@@ -281,6 +283,10 @@ export async function deleteCourse(id: string): Promise<void> {
   return invoke("delete_course", { id });
 }
 
+export async function raiseCourseVersion(id: string, version?: number): Promise<Course> {
+  return invoke<Course>("raise_course_version", { id, version });
+}
+
 export async function toggleCourseFavorite(courseId: string): Promise<boolean> {
   return invoke<boolean>("toggle_course_favorite", { courseId });
 }
@@ -371,6 +377,10 @@ export async function linkCourseItems(courseId: string): Promise<LinkItemsResult
   return invoke<LinkItemsResult>("link_course_items", { courseId });
 }
 
+export async function confirmCourseItemLinks(courseId: string, links: ItemLink[]): Promise<number> {
+  return invoke<number>("confirm_course_item_links", { courseId, request: { links } });
+}
+
 // ============================================
 // Quiz Tag Operations
 // ============================================
@@ -637,8 +647,8 @@ export async function toggleNotebookFavorite(notebookId: string): Promise<boolea
 // Page Operations
 // ============================================
 
-export async function getPagesForNotebook(notebookId: string): Promise<Page[]> {
-  return invoke<Page[]>("get_pages_for_notebook", { notebookId });
+export async function getPagesForNotebook(notebookId: string): Promise<PageTreeNode[]> {
+  return invoke<PageTreeNode[]>("get_pages_for_notebook", { notebookId });
 }
 
 export async function getPage(id: string): Promise<Page | null> {
@@ -653,8 +663,8 @@ export async function updatePage(id: string, request: UpdatePageRequest): Promis
   return invoke<Page>("update_page", { id, request });
 }
 
-export async function deletePage(id: string): Promise<void> {
-  return invoke("delete_page", { id });
+export async function deletePage(id: string, promoteChildren?: boolean): Promise<void> {
+  return invoke("delete_page", { id, promoteChildren });
 }
 
 export async function reorderPages(notebookId: string, request: ReorderPagesRequest): Promise<void> {
@@ -692,9 +702,10 @@ export async function duplicatePage(pageId: string): Promise<Page> {
 
 export async function movePage(
   pageId: string,
-  targetNotebookId: string
+  targetNotebookId: string,
+  parentPageId?: string
 ): Promise<Page> {
-  return invoke<Page>("move_page", { pageId, targetNotebookId });
+  return invoke<Page>("move_page", { pageId, targetNotebookId, parentPageId });
 }
 
 // ============================================
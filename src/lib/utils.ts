@@ -1,3 +1,5 @@
+import type { Page, PageTreeNode } from "@/types";
+
 /**
  * Fisher-Yates (Knuth) shuffle algorithm.
  * Returns a new shuffled array without modifying the original.
@@ -11,3 +13,28 @@ export function shuffle<T>(array: T[]): T[] {
   }
   return result;
 }
+
+/**
+ * Flatten a page tree into a list, each page followed by its sub-pages.
+ */
+export function flattenPageTree(nodes: PageTreeNode[]): Page[] {
+  return nodes.flatMap(({ children, ...page }) => [page, ...flattenPageTree(children)]);
+}
+
+/**
+ * Remove a page and every page nested under it, as deleting or moving it does.
+ */
+export function removePageSubtree(pages: Page[], pageId: string): Page[] {
+  const removed = new Set([pageId]);
+  let found = true;
+  while (found) {
+    found = false;
+    for (const page of pages) {
+      if (page.parentPageId && removed.has(page.parentPageId) && !removed.has(page.id)) {
+        removed.add(page.id);
+        found = true;
+      }
+    }
+  }
+  return pages.filter((page) => !removed.has(page.id));
+}
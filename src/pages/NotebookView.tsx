@@ -12,6 +12,7 @@ import {
   duplicatePage,
   movePage,
 } from "@/lib/db";
+import { flattenPageTree, removePageSubtree } from "@/lib/utils";
 import { LoadingSpinner } from "@/components";
 import { useToast } from "@/context/ToastContext";
 import { useSettings } from "@/context/SettingsContext";
@@ -117,10 +118,12 @@ export function NotebookView() {
     if (!notebookId) return;
 
     try {
-      const [notebookData, pagesData] = await Promise.all([
+      const [notebookData, pageTree] = await Promise.all([
         getNotebook(notebookId),
         getPagesForNotebook(notebookId),
       ]);
+      // Sub-pages are listed after their parent so every page can be opened
+      const pagesData = flattenPageTree(pageTree);
 
       if (!notebookData) {
         toast.error("Notebook not found");
@@ -199,10 +202,10 @@ export function NotebookView() {
   const handleDeletePage = async (pageIdToDelete: string) => {
     try {
       await deletePage(pageIdToDelete);
-      const newPages = pages.filter((p) => p.id !== pageIdToDelete);
+      const newPages = removePageSubtree(pages, pageIdToDelete);
       setPages(newPages);
 
-      if (selectedPage?.id === pageIdToDelete) {
+      if (selectedPage && !newPages.some((p) => p.id === selectedPage.id)) {
         if (newPages.length > 0) {
           handleSelectPage(newPages[0]);
         } else {
@@ -248,12 +251,12 @@ export function NotebookView() {
 
     try {
       await movePage(moveModalPage.id, targetNotebookId);
-      // Remove from current pages list
-      const newPages = pages.filter((p) => p.id !== moveModalPage.id);
+      // Remove it and its sub-pages from the current pages list
+      const newPages = removePageSubtree(pages, moveModalPage.id);
       setPages(newPages);
 
-      // If the moved page was selected, select another
-      if (selectedPage?.id === moveModalPage.id) {
+      // If the moved page or one of its sub-pages was selected, select another
+      if (selectedPage && !newPages.some((p) => p.id === selectedPage.id)) {
         if (newPages.length > 0) {
           handleSelectPage(newPages[0]);
         } else {
@@ -53,6 +53,8 @@ export interface Card {
   backType: ContentType;
   backLanguage: CodeLanguage | null;
   notes: string | null;
+  /** The notebook page the card was generated from */
+  sourcePageId: string | null;
   tags: Tag[];
   createdAt: string;
   updatedAt: string;
@@ -513,9 +515,33 @@ export interface ReorderLessonItemsRequest {
   itemIds: string[];
 }
 
+export type LinkMatch = "content_id" | "exact_name" | "normalized_name" | "fuzzy";
+
+export interface LinkCandidate {
+  itemId: string;
+  name: string;
+  notebookName?: string;
+  matchType: LinkMatch;
+  confidence: number;
+}
+
+export interface UnlinkedItem {
+  lessonItemId: string;
+  lessonId: string;
+  lessonTitle: string;
+  itemType: LessonItemType;
+  itemName: string;
+  candidates: LinkCandidate[];
+}
+
 export interface LinkItemsResult {
   itemsLinked: number;
-  itemsNotFound: string[];
+  unlinkedItems: UnlinkedItem[];
+}
+
+export interface ItemLink {
+  lessonItemId: string;
+  itemId: string;
 }
 
 export interface CourseImportResult {
@@ -549,10 +575,16 @@ export interface Page {
   content: string;
   position: number;
   isPinned: boolean;
+  parentPageId: string | null;
   createdAt: string;
   updatedAt: string;
 }
 
+/** A page with its sub-pages, as returned for a notebook */
+export interface PageTreeNode extends Page {
+  children: PageTreeNode[];
+}
+
 export interface PageSearchResult {
   id: string;
   notebookId: string;
@@ -579,6 +611,7 @@ export interface CreatePageRequest {
   title: string;
   content?: string;
   position?: number;
+  parentPageId?: string;
 }
 
 export interface UpdatePageRequest {
@@ -589,6 +622,8 @@ export interface UpdatePageRequest {
 
 export interface ReorderPagesRequest {
   pageIds: string[];
+  /** The parent whose sub-pages are reordered; omit for top-level pages */
+  parentPageId?: string;
 }
 
 export type NotesViewMode = 'card' | 'list';